@@ -0,0 +1,445 @@
+//! A counter specialized for bytes.
+
+use compat::fmt;
+use compat::hash::BuildHasher;
+use compat::iter;
+use compat::ops::{Add, AddAssign, BitAnd, BitOr, Index, IndexMut, Sub, SubAssign};
+use compat::vec::Vec;
+
+use super::Counter;
+
+/// The number of separate tables [`ByteCounter::update_slice`] tallies into.
+const LANES: usize = 4;
+
+/// The most bytes [`ByteCounter::update_slice`] tallies into its `u32` lanes before adding
+/// them to the `u64` counts, small enough that no lane can overflow.
+const BLOCK: usize = 1 << 30;
+
+/// A counter of `u8` keys, backed by a table of 256 counts rather than a `HashMap`.
+///
+/// Counting a byte is a single array increment, which makes this far faster than a
+/// `Counter<u8>` for byte-frequency work. Counts are `u64`, so it can count the bytes of
+/// files larger than 4GB even on 32-bit platforms.
+///
+/// Bytes with a count of zero are treated as absent: they are skipped by iteration and by
+/// `len`, just as a `Counter` drops entries which reach zero.
+///
+/// ```rust
+/// # use counter::ByteCounter;
+/// let counter = b"abracadabra".iter().cloned().collect::<ByteCounter>();
+/// assert_eq!(counter[&b'a'], 5);
+/// assert_eq!(counter[&b'z'], 0);
+/// assert_eq!(counter.most_common()[..2], [(b'a', 5), (b'b', 2)]);
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ByteCounter {
+    counts: [u64; 256],
+}
+
+impl ByteCounter {
+    /// Create a new, empty `ByteCounter`
+    pub fn new() -> ByteCounter {
+        ByteCounter { counts: [0; 256] }
+    }
+
+    /// Create a new `ByteCounter` initialized with the given iterable
+    pub fn init<I>(iterable: I) -> ByteCounter
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut counter = ByteCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the bytes from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        for byte in iterable.into_iter() {
+            self.counts[byte as usize] += 1;
+        }
+    }
+
+    /// Create a new `ByteCounter` of the bytes in `bytes`
+    ///
+    /// This is much faster than [`init`](#method.init) on large inputs; see
+    /// [`update_slice`](#method.update_slice).
+    ///
+    /// ```rust
+    /// # use counter::ByteCounter;
+    /// let counter = ByteCounter::from_slice(b"mississippi");
+    /// assert_eq!(counter[&b's'], 4);
+    /// assert_eq!(counter.total(), 11);
+    /// ```
+    pub fn from_slice(bytes: &[u8]) -> ByteCounter {
+        let mut counter = ByteCounter::new();
+        counter.update_slice(bytes);
+        counter
+    }
+
+    /// Add the counts of the bytes in `bytes` to this counter
+    ///
+    /// Consecutive increments of the same count depend on each other, which stalls the CPU on
+    /// data with long runs of one byte. This spreads the bytes over several tables of `u32`
+    /// counts, so that neighbouring bytes never touch the same count, and sums the tables at
+    /// the end, which on skewed data is several times faster than counting byte by byte.
+    pub fn update_slice(&mut self, bytes: &[u8]) {
+        for block in bytes.chunks(BLOCK) {
+            let mut lanes = [[0u32; 256]; LANES];
+            let mut chunks = block.chunks_exact(LANES);
+            for chunk in &mut chunks {
+                for (lane, &byte) in lanes.iter_mut().zip(chunk) {
+                    lane[byte as usize] += 1;
+                }
+            }
+            for &byte in chunks.remainder() {
+                lanes[0][byte as usize] += 1;
+            }
+            for lane in &lanes {
+                for (count, &lane_count) in self.counts.iter_mut().zip(lane.iter()) {
+                    *count += u64::from(lane_count);
+                }
+            }
+        }
+    }
+
+    /// Create a new `ByteCounter` of every byte read from `reader`
+    ///
+    /// The reader is read in blocks into a fixed buffer and counted with
+    /// [`update_slice`](#method.update_slice), so files of any size take constant memory.
+    /// Requires the `std` feature.
+    ///
+    /// ```rust
+    /// # use counter::ByteCounter;
+    /// let counter = ByteCounter::from_reader(&b"abracadabra"[..]).unwrap();
+    /// assert_eq!(counter[&b'a'], 5);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: ::std::io::Read>(mut reader: R) -> ::std::io::Result<ByteCounter> {
+        use std::io::ErrorKind;
+
+        let mut counter = ByteCounter::new();
+        let mut buffer = vec![0; 1 << 16];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(counter),
+                Ok(len) => counter.update_slice(&buffer[..len]),
+                Err(ref error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Remove the counts of the bytes from the given iterable from this counter
+    ///
+    /// Counts stop at zero rather than going negative.
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        for byte in iterable.into_iter() {
+            let count = &mut self.counts[byte as usize];
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// The number of distinct bytes with a non-zero count.
+    pub fn len(&self) -> usize {
+        self.counts.iter().filter(|&&count| count != 0).count()
+    }
+
+    /// Whether every count is zero.
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Iterate over `(byte, count)` pairs with a non-zero count, in byte order.
+    ///
+    /// ```rust
+    /// # use counter::ByteCounter;
+    /// let counter = ByteCounter::init(b"cab".iter().cloned());
+    /// assert_eq!(counter.iter().collect::<Vec<_>>(), vec![(b'a', 1), (b'b', 1), (b'c', 1)]);
+    /// ```
+    pub fn iter(&self) -> ByteIter<'_> {
+        ByteIter {
+            counts: self.counts.iter().enumerate(),
+        }
+    }
+
+    /// Create a vector of `(byte, frequency)` pairs, sorted most to least common.
+    ///
+    /// Bytes with equal counts appear in ascending order.
+    pub fn most_common(&self) -> Vec<(u8, u64)> {
+        let mut items = self.iter().collect::<Vec<_>>();
+        // the sort is stable, so ties stay in byte order
+        items.sort_by_key(|&(_, count)| ::compat::cmp::Reverse(count));
+        items
+    }
+
+    /// Borrow the whole table of counts, indexed by byte.
+    pub fn as_array(&self) -> &[u64; 256] {
+        &self.counts
+    }
+}
+
+/// An iterator over the `(byte, count)` pairs of a [`ByteCounter`](struct.ByteCounter.html).
+pub struct ByteIter<'a> {
+    counts: iter::Enumerate<::compat::slice::Iter<'a, u64>>,
+}
+
+impl<'a> Iterator for ByteIter<'a> {
+    type Item = (u8, u64);
+
+    fn next(&mut self) -> Option<(u8, u64)> {
+        self.counts
+            .find(|&(_, &count)| count != 0)
+            .map(|(byte, &count)| (byte as u8, count))
+    }
+}
+
+impl<'a> IntoIterator for &'a ByteCounter {
+    type Item = (u8, u64);
+    type IntoIter = ByteIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Default for ByteCounter {
+    fn default() -> ByteCounter {
+        ByteCounter::new()
+    }
+}
+
+impl fmt::Debug for ByteCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> Index<&'a u8> for ByteCounter {
+    type Output = u64;
+
+    /// Index in immutable contexts; absent bytes have a count of zero.
+    fn index(&self, byte: &'a u8) -> &u64 {
+        &self.counts[*byte as usize]
+    }
+}
+
+impl<'a> IndexMut<&'a u8> for ByteCounter {
+    /// Index in mutable contexts
+    fn index_mut(&mut self, byte: &'a u8) -> &mut u64 {
+        &mut self.counts[*byte as usize]
+    }
+}
+
+impl AddAssign for ByteCounter {
+    /// Add another counter to this counter
+    ///
+    /// `c += d;` -> `c[x] += d[x]` for all `x`
+    fn add_assign(&mut self, rhs: ByteCounter) {
+        for (count, other) in self.counts.iter_mut().zip(rhs.counts.iter()) {
+            *count += *other;
+        }
+    }
+}
+
+impl Add for ByteCounter {
+    type Output = ByteCounter;
+
+    /// Add two counters together.
+    ///
+    /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`
+    fn add(mut self, rhs: ByteCounter) -> ByteCounter {
+        self += rhs;
+        self
+    }
+}
+
+impl SubAssign for ByteCounter {
+    /// Subtract (keeping only positive values).
+    ///
+    /// `c -= d;` -> `c[x] = max(c[x] - d[x], 0)` for all `x`
+    fn sub_assign(&mut self, rhs: ByteCounter) {
+        for (count, other) in self.counts.iter_mut().zip(rhs.counts.iter()) {
+            *count = count.saturating_sub(*other);
+        }
+    }
+}
+
+impl Sub for ByteCounter {
+    type Output = ByteCounter;
+
+    /// Subtract (keeping only positive values).
+    ///
+    /// `out = c - d;` -> `out[x] == max(c[x] - d[x], 0)` for all `x`
+    fn sub(mut self, rhs: ByteCounter) -> ByteCounter {
+        self -= rhs;
+        self
+    }
+}
+
+impl BitAnd for ByteCounter {
+    type Output = ByteCounter;
+
+    /// Intersection
+    ///
+    /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+    fn bitand(mut self, rhs: ByteCounter) -> ByteCounter {
+        for (count, other) in self.counts.iter_mut().zip(rhs.counts.iter()) {
+            *count = (*count).min(*other);
+        }
+        self
+    }
+}
+
+impl BitOr for ByteCounter {
+    type Output = ByteCounter;
+
+    /// Union
+    ///
+    /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+    fn bitor(mut self, rhs: ByteCounter) -> ByteCounter {
+        for (count, other) in self.counts.iter_mut().zip(rhs.counts.iter()) {
+            *count = (*count).max(*other);
+        }
+        self
+    }
+}
+
+impl<I> AddAssign<I> for ByteCounter
+where
+    I: IntoIterator<Item = u8>,
+{
+    /// Directly add the counts of the bytes of `I` to `self`
+    fn add_assign(&mut self, rhs: I) {
+        self.update(rhs);
+    }
+}
+
+impl<I> SubAssign<I> for ByteCounter
+where
+    I: IntoIterator<Item = u8>,
+{
+    /// Directly subtract the counts of the bytes of `I` from `self`
+    fn sub_assign(&mut self, rhs: I) {
+        self.subtract(rhs);
+    }
+}
+
+impl iter::FromIterator<u8> for ByteCounter {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> ByteCounter {
+        ByteCounter::init(iter)
+    }
+}
+
+impl Extend<u8> for ByteCounter {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl From<ByteCounter> for Counter<u8, u64> {
+    /// Convert to a generic counter, which holds only the bytes with a non-zero count.
+    fn from(counter: ByteCounter) -> Counter<u8, u64> {
+        counter.iter().collect()
+    }
+}
+
+impl<S> From<Counter<u8, u64, S>> for ByteCounter
+where
+    S: BuildHasher,
+{
+    /// Convert from a generic counter.
+    ///
+    /// ```rust
+    /// # use counter::{ByteCounter, Counter};
+    /// let counter = "hello".bytes().collect::<Counter<_, u64>>();
+    /// let bytes = ByteCounter::from(counter.clone());
+    /// assert_eq!(bytes[&b'l'], 2);
+    /// assert_eq!(Counter::from(bytes), counter);
+    /// ```
+    fn from(counter: Counter<u8, u64, S>) -> ByteCounter {
+        let mut bytes = ByteCounter::new();
+        for (byte, count) in counter {
+            bytes.counts[byte as usize] = count;
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_slice_matches_update() {
+        let bytes = (0..10_007u32)
+            .map(|i| (i * i % 251) as u8)
+            .collect::<Vec<_>>();
+        for len in [0, 1, 3, 4, 5, 10_007] {
+            let fast = ByteCounter::from_slice(&bytes[..len]);
+            assert!(fast == ByteCounter::init(bytes[..len].iter().cloned()));
+        }
+        let mut counter = ByteCounter::from_slice(&[7; 9]);
+        counter.update_slice(&[7, 8]);
+        assert_eq!(counter[&7], 10);
+        assert_eq!(counter.len(), 2);
+    }
+
+    #[test]
+    fn test_update_and_subtract() {
+        let mut counter = ByteCounter::new();
+        counter += b"aabc".iter().cloned();
+        counter -= b"bbd".iter().cloned();
+        assert_eq!(
+            counter.iter().collect::<Vec<_>>(),
+            vec![(b'a', 2), (b'c', 1)]
+        );
+        assert_eq!(counter.len(), 2);
+        assert_eq!(counter.total(), 3);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let c = ByteCounter::init(b"abbccc".iter().cloned());
+        let d = ByteCounter::init(b"bccddd".iter().cloned());
+
+        let sum = c.clone() + d.clone();
+        assert_eq!(
+            sum.most_common(),
+            vec![(b'c', 5), (b'b', 3), (b'd', 3), (b'a', 1)]
+        );
+        assert_eq!(
+            (c.clone() - d.clone()).iter().collect::<Vec<_>>(),
+            vec![(b'a', 1), (b'b', 1), (b'c', 1)]
+        );
+        assert_eq!((c.clone() & d.clone()).total(), 3);
+        assert_eq!((c | d).total(), 9);
+    }
+
+    #[test]
+    fn test_all_bytes() {
+        let counter = (0..=255).collect::<ByteCounter>();
+        assert_eq!(counter.len(), 256);
+        assert_eq!(counter[&0], 1);
+        assert_eq!(counter[&255], 1);
+        assert!(ByteCounter::new().is_empty());
+    }
+
+    #[test]
+    fn test_matches_generic_counter() {
+        let text = b"the quick brown fox jumps over the lazy dog";
+        let generic = text.iter().cloned().collect::<Counter<_, u64>>();
+        let bytes = text.iter().cloned().collect::<ByteCounter>();
+        assert_eq!(Counter::from(bytes), generic);
+    }
+}
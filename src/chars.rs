@@ -0,0 +1,380 @@
+//! A counter specialized for chars, with a fast path for ASCII.
+
+use compat::collections::{hash_map, HashMap};
+use compat::fmt;
+use compat::hash::BuildHasher;
+use compat::iter;
+use compat::ops::{Add, AddAssign, BitAnd, BitOr, Index, Sub, SubAssign};
+use compat::vec::Vec;
+
+use super::Counter;
+
+/// A counter of `char` keys which counts ASCII in a table of 128 counts, and everything else in
+/// a `HashMap`.
+///
+/// Most text is mostly ASCII, so most updates are a single array increment rather than a hash
+/// and a lookup. Apart from its storage, it behaves like a `Counter<char>`: counts are `usize`,
+/// and chars with a count of zero are treated as absent.
+///
+/// ```rust
+/// # use counter::CharCounter;
+/// let counter = "naïve café".chars().collect::<CharCounter>();
+/// assert_eq!(counter[&'a'], 2);
+/// assert_eq!(counter[&'é'], 1);
+/// assert_eq!(counter.most_common()[0], ('a', 2));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct CharCounter {
+    ascii: [usize; 128],
+    other: HashMap<char, usize>,
+}
+
+impl CharCounter {
+    /// Create a new, empty `CharCounter`
+    pub fn new() -> CharCounter {
+        CharCounter {
+            ascii: [0; 128],
+            other: HashMap::new(),
+        }
+    }
+
+    /// Create a new `CharCounter` initialized with the given iterable
+    pub fn init<I>(iterable: I) -> CharCounter
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut counter = CharCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the chars from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        for c in iterable.into_iter() {
+            if c.is_ascii() {
+                self.ascii[c as usize] += 1;
+            } else {
+                *self.other.entry(c).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Remove the counts of the chars from the given iterable from this counter
+    ///
+    /// Non-positive counts are automatically removed
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = char>,
+    {
+        for c in iterable.into_iter() {
+            self.sub_count(c, 1);
+        }
+    }
+
+    /// The number of distinct chars with a non-zero count.
+    pub fn len(&self) -> usize {
+        self.ascii.iter().filter(|&&count| count != 0).count() + self.other.len()
+    }
+
+    /// Whether every count is zero.
+    pub fn is_empty(&self) -> bool {
+        self.other.is_empty() && self.ascii.iter().all(|&count| count == 0)
+    }
+
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> usize {
+        self.ascii.iter().sum::<usize>() + self.other.values().sum::<usize>()
+    }
+
+    /// Iterate over `(char, count)` pairs with a non-zero count.
+    ///
+    /// ASCII chars come first, in ascending order; the rest follow in arbitrary order.
+    pub fn iter(&self) -> CharIter<'_> {
+        CharIter {
+            ascii: self.ascii.iter().enumerate(),
+            other: self.other.iter(),
+        }
+    }
+
+    /// Create a vector of `(char, frequency)` pairs, sorted most to least common.
+    ///
+    /// Chars with equal counts appear in ascending order.
+    pub fn most_common(&self) -> Vec<(char, usize)> {
+        let mut items = self.iter().collect::<Vec<_>>();
+        items.sort_by(|&(a, a_count), &(b, b_count)| b_count.cmp(&a_count).then(a.cmp(&b)));
+        items
+    }
+
+    fn get(&self, c: &char) -> &usize {
+        if c.is_ascii() {
+            &self.ascii[*c as usize]
+        } else {
+            self.other.get(c).unwrap_or(&0)
+        }
+    }
+
+    fn add_count(&mut self, c: char, count: usize) {
+        if c.is_ascii() {
+            self.ascii[c as usize] += count;
+        } else if count != 0 {
+            *self.other.entry(c).or_insert(0) += count;
+        }
+    }
+
+    fn sub_count(&mut self, c: char, count: usize) {
+        if c.is_ascii() {
+            let entry = &mut self.ascii[c as usize];
+            *entry = entry.saturating_sub(count);
+        } else if let hash_map::Entry::Occupied(mut entry) = self.other.entry(c) {
+            if *entry.get() > count {
+                *entry.get_mut() -= count;
+            } else {
+                entry.remove();
+            }
+        }
+    }
+}
+
+/// An iterator over the `(char, count)` pairs of a [`CharCounter`](struct.CharCounter.html).
+pub struct CharIter<'a> {
+    ascii: iter::Enumerate<::compat::slice::Iter<'a, usize>>,
+    other: hash_map::Iter<'a, char, usize>,
+}
+
+impl<'a> Iterator for CharIter<'a> {
+    type Item = (char, usize);
+
+    fn next(&mut self) -> Option<(char, usize)> {
+        self.ascii
+            .find(|&(_, &count)| count != 0)
+            .map(|(c, &count)| (c as u8 as char, count))
+            .or_else(|| self.other.next().map(|(&c, &count)| (c, count)))
+    }
+}
+
+impl<'a> IntoIterator for &'a CharCounter {
+    type Item = (char, usize);
+    type IntoIter = CharIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Default for CharCounter {
+    fn default() -> CharCounter {
+        CharCounter::new()
+    }
+}
+
+impl fmt::Debug for CharCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> Index<&'a char> for CharCounter {
+    type Output = usize;
+
+    /// Index in immutable contexts; absent chars have a count of zero.
+    fn index(&self, c: &'a char) -> &usize {
+        self.get(c)
+    }
+}
+
+impl AddAssign for CharCounter {
+    /// Add another counter to this counter
+    ///
+    /// `c += d;` -> `c[x] += d[x]` for all `x`
+    fn add_assign(&mut self, rhs: CharCounter) {
+        for (c, count) in rhs.iter() {
+            self.add_count(c, count);
+        }
+    }
+}
+
+impl Add for CharCounter {
+    type Output = CharCounter;
+
+    /// Add two counters together.
+    ///
+    /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`
+    fn add(mut self, rhs: CharCounter) -> CharCounter {
+        self += rhs;
+        self
+    }
+}
+
+impl SubAssign for CharCounter {
+    /// Subtract (keeping only positive values).
+    ///
+    /// `c -= d;` -> `c[x] -= d[x]` for all `x`,
+    /// keeping only items with a value greater than zero.
+    fn sub_assign(&mut self, rhs: CharCounter) {
+        for (c, count) in rhs.iter() {
+            self.sub_count(c, count);
+        }
+    }
+}
+
+impl Sub for CharCounter {
+    type Output = CharCounter;
+
+    /// Subtract (keeping only positive values).
+    ///
+    /// `out = c - d;` -> `out[x] == c[x] - d[x]` for all `x`,
+    /// keeping only items with a value greater than zero.
+    fn sub(mut self, rhs: CharCounter) -> CharCounter {
+        self -= rhs;
+        self
+    }
+}
+
+impl BitAnd for CharCounter {
+    type Output = CharCounter;
+
+    /// Intersection
+    ///
+    /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+    fn bitand(mut self, rhs: CharCounter) -> CharCounter {
+        for (count, other) in self.ascii.iter_mut().zip(rhs.ascii.iter()) {
+            *count = (*count).min(*other);
+        }
+        self.other.retain(|c, count| match rhs.other.get(c) {
+            Some(&other) => {
+                *count = (*count).min(other);
+                true
+            }
+            None => false,
+        });
+        self
+    }
+}
+
+impl BitOr for CharCounter {
+    type Output = CharCounter;
+
+    /// Union
+    ///
+    /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+    fn bitor(mut self, rhs: CharCounter) -> CharCounter {
+        for (count, other) in self.ascii.iter_mut().zip(rhs.ascii.iter()) {
+            *count = (*count).max(*other);
+        }
+        for (c, other) in rhs.other {
+            let count = self.other.entry(c).or_insert(0);
+            *count = (*count).max(other);
+        }
+        self
+    }
+}
+
+impl<I> AddAssign<I> for CharCounter
+where
+    I: IntoIterator<Item = char>,
+{
+    /// Directly add the counts of the chars of `I` to `self`
+    fn add_assign(&mut self, rhs: I) {
+        self.update(rhs);
+    }
+}
+
+impl<I> SubAssign<I> for CharCounter
+where
+    I: IntoIterator<Item = char>,
+{
+    /// Directly subtract the counts of the chars of `I` from `self`,
+    /// keeping only items with a value greater than zero.
+    fn sub_assign(&mut self, rhs: I) {
+        self.subtract(rhs);
+    }
+}
+
+impl iter::FromIterator<char> for CharCounter {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> CharCounter {
+        CharCounter::init(iter)
+    }
+}
+
+impl Extend<char> for CharCounter {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl From<CharCounter> for Counter<char> {
+    fn from(counter: CharCounter) -> Counter<char> {
+        counter.iter().collect()
+    }
+}
+
+impl<S> From<Counter<char, usize, S>> for CharCounter
+where
+    S: BuildHasher,
+{
+    /// Convert from a generic counter.
+    ///
+    /// ```rust
+    /// # use counter::{CharCounter, Counter};
+    /// let counter = "crème brûlée".chars().collect::<Counter<_>>();
+    /// let chars = CharCounter::from(counter.clone());
+    /// assert_eq!(chars[&'e'], 2);
+    /// assert_eq!(chars[&'û'], 1);
+    /// assert_eq!(Counter::from(chars), counter);
+    /// ```
+    fn from(counter: Counter<char, usize, S>) -> CharCounter {
+        let mut chars = CharCounter::new();
+        for (c, count) in counter {
+            chars.add_count(c, count);
+        }
+        chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mixed_text() {
+        let mut counter = CharCounter::init("über uns".chars());
+        assert_eq!(counter[&'u'], 1);
+        assert_eq!(counter[&'ü'], 1);
+        assert_eq!(counter.len(), 8);
+        assert_eq!(counter.total(), 8);
+
+        counter -= "üü".chars();
+        assert_eq!(counter[&'ü'], 0);
+        assert_eq!(counter.len(), 7);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let c = "aééððð".chars().collect::<CharCounter>();
+        let d = "éððddd".chars().collect::<CharCounter>();
+
+        let sum = c.clone() + d.clone();
+        assert_eq!(
+            sum.most_common(),
+            vec![('ð', 5), ('d', 3), ('é', 3), ('a', 1)]
+        );
+        assert_eq!(
+            (c.clone() - d.clone()).most_common(),
+            vec![('a', 1), ('é', 1), ('ð', 1)]
+        );
+        assert_eq!((c.clone() & d.clone()).total(), 3);
+        assert_eq!((c | d).total(), 9);
+    }
+
+    #[test]
+    fn test_matches_generic_counter() {
+        let text = "Ἐν ἀρχῇ ἦν ὁ λόγος, in the beginning was the word";
+        let generic = text.chars().collect::<Counter<_>>();
+        let chars = text.chars().collect::<CharCounter>();
+        assert_eq!(chars.most_common(), generic.most_common_ordered());
+        assert_eq!(Counter::from(chars), generic);
+    }
+}
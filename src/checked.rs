@@ -0,0 +1,290 @@
+//! Counting operations which report overflow instead of panicking or wrapping.
+//!
+//! Intersection and union only ever pick one of the existing counts, so they can't overflow
+//! and have no checked form.
+
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
+
+use compat::collections::hash_map::Entry;
+use compat::error::Error;
+use compat::fmt;
+use compat::hash::{BuildHasher, Hash};
+
+use super::Counter;
+
+/// The error returned by the `checked_*` methods of [`Counter`](struct.Counter.html)
+/// when a count would overflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverflowError;
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("count overflowed")
+    }
+}
+
+impl Error for OverflowError {}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: CheckedAdd + Zero + One,
+    S: BuildHasher,
+{
+    /// Add the counts of the elements from the given iterable to this counter,
+    /// stopping at the first count which would overflow.
+    ///
+    /// On error, the elements preceding the overflowing one have already been counted,
+    /// and the overflowing count is left unchanged.
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// let mut counter: Counter<_, u8> = Counter::new();
+    /// assert_eq!(counter.checked_update("ab".chars()), Ok(()));
+    ///
+    /// counter.checked_add_count('a', 254).unwrap();
+    /// assert_eq!(counter.checked_update("ba".chars()), Err(OverflowError));
+    /// assert_eq!(counter[&'a'], 255);
+    /// assert_eq!(counter[&'b'], 2);
+    /// ```
+    pub fn checked_update<I>(&mut self, iterable: I) -> Result<(), OverflowError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.checked_add_count(item, N::one())?;
+        }
+        Ok(())
+    }
+
+    /// Add `count` to the count of `item`, unless the result would overflow.
+    ///
+    /// As with `add_count`, an item whose count comes to zero is removed. On error, the
+    /// counter is left unchanged.
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// let mut counter: Counter<_, u8> = Counter::new();
+    /// assert_eq!(counter.checked_add_count('a', 200), Ok(()));
+    /// assert_eq!(counter.checked_add_count('a', 100), Err(OverflowError));
+    /// assert_eq!(counter[&'a'], 200);
+    /// ```
+    pub fn checked_add_count(&mut self, item: T, count: N) -> Result<(), OverflowError> {
+        match self.map.entry(item) {
+            Entry::Occupied(mut entry) => {
+                let sum = entry.get().checked_add(&count).ok_or(OverflowError)?;
+                if sum.is_zero() {
+                    entry.remove();
+                } else {
+                    *entry.get_mut() = sum;
+                }
+            }
+            Entry::Vacant(entry) => {
+                if !count.is_zero() {
+                    entry.insert(count);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + CheckedAdd + Zero,
+    S: BuildHasher + Clone,
+{
+    /// Add two counters together, unless any of the resulting counts would overflow.
+    ///
+    /// `out = c.checked_add(&d)?;` -> `out[x] == c[x] + d[x]` for all `x`
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// let c: Counter<_, u8> = "aaab".chars().collect();
+    /// let d: Counter<_, u8> = "abb".chars().collect();
+    /// assert_eq!(c.checked_add(&d), Ok(&c + &d));
+    ///
+    /// let full: Counter<_, u8> = vec![('b', 255)].into_iter().collect();
+    /// assert_eq!(c.checked_add(&full), Err(OverflowError));
+    /// ```
+    pub fn checked_add(&self, rhs: &Counter<T, N, S>) -> Result<Counter<T, N, S>, OverflowError> {
+        let mut counter = self.clone();
+        for (key, value) in rhs.map.iter() {
+            let sum = match counter.map.get(key) {
+                Some(entry) => entry.checked_add(value).ok_or(OverflowError)?,
+                None => value.clone(),
+            };
+            if sum.is_zero() {
+                counter.map.remove(key);
+            } else {
+                counter.map.insert(key.clone(), sum);
+            }
+        }
+        Ok(counter)
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + CheckedSub + PartialOrd + Zero,
+    S: BuildHasher + Clone,
+{
+    /// Subtract one counter from another, keeping only positive values, unless any of the
+    /// differences would overflow.
+    ///
+    /// `out = c.checked_sub(&d)?;` -> `out[x] == c[x] - d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    ///
+    /// Unsigned counts can't overflow this way; signed ones can, when subtracting a negative
+    /// count.
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// # use std::collections::HashMap;
+    /// let c: Counter<_, i8> = "aaab".chars().collect();
+    /// let d: Counter<_, i8> = "abb".chars().collect();
+    /// assert_eq!(c.checked_sub(&d), Ok(&c - &d));
+    ///
+    /// let negative = Counter::from([('a', -127)].iter().cloned().collect::<HashMap<_, i8>>());
+    /// assert_eq!(c.checked_sub(&negative), Err(OverflowError));
+    /// ```
+    pub fn checked_sub(&self, rhs: &Counter<T, N, S>) -> Result<Counter<T, N, S>, OverflowError> {
+        let mut counter = self.clone();
+        for (key, value) in rhs.map.iter() {
+            let difference = match counter.map.get(key) {
+                Some(entry) if *entry >= *value => entry.checked_sub(value).ok_or(OverflowError)?,
+                Some(_) => N::zero(),
+                None => continue,
+            };
+            if difference.is_zero() {
+                counter.map.remove(key);
+            } else {
+                counter.map.insert(key.clone(), difference);
+            }
+        }
+        Ok(counter)
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + CheckedMul + CheckedDiv + Zero,
+    S: BuildHasher + Clone,
+{
+    /// Scale every count by a scalar, unless any of the products would overflow.
+    ///
+    /// `out = c.checked_mul(k)?;` -> `out[x] == c[x] * k` for all `x`, removing items whose
+    /// count becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// let c: Counter<_, u8> = "aab".chars().collect();
+    /// assert_eq!(c.checked_mul(3), Ok(c.clone() * 3));
+    /// assert_eq!(c.checked_mul(200), Err(OverflowError));
+    /// ```
+    pub fn checked_mul(&self, rhs: N) -> Result<Counter<T, N, S>, OverflowError> {
+        self.checked_scale(|count| count.checked_mul(&rhs))
+    }
+
+    /// Divide every count by a scalar, unless any of the quotients would overflow.
+    ///
+    /// `out = c.checked_div(k)?;` -> `out[x] == c[x] / k` for all `x`, removing items whose
+    /// count becomes zero. Dividing by zero is an error too, following
+    /// [`CheckedDiv`](https://docs.rs/num-traits/0.2/num_traits/ops/checked/trait.CheckedDiv.html).
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OverflowError};
+    /// # use std::collections::HashMap;
+    /// let c = Counter::from([('a', -128), ('b', 6)].iter().cloned().collect::<HashMap<_, i8>>());
+    /// assert_eq!(c.checked_div(2), Ok(c.clone() / 2));
+    /// assert_eq!(c.checked_div(-1), Err(OverflowError));
+    /// assert_eq!(c.checked_div(0), Err(OverflowError));
+    /// ```
+    pub fn checked_div(&self, rhs: N) -> Result<Counter<T, N, S>, OverflowError> {
+        self.checked_scale(|count| count.checked_div(&rhs))
+    }
+
+    fn checked_scale<F>(&self, mut scale: F) -> Result<Counter<T, N, S>, OverflowError>
+    where
+        F: FnMut(&N) -> Option<N>,
+    {
+        let mut counter = self.clone();
+        for count in counter.map.values_mut() {
+            *count = scale(count).ok_or(OverflowError)?;
+        }
+        counter.map.retain(|_, count| !count.is_zero());
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_update() {
+        let mut counter: Counter<_, u8> = Counter::new();
+        counter
+            .checked_update(::std::iter::repeat_n('a', 255))
+            .unwrap();
+        assert_eq!(counter[&'a'], 255);
+
+        assert_eq!(counter.checked_update("ba".chars()), Err(OverflowError));
+        assert_eq!(counter[&'a'], 255);
+        assert_eq!(counter[&'b'], 1);
+    }
+
+    #[test]
+    fn test_checked_add_count_zero_does_not_insert() {
+        let mut counter: Counter<char, u8> = Counter::new();
+        counter.checked_add_count('a', 0).unwrap();
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let c: Counter<_, u8> = "abbccc".chars().collect();
+        let d: Counter<_, u8> = "bccddd".chars().collect();
+        assert_eq!(c.checked_add(&d), Ok(Counter::init("abbbcccccddd".chars())));
+
+        let full: Counter<_, u8> = vec![('d', 255)].into_iter().collect();
+        assert_eq!(d.checked_add(&full), Err(OverflowError));
+        assert_eq!(c.checked_add(&full).unwrap()[&'d'], 255);
+    }
+
+    #[test]
+    fn test_checked_zero_sums_are_removed() {
+        let mut counter: Counter<_, i8> = "ab".chars().collect();
+        counter.checked_add_count('a', -1).unwrap();
+        assert!(counter.map == hashmap!{'b' => 1});
+
+        let c: Counter<_, i8> = "ab".chars().collect();
+        let mut d = Counter::from(hashmap!{'a' => -1});
+        d[&'c'] = 0;
+        assert!(c.checked_add(&d).unwrap().map == hashmap!{'b' => 1});
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        let c: Counter<_, u8> = "abbccc".chars().collect();
+        let d: Counter<_, u8> = "bbbccd".chars().collect();
+        assert!(c.checked_sub(&d).unwrap().map == hashmap!{'a' => 1, 'c' => 1});
+
+        let c = Counter::from(hashmap!{'a' => 100i8});
+        let d = Counter::from(hashmap!{'a' => -100i8});
+        assert_eq!(c.checked_sub(&d), Err(OverflowError));
+    }
+
+    #[test]
+    fn test_checked_mul_and_div() {
+        let c: Counter<_, u8> = "abbccc".chars().collect();
+        assert!(c.checked_mul(2).unwrap().map == hashmap!{'a' => 2, 'b' => 4, 'c' => 6});
+        assert!(c.checked_mul(0).unwrap().is_empty());
+        assert_eq!(c.checked_mul(100), Err(OverflowError));
+        assert!(c.checked_div(2).unwrap().map == hashmap!{'b' => 1, 'c' => 1});
+        assert_eq!(c.checked_div(0), Err(OverflowError));
+    }
+}
@@ -0,0 +1,431 @@
+//! A counter which stores each count in the narrowest integer it fits in.
+
+use num_traits::{CheckedAdd, One, Zero};
+
+use compat::collections::{hash_map, HashMap};
+use compat::convert::TryFrom;
+use compat::hash::Hash;
+use compat::iter::FromIterator;
+use compat::mem;
+use compat::ops::SubAssign;
+use compat::vec::Vec;
+
+use super::memory::table_bytes;
+use super::Counter;
+
+/// A counter of `u64` counts which stores small counts in fewer bytes.
+///
+/// In heavy-tailed data most keys are only seen a few times, and an 8-byte count per key
+/// wastes memory. A `CompactCounter` keeps each count as a `u8` until it outgrows it, then
+/// moves that one entry to `u16`, `u32`, and finally `u64` storage. The promotion is invisible
+/// through the API, which reads and writes counts as `u64`.
+///
+/// Each tier is a hash table holding its keys and counts side by side, so a narrow count only
+/// saves memory when the key is aligned to fewer than 8 bytes: a `(u32, u8)` entry takes 8
+/// bytes against 16 for `(u32, u64)`, but `(String, u8)` and `(&str, u8)` are padded to the
+/// same 32 and 24 bytes as with a `u64` count. For such keys, a plain `Counter<T, u64>` is as
+/// small and faster, since a lookup here may probe all four tiers.
+///
+/// Since counts live in narrower integers, there is no `Index` implementation; use
+/// [`get`](#method.get) instead. Promoted entries stay wide even if their count decreases.
+///
+/// ```rust
+/// # use counter::CompactCounter;
+/// let mut counter = "abbccc".chars().collect::<CompactCounter<_>>();
+/// counter.add_count('a', 1000);
+/// assert_eq!(counter.get(&'a'), 1001);
+/// assert_eq!(counter.get(&'z'), 0);
+/// assert_eq!(counter.most_common(), vec![('a', 1001), ('c', 3), ('b', 2)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompactCounter<T: Hash + Eq> {
+    narrow: HashMap<T, u8>,
+    short: HashMap<T, u16>,
+    medium: HashMap<T, u32>,
+    wide: HashMap<T, u64>,
+}
+
+impl<T> CompactCounter<T>
+where
+    T: Hash + Eq,
+{
+    /// Create a new, empty `CompactCounter`
+    pub fn new() -> CompactCounter<T> {
+        CompactCounter {
+            narrow: HashMap::default(),
+            short: HashMap::default(),
+            medium: HashMap::default(),
+            wide: HashMap::default(),
+        }
+    }
+
+    /// Create a new `CompactCounter` initialized with the given iterable
+    pub fn init<I>(iterable: I) -> CompactCounter<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = CompactCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.add_count(item, 1);
+        }
+    }
+
+    /// Add `count` to the count of `key`, moving it to wider storage if it no longer fits.
+    ///
+    /// Panics if the count overflows `u64`.
+    pub fn add_count(&mut self, key: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let total = match add_in_place(&mut self.narrow, &key, count)
+            .or_else(|| add_in_place(&mut self.short, &key, count))
+            .or_else(|| add_in_place(&mut self.medium, &key, count))
+            .or_else(|| add_in_place(&mut self.wide, &key, count))
+        {
+            Some(Ok(())) => return,
+            Some(Err(total)) => total,
+            None => count,
+        };
+        self.insert(key, total);
+    }
+
+    /// Remove the counts of the elements from the given iterable from this counter
+    ///
+    /// Non-positive counts are automatically removed
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            let _ = decrement(&mut self.narrow, &item)
+                || decrement(&mut self.short, &item)
+                || decrement(&mut self.medium, &item)
+                || decrement(&mut self.wide, &item);
+        }
+    }
+
+    /// The count of `key`, or zero if it isn't present.
+    pub fn get(&self, key: &T) -> u64 {
+        self.narrow
+            .get(key)
+            .map(|&count| u64::from(count))
+            .or_else(|| self.short.get(key).map(|&count| u64::from(count)))
+            .or_else(|| self.medium.get(key).map(|&count| u64::from(count)))
+            .or_else(|| self.wide.get(key).cloned())
+            .unwrap_or(0)
+    }
+
+    /// Whether `key` has a count in this counter.
+    pub fn contains_key(&self, key: &T) -> bool {
+        self.narrow.contains_key(key)
+            || self.short.contains_key(key)
+            || self.medium.contains_key(key)
+            || self.wide.contains_key(key)
+    }
+
+    /// Remove `key` from the counter, returning its count if it was present.
+    pub fn remove(&mut self, key: &T) -> Option<u64> {
+        self.narrow
+            .remove(key)
+            .map(u64::from)
+            .or_else(|| self.short.remove(key).map(u64::from))
+            .or_else(|| self.medium.remove(key).map(u64::from))
+            .or_else(|| self.wide.remove(key))
+    }
+
+    /// The number of distinct keys in the counter.
+    pub fn len(&self) -> usize {
+        self.narrow.len() + self.short.len() + self.medium.len() + self.wide.len()
+    }
+
+    /// Whether the counter contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> u64 {
+        self.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Iterate over `(&item, count)` pairs, in arbitrary order.
+    pub fn iter(&self) -> CompactIter<'_, T> {
+        CompactIter {
+            narrow: self.narrow.iter(),
+            short: self.short.iter(),
+            medium: self.medium.iter(),
+            wide: self.wide.iter(),
+        }
+    }
+
+    /// The approximate number of bytes this counter occupies: the counter itself, plus the
+    /// hash tables of its four tiers, estimated as in
+    /// [`Counter::estimated_memory_bytes`](struct.Counter.html#method.estimated_memory_bytes).
+    ///
+    /// ```rust
+    /// # use counter::{CompactCounter, Counter};
+    /// let compact = (0..1000u32).collect::<CompactCounter<_>>();
+    /// let plain = (0..1000u32).collect::<Counter<_, u64>>();
+    /// assert!(compact.estimated_memory_bytes() < plain.estimated_memory_bytes());
+    /// ```
+    pub fn estimated_memory_bytes(&self) -> usize {
+        mem::size_of::<Self>()
+            + table_bytes::<T, u8>(self.narrow.capacity())
+            + table_bytes::<T, u16>(self.short.capacity())
+            + table_bytes::<T, u32>(self.medium.capacity())
+            + table_bytes::<T, u64>(self.wide.capacity())
+    }
+
+    /// Move the counts into a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, u64> {
+        let mut counter = Counter::new();
+        counter.map.extend(
+            self.narrow
+                .into_iter()
+                .map(|(item, count)| (item, u64::from(count))),
+        );
+        counter.map.extend(
+            self.short
+                .into_iter()
+                .map(|(item, count)| (item, u64::from(count))),
+        );
+        counter.map.extend(
+            self.medium
+                .into_iter()
+                .map(|(item, count)| (item, u64::from(count))),
+        );
+        counter.map.extend(self.wide);
+        counter
+    }
+
+    /// Put a key which isn't in any tier into the narrowest one its count fits in.
+    fn insert(&mut self, key: T, count: u64) {
+        if let Ok(count) = u8::try_from(count) {
+            self.narrow.insert(key, count);
+        } else if let Ok(count) = u16::try_from(count) {
+            self.short.insert(key, count);
+        } else if let Ok(count) = u32::try_from(count) {
+            self.medium.insert(key, count);
+        } else {
+            self.wide.insert(key, count);
+        }
+    }
+}
+
+impl<T> CompactCounter<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, u64)> {
+        let mut items = self
+            .iter()
+            .map(|(item, count)| (item.clone(), count))
+            .collect::<Vec<_>>();
+        items.sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+        items
+    }
+}
+
+/// Add `count` to `key`'s count in `tier`, if it is there.
+///
+/// Returns `None` if `key` isn't in `tier`, and `Some(Err(total))` if the new count doesn't fit,
+/// in which case `key` has been removed from `tier`.
+fn add_in_place<T, W>(tier: &mut HashMap<T, W>, key: &T, count: u64) -> Option<Result<(), u64>>
+where
+    T: Hash + Eq,
+    W: Copy + CheckedAdd + TryFrom<u64> + Into<u64>,
+{
+    let current = tier.get_mut(key)?;
+    if let Some(sum) = W::try_from(count)
+        .ok()
+        .and_then(|count| current.checked_add(&count))
+    {
+        *current = sum;
+        return Some(Ok(()));
+    }
+    let total = (*current)
+        .into()
+        .checked_add(count)
+        .unwrap_or_else(|| panic!("count overflowed u64"));
+    tier.remove(key);
+    Some(Err(total))
+}
+
+/// Subtract one from `key`'s count in `tier`, removing it at zero; whether `key` was there.
+fn decrement<T, W>(tier: &mut HashMap<T, W>, key: &T) -> bool
+where
+    T: Hash + Eq,
+    W: PartialOrd + SubAssign + Zero + One,
+{
+    match tier.get_mut(key) {
+        Some(count) => {
+            if *count > W::one() {
+                *count -= W::one();
+            } else {
+                tier.remove(key);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// An iterator over the `(&item, count)` pairs of a
+/// [`CompactCounter`](struct.CompactCounter.html).
+pub struct CompactIter<'a, T: 'a> {
+    narrow: hash_map::Iter<'a, T, u8>,
+    short: hash_map::Iter<'a, T, u16>,
+    medium: hash_map::Iter<'a, T, u32>,
+    wide: hash_map::Iter<'a, T, u64>,
+}
+
+impl<'a, T> Iterator for CompactIter<'a, T> {
+    type Item = (&'a T, u64);
+
+    fn next(&mut self) -> Option<(&'a T, u64)> {
+        self.narrow
+            .next()
+            .map(|(item, &count)| (item, u64::from(count)))
+            .or_else(|| {
+                self.short
+                    .next()
+                    .map(|(item, &count)| (item, u64::from(count)))
+            })
+            .or_else(|| {
+                self.medium
+                    .next()
+                    .map(|(item, &count)| (item, u64::from(count)))
+            })
+            .or_else(|| self.wide.next().map(|(item, &count)| (item, count)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.narrow.len() + self.short.len() + self.medium.len() + self.wide.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CompactCounter<T>
+where
+    T: Hash + Eq,
+{
+    type Item = (&'a T, u64);
+    type IntoIter = CompactIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Default for CompactCounter<T>
+where
+    T: Hash + Eq,
+{
+    fn default() -> CompactCounter<T> {
+        CompactCounter::new()
+    }
+}
+
+impl<T> FromIterator<T> for CompactCounter<T>
+where
+    T: Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        CompactCounter::init(iter)
+    }
+}
+
+impl<T> Extend<T> for CompactCounter<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compat::string::{String, ToString};
+
+    #[test]
+    fn test_counts_are_promoted() {
+        let mut counter = CompactCounter::new();
+        counter.update(vec!['a'; 255]);
+        assert_eq!(counter.narrow.len(), 1);
+        counter.update(vec!['a', 'b']);
+        assert_eq!(counter.short.get(&'a'), Some(&256));
+        counter.add_count('b', 1 << 40);
+        assert_eq!(counter.wide.get(&'b'), Some(&((1 << 40) + 1)));
+        counter.add_count('c', 70_000);
+        assert_eq!(counter.medium.get(&'c'), Some(&70_000));
+
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter.total(), 256 + (1 << 40) + 1 + 70_000);
+        assert_eq!(counter.get(&'a'), 256);
+        assert!(
+            counter.into_counter().into_map()
+                == hashmap!{
+                    'a' => 256,
+                    'b' => (1 << 40) + 1,
+                    'c' => 70_000,
+                }
+        );
+    }
+
+    #[test]
+    fn test_subtract_and_remove() {
+        let mut counter = CompactCounter::init("aabbb".chars());
+        counter.add_count('c', 300);
+        counter.subtract("abcz".chars());
+        assert_eq!(counter.get(&'a'), 1);
+        assert_eq!(counter.get(&'c'), 299);
+        counter.subtract("a".chars());
+        assert!(!counter.contains_key(&'a'));
+        assert_eq!(counter.remove(&'c'), Some(299));
+        assert_eq!(counter.remove(&'c'), None);
+        assert_eq!(counter.iter().collect::<Vec<_>>(), vec![(&'b', 2)]);
+    }
+
+    #[test]
+    fn test_memory_depends_on_key_alignment() {
+        let keys = (0..1000u32).collect::<Vec<_>>();
+        let compact = keys.iter().cloned().collect::<CompactCounter<_>>();
+        let plain = keys.iter().cloned().collect::<Counter<_, u64>>();
+        let compact_table =
+            compact.estimated_memory_bytes() - mem::size_of::<CompactCounter<u32>>();
+        let plain_table = plain.estimated_memory_bytes() - mem::size_of::<Counter<u32, u64>>();
+        // each bucket takes 9 bytes rather than 17
+        assert!(compact_table * 5 < plain_table * 3);
+
+        // the count takes up the padding after a `String`, whatever its width
+        let words = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let compact = words.iter().cloned().collect::<CompactCounter<_>>();
+        let plain = words.iter().cloned().collect::<Counter<_, u64>>();
+        assert_eq!(
+            compact.estimated_memory_bytes() - mem::size_of::<CompactCounter<String>>(),
+            plain.estimated_memory_bytes() - mem::size_of::<Counter<String, u64>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "count overflowed u64")]
+    fn test_overflow_panics() {
+        let mut counter = CompactCounter::new();
+        counter.add_count('a', u64::MAX);
+        counter.add_count('a', 1);
+    }
+}
@@ -0,0 +1,28 @@
+//! The parts of the standard library this crate uses, taken from `core`, `alloc`, and
+//! `hashbrown` when the `std` feature is disabled.
+
+#[cfg(feature = "std")]
+pub use std::{
+    borrow, boxed, cmp, convert, error, fmt, hash, iter, marker, mem, num, ops, slice, str, string, vec,
+};
+
+#[cfg(not(feature = "std"))]
+pub use alloc::{borrow, boxed, string, vec};
+#[cfg(not(feature = "std"))]
+pub use core::{cmp, convert, error, fmt, hash, iter, mem, num, ops, slice, str};
+
+pub mod collections {
+    #[cfg(feature = "std")]
+    pub use std::collections::{btree_map, hash_map, BTreeMap, BinaryHeap, HashMap, VecDeque};
+
+    #[cfg(not(feature = "std"))]
+    pub use alloc::collections::{btree_map, BTreeMap, BinaryHeap, VecDeque};
+    #[cfg(not(feature = "std"))]
+    pub use hashbrown::{hash_map, HashMap};
+}
+
+#[cfg(not(feature = "std"))]
+pub use hashbrown::DefaultHashBuilder;
+/// The hasher of a `Counter` which doesn't name one.
+#[cfg(feature = "std")]
+pub use std::collections::hash_map::RandomState as DefaultHashBuilder;
@@ -0,0 +1,546 @@
+//! Counters which can be updated from many threads at once.
+
+use num_traits::{One, Zero};
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::mem;
+use std::ops::{AddAssign, Deref, DerefMut, SubAssign};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+
+use super::Counter;
+
+/// A counter split into independently locked shards, for counting from many threads.
+///
+/// Each key belongs to one shard, chosen by its hash, so threads counting different keys
+/// rarely contend for the same lock. All methods take `&self`; share the counter between
+/// threads with an `Arc` or a scoped thread.
+///
+/// ```rust
+/// # use counter::ConcurrentCounter;
+/// use std::thread;
+///
+/// let counter = ConcurrentCounter::<_>::new();
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             for word in "the cat and the hat".split_whitespace() {
+///                 counter.incr(word);
+///             }
+///         });
+///     }
+/// });
+/// let snapshot = counter.snapshot();
+/// assert_eq!(snapshot[&"the"], 8);
+/// assert_eq!(snapshot[&"cat"], 4);
+/// ```
+pub struct ConcurrentCounter<T: Hash + Eq, N = usize> {
+    shards: Vec<Mutex<Counter<T, N>>>,
+    hash_builder: RandomState,
+}
+
+impl<T, N> ConcurrentCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty `ConcurrentCounter`, with a few shards per available CPU.
+    pub fn new() -> ConcurrentCounter<T, N> {
+        let cpus = thread::available_parallelism().map_or(1, |cpus| cpus.get());
+        ConcurrentCounter::with_shards(4 * cpus)
+    }
+
+    /// Create a new, empty `ConcurrentCounter` with the given number of shards.
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> ConcurrentCounter<T, N> {
+        assert!(shards > 0, "a ConcurrentCounter needs at least one shard");
+        ConcurrentCounter {
+            shards: (0..shards).map(|_| Mutex::new(Counter::new())).collect(),
+            hash_builder: RandomState::new(),
+        }
+    }
+
+    /// Add one to the count of `key`.
+    pub fn incr(&self, key: T) {
+        self.shard(&key).update(Some(key));
+    }
+
+    /// Add `count` to the count of `key`.
+    pub fn add_count(&self, key: T, count: N) {
+        self.shard(&key).add_count(key, count);
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    ///
+    /// Each element takes its shard's lock separately; to count a large batch from a single
+    /// thread, count it into a `Counter` first and merge it with `add_counter`.
+    pub fn update<I>(&self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable {
+            self.incr(item);
+        }
+    }
+
+    /// Add all the counts of `counter` to this counter.
+    pub fn add_counter<S>(&self, counter: Counter<T, N, S>)
+    where
+        S: BuildHasher,
+    {
+        for (item, count) in counter {
+            self.add_count(item, count);
+        }
+    }
+
+    /// Consume this counter, merging its shards into a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, N> {
+        let mut counter = Counter::new();
+        for shard in self.shards {
+            let shard = shard
+                .into_inner()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            counter.update_with_counts(shard);
+        }
+        counter
+    }
+
+    fn shard(&self, key: &T) -> MutexGuard<'_, Counter<T, N>> {
+        let index = self.hash_builder.hash_one(key) as usize % self.shards.len();
+        // a panic while holding the lock can't leave a shard's counts inconsistent
+        self.shards[index]
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T, N> ConcurrentCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Clone,
+{
+    /// The current count of `key`.
+    pub fn get(&self, key: &T) -> N {
+        self.shard(key).get_or_zero(key).clone()
+    }
+
+    /// Copy the current counts into a regular `Counter`.
+    ///
+    /// Shards are locked one at a time, so counts added concurrently with the snapshot may
+    /// or may not be included.
+    pub fn snapshot(&self) -> Counter<T, N> {
+        let mut counter = Counter::new();
+        for shard in &self.shards {
+            let shard = shard
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            counter.update_with_counts(
+                shard
+                    .iter()
+                    .map(|(item, count)| (item.clone(), count.clone())),
+            );
+        }
+        counter
+    }
+}
+
+impl<T, N> Default for ConcurrentCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn default() -> ConcurrentCounter<T, N> {
+        ConcurrentCounter::new()
+    }
+}
+
+/// A counter of `AtomicU64` counts, for hot keys which are incremented from many threads.
+///
+/// Incrementing a key which is already present only takes a shared read lock and an atomic
+/// add, so threads never block each other on existing keys. The first increment of a new key
+/// takes a write lock to insert it.
+///
+/// ```rust
+/// # use counter::AtomicCounter;
+/// use std::thread;
+///
+/// let counter = AtomicCounter::new();
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             for _ in 0..1000 {
+///                 counter.incr("requests");
+///             }
+///             counter.add("bytes", 512);
+///         });
+///     }
+/// });
+/// assert_eq!(counter.get(&"requests"), 4000);
+/// assert_eq!(counter.snapshot()[&"bytes"], 2048);
+/// ```
+pub struct AtomicCounter<T: Hash + Eq> {
+    map: RwLock<HashMap<T, AtomicU64>>,
+}
+
+impl<T> AtomicCounter<T>
+where
+    T: Hash + Eq,
+{
+    /// Create a new, empty `AtomicCounter`.
+    pub fn new() -> AtomicCounter<T> {
+        AtomicCounter {
+            map: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Add one to the count of `key`.
+    pub fn incr(&self, key: T) {
+        self.add(key, 1);
+    }
+
+    /// Add `count` to the count of `key`.
+    ///
+    /// Counts wrap around on overflow, like `AtomicU64::fetch_add`.
+    pub fn add(&self, key: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        if let Some(existing) = self.read().get(&key) {
+            existing.fetch_add(count, Ordering::Relaxed);
+            return;
+        }
+        // another thread may have inserted the key since the read lock was released
+        self.write()
+            .entry(key)
+            .or_insert_with(|| AtomicU64::new(0))
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    pub fn update<I>(&self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable {
+            self.incr(item);
+        }
+    }
+
+    /// The current count of `key`.
+    pub fn get(&self, key: &T) -> u64 {
+        self.read()
+            .get(key)
+            .map_or(0, |count| count.load(Ordering::Relaxed))
+    }
+
+    /// The number of distinct keys counted so far.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether nothing has been counted yet.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Consume this counter, returning its counts as a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, u64> {
+        let map = self
+            .map
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        map.into_iter()
+            .map(|(item, count)| (item, count.into_inner()))
+            .collect()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, HashMap<T, AtomicU64>> {
+        // counts are atomics and insertion is a single map operation, so a panicking thread
+        // can't leave the map inconsistent
+        self.map
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, HashMap<T, AtomicU64>> {
+        self.map
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<T> AtomicCounter<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Copy the current counts into a regular `Counter`.
+    ///
+    /// Counts are read one at a time, so increments made concurrently with the snapshot may
+    /// or may not be included.
+    pub fn snapshot(&self) -> Counter<T, u64> {
+        self.read()
+            .iter()
+            .map(|(item, count)| (item.clone(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
+
+impl<T> Default for AtomicCounter<T>
+where
+    T: Hash + Eq,
+{
+    fn default() -> AtomicCounter<T> {
+        AtomicCounter::new()
+    }
+}
+
+/// A global counter fed by per-thread local counters, for counting without synchronization on
+/// the hot path.
+///
+/// Each thread takes its own [`LocalCounter`](struct.LocalCounter.html) from
+/// [`local`](#method.local) and counts into it like a regular `Counter`. Its counts reach the
+/// set only when it is flushed, explicitly with `flush` or implicitly when it is dropped, so
+/// the shared lock is taken once per flush rather than once per item.
+///
+/// ```rust
+/// # use counter::LocalCounterSet;
+/// use std::thread;
+///
+/// let set = LocalCounterSet::<_>::new();
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| {
+///             let mut local = set.local();
+///             for word in "the cat and the hat".split_whitespace() {
+///                 local.update(Some(word));
+///             }
+///             local.flush();
+///             local.update(Some("end"));
+///             // dropping the local counter flushes the rest
+///         });
+///     }
+/// });
+/// let counts = set.aggregate();
+/// assert_eq!(counts[&"the"], 8);
+/// assert_eq!(counts[&"end"], 4);
+/// ```
+pub struct LocalCounterSet<T: Hash + Eq, N = usize> {
+    global: Mutex<Counter<T, N>>,
+}
+
+impl<T, N> LocalCounterSet<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty `LocalCounterSet`.
+    pub fn new() -> LocalCounterSet<T, N> {
+        LocalCounterSet {
+            global: Mutex::new(Counter::new()),
+        }
+    }
+
+    /// Create an empty local counter which flushes into this set.
+    ///
+    /// A local counter is meant to be owned by a single thread; take one per thread.
+    pub fn local(&self) -> LocalCounter<'_, T, N> {
+        LocalCounter {
+            counter: Counter::new(),
+            set: self,
+        }
+    }
+
+    /// Consume this set, returning all the flushed counts.
+    pub fn into_counter(self) -> Counter<T, N> {
+        self.global
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn merge(&self, counter: Counter<T, N>) {
+        self.global
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .update_with_counts(counter);
+    }
+}
+
+impl<T, N> LocalCounterSet<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Clone,
+{
+    /// Copy the counts flushed so far into a regular `Counter`.
+    ///
+    /// Counts still held by local counters which haven't been flushed are not included.
+    pub fn aggregate(&self) -> Counter<T, N> {
+        let global = self
+            .global
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        global
+            .iter()
+            .map(|(item, count)| (item.clone(), count.clone()))
+            .collect()
+    }
+}
+
+impl<T, N> Default for LocalCounterSet<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn default() -> LocalCounterSet<T, N> {
+        LocalCounterSet::new()
+    }
+}
+
+/// A thread's own counter, created by
+/// [`LocalCounterSet::local`](struct.LocalCounterSet.html#method.local).
+///
+/// It dereferences to a regular `Counter`, so all the usual counting methods apply without any
+/// synchronization. Its counts are merged into the set by `flush`, and when it is dropped.
+pub struct LocalCounter<'a, T: Hash + Eq + 'a, N: 'a = usize>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    counter: Counter<T, N>,
+    set: &'a LocalCounterSet<T, N>,
+}
+
+impl<'a, T, N> LocalCounter<'a, T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Merge the counts of this local counter into its set, leaving it empty.
+    pub fn flush(&mut self) {
+        if !self.counter.map.is_empty() {
+            self.set.merge(mem::take(&mut self.counter));
+        }
+    }
+}
+
+impl<'a, T, N> Deref for LocalCounter<'a, T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    type Target = Counter<T, N>;
+
+    fn deref(&self) -> &Counter<T, N> {
+        &self.counter
+    }
+}
+
+impl<'a, T, N> DerefMut for LocalCounter<'a, T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn deref_mut(&mut self) -> &mut Counter<T, N> {
+        &mut self.counter
+    }
+}
+
+impl<'a, T, N> Drop for LocalCounter<'a, T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_counter() {
+        let counter = ConcurrentCounter::with_shards(3);
+        thread::scope(|scope| {
+            for t in 0..8 {
+                let counter = &counter;
+                scope.spawn(move || {
+                    counter.update((0..1000).map(|n| n % 10));
+                    counter.add_count(100 + t, 2);
+                });
+            }
+        });
+        assert_eq!(counter.get(&3), 800);
+        assert_eq!(counter.get(&42), 0);
+
+        let snapshot = counter.snapshot();
+        assert_eq!(snapshot.len(), 18);
+        assert_eq!(snapshot.total(), 8 * 1002);
+        assert_eq!(counter.into_counter(), snapshot);
+    }
+
+    #[test]
+    fn test_add_counter() {
+        let counter = ConcurrentCounter::<char>::with_shards(2);
+        counter.add_counter(Counter::init("abb".chars()));
+        counter.update("bc".chars());
+        assert!(counter.into_counter().into_map() == hashmap!{'a' => 1, 'b' => 3, 'c' => 1});
+    }
+
+    #[test]
+    fn test_atomic_counter() {
+        let counter = AtomicCounter::new();
+        thread::scope(|scope| {
+            for t in 0..8u32 {
+                let counter = &counter;
+                scope.spawn(move || {
+                    counter.update((0..1000u32).map(|n| n % 10));
+                    counter.add(100 + t, 2);
+                    counter.add(200, 0);
+                });
+            }
+        });
+        assert_eq!(counter.get(&3), 800);
+        assert_eq!(counter.get(&200), 0);
+        assert_eq!(counter.len(), 18);
+
+        let snapshot = counter.snapshot();
+        assert_eq!(snapshot.total(), 8 * 1002);
+        assert_eq!(counter.into_counter(), snapshot);
+        assert!(AtomicCounter::<char>::new().is_empty());
+    }
+
+    #[test]
+    fn test_local_counter_set() {
+        let set = LocalCounterSet::<u32>::new();
+        thread::scope(|scope| {
+            for t in 0..8 {
+                let set = &set;
+                scope.spawn(move || {
+                    let mut local = set.local();
+                    local.update((0..1000).map(|n| n % 10));
+                    local.flush();
+                    assert!(local.is_empty());
+                    local.update(Some(100 + t));
+                });
+            }
+        });
+        let counts = set.aggregate();
+        assert_eq!(counts[&3], 800);
+        assert_eq!(counts.len(), 18);
+        assert_eq!(set.into_counter(), counts);
+    }
+
+    #[test]
+    fn test_unflushed_counts_are_not_aggregated() {
+        let set = LocalCounterSet::<char>::new();
+        let mut local = set.local();
+        local.update("aab".chars());
+        assert!(set.aggregate().is_empty());
+        drop(local);
+        assert!(set.aggregate().into_map() == hashmap!{'a' => 2, 'b' => 1});
+    }
+}
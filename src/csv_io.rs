@@ -0,0 +1,146 @@
+//! CSV import and export for `Counter`, behind the `csv` feature.
+
+use csv;
+use num_traits::Zero;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::hash::{BuildHasher, Hash};
+use std::io::{Read, Write};
+use std::ops::AddAssign;
+
+use super::{cmp_counts_descending, Counter};
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Write this counter as CSV, with a `key,count` header row followed by one row per key,
+    /// most common first.
+    ///
+    /// Keys with equal counts are written in their natural order, so the output is
+    /// deterministic, and counts which can't be ordered, such as NaN, are written last. Each
+    /// key should serialize to a single CSV field.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let mut out = Vec::new();
+    /// counter.to_csv(&mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "key,count\nc,3\nb,2\na,1\n");
+    /// ```
+    pub fn to_csv<W>(&self, writer: W) -> Result<(), csv::Error>
+    where
+        W: Write,
+        T: Serialize + Ord,
+        N: Serialize + PartialOrd,
+    {
+        let mut rows = self.map.iter().collect::<Vec<_>>();
+        rows.sort_by(|&(a, a_count), &(b, b_count)| {
+            cmp_counts_descending(a_count, b_count).then_with(|| a.cmp(b))
+        });
+
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["key", "count"])?;
+        for row in rows {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a counter from CSV written by [`to_csv`](#method.to_csv): a header row, then
+    /// `key,count` rows.
+    ///
+    /// Repeated keys are combined as by [`update_with_counts`](#method.update_with_counts).
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let csv = "key,count\napple,3\npear,1\napple,2\n";
+    /// let counter: Counter<String> = Counter::from_csv(csv.as_bytes()).unwrap();
+    /// assert_eq!(counter[&"apple".to_string()], 5);
+    /// assert_eq!(counter[&"pear".to_string()], 1);
+    /// ```
+    pub fn from_csv<R>(reader: R) -> Result<Counter<T, N, S>, csv::Error>
+    where
+        R: Read,
+        T: DeserializeOwned,
+        N: DeserializeOwned + Zero + AddAssign,
+        S: Default,
+    {
+        let mut counter = Counter::default();
+        for row in csv::Reader::from_reader(reader).deserialize() {
+            let (key, count): (T, N) = row?;
+            counter.add_count_pruned(key, count);
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Counter;
+
+    #[test]
+    fn test_round_trip() {
+        let counter = "the cat and the hat and the bat"
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Counter<_>>();
+        let mut out = Vec::new();
+        counter.to_csv(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "key,count\nthe,3\nand,2\nbat,1\ncat,1\nhat,1\n"
+        );
+        assert_eq!(Counter::from_csv(&out[..]).unwrap(), counter);
+    }
+
+    #[test]
+    fn test_nan_counts_are_written_last() {
+        let mut counter = Counter::<u32, f64>::new();
+        for key in 0..60 {
+            let count = if key % 3 == 0 { f64::NAN } else { 0.5 };
+            counter.map.insert(key, count);
+        }
+        let mut out = Vec::new();
+        counter.to_csv(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let rows = out.lines().skip(1).collect::<Vec<_>>();
+        assert_eq!(rows.len(), 60);
+        assert!(rows[..40].iter().all(|row| row.ends_with(",0.5")));
+        assert!(rows[40..].iter().all(|row| row.ends_with(",NaN")));
+    }
+
+    #[test]
+    fn test_numeric_keys_and_float_counts() {
+        let mut counter: Counter<u32, f64> = Counter::new();
+        counter.update_weighted(vec![7, 3, 7], 0.5);
+        let mut out = Vec::new();
+        counter.to_csv(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "key,count\n7,1.0\n3,0.5\n"
+        );
+        assert_eq!(Counter::<u32, f64>::from_csv(&out[..]).unwrap(), counter);
+    }
+
+    #[test]
+    fn test_bad_count() {
+        let csv = "key,count\na,lots\n";
+        assert!(Counter::<String>::from_csv(csv.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_zero_counts_are_dropped() {
+        let csv = "key,count
+a,0
+b,2
+c,1
+c,-1
+";
+        let counter = Counter::<String, i32>::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(counter.keys().collect::<Vec<_>>(), vec!["b"]);
+    }
+}
@@ -0,0 +1,292 @@
+//! A concurrent counter backed by `DashMap`, behind the `dashmap` feature.
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use num_traits::{One, Zero};
+
+use std::hash::{BuildHasher, Hash};
+use std::ops::{AddAssign, SubAssign};
+
+use super::Counter;
+
+/// A counter backed by a [`DashMap`](https://docs.rs/dashmap), which every thread can update
+/// through a shared reference.
+///
+/// It offers the counting surface of [`Counter`](struct.Counter.html): `update` and
+/// `subtract`, merging whole counters, and `most_common`. Like `Counter`, it removes entries
+/// whose count reaches zero. Requires the `dashmap` feature.
+///
+/// ```rust
+/// # use counter::DashCounter;
+/// use std::thread;
+///
+/// let counter = DashCounter::<_>::new();
+/// thread::scope(|scope| {
+///     for _ in 0..4 {
+///         scope.spawn(|| counter.update("abbccc".chars()));
+///     }
+/// });
+/// assert_eq!(counter.most_common(), vec![('c', 12), ('b', 8), ('a', 4)]);
+/// ```
+pub struct DashCounter<T: Hash + Eq, N = usize> {
+    map: DashMap<T, N>,
+}
+
+impl<T, N> DashCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty `DashCounter`
+    pub fn new() -> DashCounter<T, N> {
+        DashCounter {
+            map: DashMap::new(),
+        }
+    }
+
+    /// Create a new `DashCounter` initialized with the given iterable
+    pub fn init<I>(iterable: I) -> DashCounter<T, N>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let counter = DashCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    pub fn update<I>(&self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable {
+            *self.map.entry(item).or_insert_with(N::zero) += N::one();
+        }
+    }
+
+    /// Remove the counts of the elements from the given iterable from this counter
+    ///
+    /// Non-positive counts are automatically removed
+    pub fn subtract<I>(&self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable {
+            self.remove_count(item, N::one());
+        }
+    }
+
+    /// Add `count` to the count of `key`, removing the entry if its count comes to zero.
+    pub fn add_count(&self, key: T, count: N) {
+        match self.map.entry(key) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += count;
+                if entry.get().is_zero() {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                if !count.is_zero() {
+                    entry.insert(count);
+                }
+            }
+        }
+    }
+
+    /// Remove `count` from the count of `key`
+    ///
+    /// As with [`subtract`](#method.subtract), counts never go below zero and an entry which
+    /// reaches zero is removed.
+    pub fn remove_count(&self, key: T, count: N) {
+        if let Entry::Occupied(mut entry) = self.map.entry(key) {
+            if *entry.get() > count {
+                *entry.get_mut() -= count;
+            } else {
+                entry.remove();
+            }
+        }
+    }
+
+    /// Add all the counts of `counter` to this counter.
+    pub fn add_counter<S>(&self, counter: Counter<T, N, S>)
+    where
+        S: BuildHasher,
+    {
+        for (item, count) in counter {
+            self.add_count(item, count);
+        }
+    }
+
+    /// Subtract all the counts of `counter` from this counter, keeping only positive counts.
+    pub fn subtract_counter<S>(&self, counter: Counter<T, N, S>)
+    where
+        S: BuildHasher,
+    {
+        for (item, count) in counter {
+            self.remove_count(item, count);
+        }
+    }
+
+    /// The number of distinct items in this counter.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether this counter is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Consume this counter, returning its counts as a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, N> {
+        self.map.into_iter().collect()
+    }
+}
+
+impl<T, N> DashCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Clone,
+{
+    /// The current count of `key`.
+    pub fn get(&self, key: &T) -> N {
+        self.map
+            .get(key)
+            .map_or_else(N::zero, |count| count.value().clone())
+    }
+
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for entry in self.map.iter() {
+            total += entry.value().clone();
+        }
+        total
+    }
+
+    /// Copy the current counts into a regular `Counter`.
+    ///
+    /// Entries are read one shard at a time, so updates made concurrently with the snapshot
+    /// may or may not be included.
+    pub fn snapshot(&self) -> Counter<T, N> {
+        self.map
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+}
+
+impl<T, N> DashCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Clone + Ord,
+{
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, N)> {
+        self.snapshot().most_common()
+    }
+
+    /// Create a vector of the `n` most common `(elem, frequency)` pairs, sorted most to least
+    /// common.
+    pub fn most_common_n(&self, n: usize) -> Vec<(T, N)> {
+        self.snapshot().most_common_n(n)
+    }
+}
+
+impl<T, N> Default for DashCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn default() -> DashCounter<T, N> {
+        DashCounter::new()
+    }
+}
+
+impl<T, N, S> AddAssign<Counter<T, N, S>> for DashCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Add a counter to this counter, exactly like [`add_counter`](#method.add_counter).
+    fn add_assign(&mut self, rhs: Counter<T, N, S>) {
+        self.add_counter(rhs);
+    }
+}
+
+impl<T, N, S> SubAssign<Counter<T, N, S>> for DashCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Subtract a counter from this counter, exactly like
+    /// [`subtract_counter`](#method.subtract_counter).
+    fn sub_assign(&mut self, rhs: Counter<T, N, S>) {
+        self.subtract_counter(rhs);
+    }
+}
+
+impl<T, N> From<DashCounter<T, N>> for Counter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn from(counter: DashCounter<T, N>) -> Counter<T, N> {
+        counter.into_counter()
+    }
+}
+
+impl<T, N, S> From<Counter<T, N, S>> for DashCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    fn from(counter: Counter<T, N, S>) -> DashCounter<T, N> {
+        let dash = DashCounter::new();
+        dash.add_counter(counter);
+        dash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_update_and_subtract() {
+        let counter = DashCounter::<_>::init("abbccc".chars());
+        counter.subtract("abz".chars());
+        counter.add_count('d', 2);
+        counter.remove_count('c', 5);
+        assert!(counter.snapshot().into_map() == hashmap!{'b' => 1, 'd' => 2});
+        assert_eq!(counter.total(), 3);
+        assert_eq!(counter.get(&'z'), 0);
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        let counter = DashCounter::<u32>::new();
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| counter.update((0..1000).map(|n| n % 10)));
+            }
+        });
+        assert_eq!(counter.len(), 10);
+        assert_eq!(counter.get(&7), 800);
+        assert_eq!(counter.most_common_n(1)[0].1, 800);
+    }
+
+    #[test]
+    fn test_arithmetic_and_conversions() {
+        let mut counter = DashCounter::from(Counter::<_>::init("aab".chars()));
+        counter += Counter::init("bc".chars());
+        counter -= Counter::init("aaac".chars());
+        assert_eq!(Counter::from(counter), Counter::init("bb".chars()));
+    }
+}
@@ -0,0 +1,209 @@
+//! A counter whose counts fade over time.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::Counter;
+
+/// A counter of `f64` scores which halve every `half_life` units of time.
+///
+/// Time is a number kept by the counter, which only moves forward when told to: by one unit
+/// with [`tick`](#method.tick), or by any amount with [`advance`](#method.advance), such as
+/// the seconds elapsed since the last call. Scores decay lazily, when their key is next
+/// counted or read, so advancing time doesn't touch every entry. Recently counted keys rank
+/// above keys which were counted often long ago, which suits "trending" lists in long-running
+/// services.
+///
+/// Scores never reach zero by decay alone; [`prune`](#method.prune) removes the ones which
+/// have faded below a threshold.
+///
+/// ```rust
+/// # use counter::DecayCounter;
+/// let mut trending = DecayCounter::new(1.0);
+/// trending.add_count("old", 8.0);
+/// trending.advance(3.0);
+/// trending.add_count("new", 2.0);
+/// assert_eq!(trending.get(&"old"), 1.0);
+/// assert_eq!(trending.most_common()[0], ("new", 2.0));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecayCounter<T: Hash + Eq> {
+    // each key's score, as it was at the time alongside it
+    scores: HashMap<T, (f64, f64)>,
+    half_life: f64,
+    now: f64,
+}
+
+impl<T> DecayCounter<T>
+where
+    T: Hash + Eq,
+{
+    /// Create a new, empty `DecayCounter` whose scores halve every `half_life` units of time.
+    ///
+    /// Panics unless `half_life` is positive and finite.
+    pub fn new(half_life: f64) -> DecayCounter<T> {
+        assert!(
+            half_life > 0.0 && half_life.is_finite(),
+            "the half-life of a DecayCounter must be positive"
+        );
+        DecayCounter {
+            scores: HashMap::new(),
+            half_life,
+            now: 0.0,
+        }
+    }
+
+    /// The time it takes a score to halve.
+    pub fn half_life(&self) -> f64 {
+        self.half_life
+    }
+
+    /// The counter's current time, starting from zero.
+    pub fn now(&self) -> f64 {
+        self.now
+    }
+
+    /// Move time forward by one unit.
+    pub fn tick(&mut self) {
+        self.advance(1.0);
+    }
+
+    /// Move time forward by `elapsed` units.
+    ///
+    /// Negative or NaN amounts are ignored, since time can't go backwards.
+    pub fn advance(&mut self, elapsed: f64) {
+        if elapsed > 0.0 {
+            self.now += elapsed;
+        }
+    }
+
+    /// Add one to the score of each of the elements from the given iterable, at the current
+    /// time
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.add_count(item, 1.0);
+        }
+    }
+
+    /// Add `count` to the score of `item`, at the current time.
+    pub fn add_count(&mut self, item: T, count: f64) {
+        let (now, half_life) = (self.now, self.half_life);
+        let score = self.scores.entry(item).or_insert((0.0, now));
+        *score = (decay(score.0, now - score.1, half_life) + count, now);
+    }
+
+    /// The current score of `item`, or zero if it isn't present.
+    pub fn get(&self, item: &T) -> f64 {
+        self.scores
+            .get(item)
+            .map_or(0.0, |&(score, time)| self.decayed(score, time))
+    }
+
+    /// The number of keys in the counter, however faded.
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether the counter contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Sum of all the current scores.
+    pub fn total(&self) -> f64 {
+        self.iter().map(|(_, score)| score).sum()
+    }
+
+    /// Iterate over `(&item, score)` pairs at the current time, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, f64)> {
+        self.scores
+            .iter()
+            .map(move |(item, &(score, time))| (item, self.decayed(score, time)))
+    }
+
+    /// Remove every key whose current score is below `threshold`.
+    pub fn prune(&mut self, threshold: f64) {
+        let (now, half_life) = (self.now, self.half_life);
+        self.scores
+            .retain(|_, &mut (score, time)| decay(score, now - time, half_life) >= threshold);
+    }
+
+    fn decayed(&self, score: f64, time: f64) -> f64 {
+        decay(score, self.now - time, self.half_life)
+    }
+}
+
+impl<T> DecayCounter<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Create a vector of `(elem, score)` pairs at the current time, sorted highest to
+    /// lowest.
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, f64)> {
+        let mut items = self
+            .iter()
+            .map(|(item, score)| (item.clone(), score))
+            .collect::<Vec<_>>();
+        items.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        items
+    }
+
+    /// Copy the current scores into a regular `Counter`.
+    pub fn to_counter(&self) -> Counter<T, f64> {
+        let mut counter = Counter::new();
+        counter
+            .map
+            .extend(self.iter().map(|(item, score)| (item.clone(), score)));
+        counter
+    }
+}
+
+impl<T> Extend<T> for DecayCounter<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+/// `score` after `elapsed` time with the given half-life.
+fn decay(score: f64, elapsed: f64, half_life: f64) -> f64 {
+    score * (-elapsed / half_life).exp2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_halve_each_half_life() {
+        let mut counter = DecayCounter::new(2.0);
+        counter.update(vec!['a', 'a', 'b']);
+        counter.tick();
+        counter.tick();
+        assert_eq!(counter.get(&'a'), 1.0);
+        counter.add_count('b', 1.0);
+        assert_eq!(counter.get(&'b'), 1.5);
+        counter.advance(-5.0);
+        assert_eq!(counter.now(), 2.0);
+        assert_eq!(counter.total(), 2.5);
+        assert_eq!(counter.get(&'z'), 0.0);
+    }
+
+    #[test]
+    fn test_prune_and_convert() {
+        let mut counter = DecayCounter::new(1.0);
+        counter.add_count("stale", 4.0);
+        counter.advance(4.0);
+        counter.add_count("fresh", 1.0);
+        counter.prune(0.5);
+        assert_eq!(counter.len(), 1);
+        assert!(counter.to_counter().into_map() == hashmap!{"fresh" => 1.0});
+    }
+}
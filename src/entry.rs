@@ -0,0 +1,162 @@
+//! In-place access to a single count which keeps the counter's invariants.
+
+use num_traits::Zero;
+
+use compat::collections::hash_map;
+use compat::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use compat::marker::PhantomData;
+use compat::ops::{Deref, DerefMut};
+use compat::DefaultHashBuilder;
+
+use super::Counter;
+
+/// A guard giving mutable access to the count of a single item.
+///
+/// It is created by [`Counter::entry`](struct.Counter.html#method.entry). The count
+/// dereferences to `N`, and all arithmetic on it goes through `N`, so the count type's
+/// overflow policy applies as usual. When the guard is dropped, an entry whose count is
+/// zero is removed from the counter.
+pub struct Entry<'a, T: 'a, N: 'a + Zero, S: 'a = DefaultHashBuilder> {
+    // always `Some` until the guard is consumed by `remove`
+    #[cfg(feature = "std")]
+    entry: Option<hash_map::OccupiedEntry<'a, T, N>>,
+    #[cfg(not(feature = "std"))]
+    entry: Option<hash_map::OccupiedEntry<'a, T, N, S>>,
+    // the standard library's entries don't name the map's hasher, but `hashbrown`'s do
+    #[cfg(feature = "std")]
+    hasher: PhantomData<&'a S>,
+}
+
+impl<'a, T, N, S> Entry<'a, T, N, S>
+where
+    N: Zero,
+{
+    /// The item this entry counts.
+    pub fn key(&self) -> &T {
+        self.entry.as_ref().expect("entry already removed").key()
+    }
+
+    /// The current count of the item.
+    pub fn get(&self) -> &N {
+        self.entry.as_ref().expect("entry already removed").get()
+    }
+
+    /// Remove the item from the counter, returning its count.
+    pub fn remove(mut self) -> N {
+        self.entry.take().expect("entry already removed").remove()
+    }
+}
+
+impl<'a, T, N, S> Deref for Entry<'a, T, N, S>
+where
+    N: Zero,
+{
+    type Target = N;
+
+    fn deref(&self) -> &N {
+        self.get()
+    }
+}
+
+impl<'a, T, N, S> DerefMut for Entry<'a, T, N, S>
+where
+    N: Zero,
+{
+    fn deref_mut(&mut self) -> &mut N {
+        self.entry
+            .as_mut()
+            .expect("entry already removed")
+            .get_mut()
+    }
+}
+
+impl<'a, T, N, S> Drop for Entry<'a, T, N, S>
+where
+    N: Zero,
+{
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            if entry.get().is_zero() {
+                entry.remove();
+            }
+        }
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero,
+    S: BuildHasher,
+{
+    /// Get a guard for in-place manipulation of the count of `key`.
+    ///
+    /// An absent item starts with a count of zero. If the count is zero when the guard is
+    /// dropped, the item is removed, so no zero counts are left behind.
+    ///
+    /// This shadows `HashMap::entry`, which remains available through `deref_mut()`.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = "aab".chars().collect::<Counter<_>>();
+    /// *counter.entry('a') += 3;
+    /// *counter.entry('b') -= 1;
+    /// {
+    ///     let entry = counter.entry('z');
+    ///     assert_eq!(*entry, 0);
+    /// }
+    /// assert_eq!(counter[&'a'], 5);
+    /// assert!(!counter.contains_key(&'b'));
+    /// assert!(!counter.contains_key(&'z'));
+    /// ```
+    pub fn entry(&mut self, key: T) -> Entry<'_, T, N, S> {
+        let entry = match self.map.entry(key) {
+            hash_map::Entry::Occupied(entry) => entry,
+            hash_map::Entry::Vacant(entry) => entry.insert_entry(N::zero()),
+        };
+        Entry {
+            entry: Some(entry),
+            #[cfg(feature = "std")]
+            hasher: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use overflow::Saturating;
+
+    #[test]
+    fn test_entry_prunes_zero() {
+        let mut counter = Counter::<_, i32>::init("abbccc".chars());
+        *counter.entry('a') -= 1;
+        *counter.entry('b') -= 3;
+        *counter.entry('d') += 2;
+        let _ = counter.entry('e');
+        let expected = hashmap!{
+            'b' => -1,
+            'c' => 3,
+            'd' => 2,
+        };
+        assert!(counter.into_map() == expected);
+    }
+
+    #[test]
+    fn test_entry_remove() {
+        let mut counter = Counter::<_>::init("abb".chars());
+        let entry = counter.entry('b');
+        assert_eq!(entry.key(), &'b');
+        assert_eq!(entry.remove(), 2);
+        assert_eq!(counter.into_map(), hashmap!{'a' => 1});
+    }
+
+    #[test]
+    fn test_entry_applies_overflow_policy() {
+        let mut counter: Counter<_, Saturating<u8>> = Counter::new();
+        *counter.entry('a') += Saturating(200);
+        *counter.entry('a') += Saturating(200);
+        assert_eq!(counter[&'a'], Saturating(255));
+    }
+}
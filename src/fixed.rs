@@ -0,0 +1,360 @@
+//! A counter of fixed capacity which never allocates.
+
+use num_traits::{One, Zero};
+
+use compat::error::Error;
+use compat::fmt;
+use compat::hash::Hash;
+use compat::ops::{AddAssign, Index, SubAssign};
+use compat::slice;
+
+use super::Counter;
+
+/// The error returned when a key is added to a full [`FixedCounter`](struct.FixedCounter.html).
+///
+/// It hands back the key which didn't fit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError<T>(pub T);
+
+impl<T> CapacityError<T> {
+    /// The key which didn't fit in the counter.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("counter is full")
+    }
+}
+
+impl<T: fmt::Debug> Error for CapacityError<T> {}
+
+/// A counter of at most `CAP` distinct keys, stored inline in an array.
+///
+/// It never allocates, which makes it usable where there is no heap at all, such as
+/// firmware tallying event codes. Keys are found by linear scan, so they only need to be
+/// `Eq`. Adding a new key to a full counter returns a [`CapacityError`](struct.CapacityError.html)
+/// instead of growing; counting keys which are already present always succeeds.
+///
+/// ```rust
+/// # use counter::{CapacityError, FixedCounter};
+/// let mut counter: FixedCounter<u8, 2> = FixedCounter::new();
+/// assert_eq!(counter.try_update(vec![7, 3, 7]), Ok(()));
+/// assert_eq!(counter.try_update(vec![3, 9]), Err(CapacityError(9)));
+/// assert_eq!(counter[&7], 2);
+/// assert_eq!(counter[&3], 2);
+/// assert!(counter.is_full());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FixedCounter<T, const CAP: usize, N = usize> {
+    // the first `len` entries are `Some`, the rest `None`
+    entries: [Option<(T, N)>; CAP],
+    len: usize,
+    // necessary for `Index::index` since we cannot declare generic `static` variables.
+    zero: N,
+}
+
+impl<T, const CAP: usize, N> FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Zero,
+{
+    /// Create a new, empty `FixedCounter`
+    pub fn new() -> FixedCounter<T, CAP, N> {
+        FixedCounter {
+            entries: [const { None }; CAP],
+            len: 0,
+            zero: N::zero(),
+        }
+    }
+
+    /// The maximum number of distinct keys the counter can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// The number of distinct keys in the counter.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the counter contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the counter can't take any more distinct keys.
+    pub fn is_full(&self) -> bool {
+        self.len == CAP
+    }
+
+    /// Get the count of `key`, if it is present.
+    pub fn get(&self, key: &T) -> Option<&N> {
+        self.iter()
+            .find(|&(item, _)| item == key)
+            .map(|(_, count)| count)
+    }
+
+    /// Get the count of `key`, or zero if it isn't present.
+    pub fn get_or_zero(&self, key: &T) -> &N {
+        self.get(key).unwrap_or(&self.zero)
+    }
+
+    /// Whether `key` has a count in this counter.
+    pub fn contains_key(&self, key: &T) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove `key` from the counter, returning its count if it was present.
+    pub fn remove(&mut self, key: &T) -> Option<N> {
+        let index = self.position(key)?;
+        self.len -= 1;
+        self.entries.swap(index, self.len);
+        self.entries[self.len].take().map(|(_, count)| count)
+    }
+
+    /// Remove all the keys, keeping the capacity.
+    pub fn clear(&mut self) {
+        for entry in &mut self.entries[..self.len] {
+            *entry = None;
+        }
+        self.len = 0;
+    }
+
+    /// Iterate over `(&item, &count)` pairs, in arbitrary order.
+    pub fn iter(&self) -> FixedIter<'_, T, N> {
+        FixedIter {
+            entries: self.entries[..self.len].iter(),
+        }
+    }
+
+    fn position(&self, key: &T) -> Option<usize> {
+        self.iter().position(|(item, _)| item == key)
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut N> {
+        self.entries[..self.len]
+            .iter_mut()
+            .filter_map(|entry| entry.as_mut())
+            .find(|(item, _)| item == key)
+            .map(|(_, count)| count)
+    }
+}
+
+impl<T, const CAP: usize, N> FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Add the counts of the elements from the given iterable to this counter,
+    /// stopping at the first new key which doesn't fit.
+    ///
+    /// On error, the elements preceding the rejected one have already been counted.
+    pub fn try_update<I>(&mut self, iterable: I) -> Result<(), CapacityError<T>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.try_add_count(item, N::one())?;
+        }
+        Ok(())
+    }
+
+    /// Add `count` to the count of `key`, unless `key` is new and the counter is full.
+    ///
+    /// Adding a zero count to an absent key leaves the counter unchanged, and always succeeds.
+    pub fn try_add_count(&mut self, key: T, count: N) -> Result<(), CapacityError<T>> {
+        if let Some(entry) = self.get_mut(&key) {
+            *entry += count;
+            return Ok(());
+        }
+        if count.is_zero() {
+            return Ok(());
+        }
+        if self.is_full() {
+            return Err(CapacityError(key));
+        }
+        self.entries[self.len] = Some((key, count));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the counts of the elements from the given iterable from this counter
+    ///
+    /// Non-positive counts are automatically removed, freeing their slots.
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            let mut remove = false;
+            if let Some(entry) = self.get_mut(&item) {
+                if *entry > N::zero() {
+                    *entry -= N::one();
+                }
+                remove = *entry == N::zero();
+            }
+            if remove {
+                self.remove(&item);
+            }
+        }
+    }
+}
+
+impl<T, const CAP: usize, N> FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Clone + Zero + AddAssign,
+{
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for (_, count) in self.iter() {
+            total += count.clone();
+        }
+        total
+    }
+}
+
+impl<T, const CAP: usize, N> FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Zero + Ord,
+{
+    /// Reorder the entries in place, most to least common, so that [`iter`](#method.iter)
+    /// visits them in that order.
+    ///
+    /// This doesn't allocate. The ordering of keys with equal counts is unstable.
+    ///
+    /// ```rust
+    /// # use counter::FixedCounter;
+    /// let mut counter: FixedCounter<char, 4> = FixedCounter::new();
+    /// counter.try_update("abbccc".chars()).unwrap();
+    /// counter.sort_most_common();
+    /// let order = counter.iter().map(|(&item, _)| item).collect::<String>();
+    /// assert_eq!(order, "cba");
+    /// ```
+    pub fn sort_most_common(&mut self) {
+        self.entries[..self.len].sort_unstable_by(|a, b| match (a, b) {
+            (Some((_, a_count)), Some((_, b_count))) => b_count.cmp(a_count),
+            _ => unreachable!("the first `len` entries are occupied"),
+        });
+    }
+}
+
+impl<T, const CAP: usize, N> FixedCounter<T, CAP, N>
+where
+    T: Hash + Eq,
+    N: Zero,
+{
+    /// Move the counts into a regular, growable `Counter`.
+    pub fn into_counter(self) -> Counter<T, N> {
+        let mut counter = Counter::default();
+        counter.map.extend(
+            // `IntoIterator::into_iter` iterates arrays by value, even in edition 2015
+            IntoIterator::into_iter(self.entries)
+                .flatten()
+                .filter(|(_, count)| !count.is_zero()),
+        );
+        counter
+    }
+}
+
+impl<T, const CAP: usize, N> Default for FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Zero,
+{
+    fn default() -> FixedCounter<T, CAP, N> {
+        FixedCounter::new()
+    }
+}
+
+impl<'a, T, const CAP: usize, N> Index<&'a T> for FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Zero,
+{
+    type Output = N;
+
+    /// Index in immutable contexts
+    ///
+    /// Returns a reference to a `zero` value for missing keys.
+    fn index(&self, key: &'a T) -> &N {
+        self.get_or_zero(key)
+    }
+}
+
+/// An iterator over the `(&item, &count)` pairs of a [`FixedCounter`](struct.FixedCounter.html).
+pub struct FixedIter<'a, T: 'a, N: 'a> {
+    entries: slice::Iter<'a, Option<(T, N)>>,
+}
+
+impl<'a, T, N> Iterator for FixedIter<'a, T, N> {
+    type Item = (&'a T, &'a N);
+
+    fn next(&mut self) -> Option<(&'a T, &'a N)> {
+        self.entries
+            .next()
+            .and_then(|entry| entry.as_ref())
+            .map(|(item, count)| (item, count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<'a, T, const CAP: usize, N> IntoIterator for &'a FixedCounter<T, CAP, N>
+where
+    T: Eq,
+    N: Zero,
+{
+    type Item = (&'a T, &'a N);
+    type IntoIter = FixedIter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_counter_rejects_new_keys() {
+        let mut counter: FixedCounter<_, 3> = FixedCounter::new();
+        counter.try_update("abcab".chars()).unwrap();
+        assert_eq!(counter.try_update("abd".chars()), Err(CapacityError('d')));
+        assert_eq!(counter.try_add_count('e', 0), Ok(()));
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter.total(), 7);
+        assert_eq!(counter[&'a'], 3);
+        assert_eq!(counter[&'d'], 0);
+    }
+
+    #[test]
+    fn test_subtract_frees_slots() {
+        let mut counter: FixedCounter<_, 2> = FixedCounter::new();
+        counter.try_update("aab".chars()).unwrap();
+        counter.subtract("bz".chars());
+        assert!(!counter.contains_key(&'b'));
+        counter.try_update("c".chars()).unwrap();
+        assert_eq!(counter.remove(&'a'), Some(2));
+        assert!(counter.into_counter().into_map() == hashmap!{'c' => 1});
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut counter: FixedCounter<_, 2, u8> = FixedCounter::default();
+        counter.try_update(vec![1, 2]).unwrap();
+        assert!(counter.is_full());
+        counter.clear();
+        assert!(counter.is_empty());
+        assert_eq!(counter.capacity(), 2);
+        assert_eq!(counter.iter().count(), 0);
+    }
+}
@@ -0,0 +1,68 @@
+//! Counting the user-perceived characters of a string.
+
+use num_traits::{One, Zero};
+use unicode_segmentation::UnicodeSegmentation;
+
+use compat::hash::BuildHasher;
+use compat::ops::{AddAssign, SubAssign};
+
+use super::Counter;
+
+impl<'a, N> Counter<&'a str, N>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new `Counter` of the grapheme clusters in `text`.
+    ///
+    /// A grapheme cluster is what a reader sees as a single character, which may be made of
+    /// several `char`s: a letter and its combining accents, a flag, or an emoji sequence.
+    /// Counting `text.chars()` splits these apart; this counts each cluster once, as a slice
+    /// of `text`. Clusters are the extended ones of
+    /// [UAX #29](https://www.unicode.org/reports/tr29/). Requires the `unicode` feature.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// // the second "é" is an "e" followed by a combining acute accent
+    /// let text = "café cafe\u{301} 🇫🇷🇫🇷";
+    /// let counter = Counter::<_>::from_graphemes(text);
+    /// assert_eq!(counter[&"e\u{301}"], 1);
+    /// assert_eq!(counter[&"🇫🇷"], 2);
+    /// assert_eq!(counter.get("\u{301}"), None);
+    /// ```
+    pub fn from_graphemes(text: &'a str) -> Counter<&'a str, N> {
+        Counter::init(text.graphemes(true))
+    }
+}
+
+impl<'a, N, S> Counter<&'a str, N, S>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Add the counts of the grapheme clusters in `text` to this counter.
+    ///
+    /// See [`from_graphemes`](#method.from_graphemes) for what a grapheme cluster is.
+    pub fn update_graphemes(&mut self, text: &'a str) {
+        self.update(text.graphemes(true));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combining_marks_stay_with_their_base() {
+        let counter = Counter::<_>::from_graphemes("ñan\u{303}a");
+        assert!(counter.into_map() == hashmap!{"ñ" => 1, "a" => 2, "n\u{303}" => 1});
+    }
+
+    #[test]
+    fn test_update_graphemes() {
+        let mut counter = Counter::<_, u8>::from_graphemes("👍🏽!");
+        counter.update_graphemes("👍🏽👍");
+        assert_eq!(counter[&"👍🏽"], 2);
+        assert_eq!(counter[&"👍"], 1);
+        assert_eq!(counter.total(), 4);
+    }
+}
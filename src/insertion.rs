@@ -0,0 +1,102 @@
+//! A counter which remembers the order in which keys were first seen.
+
+use num_traits::{One, Zero};
+
+use indexmap::{self, IndexMap};
+use std::hash::{BuildHasher, Hash};
+use std::iter;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Deref, DerefMut, Index, Sub,
+    SubAssign,
+};
+
+use super::Counter;
+
+map_counter! {
+    /// A counter backed by an [`IndexMap`](https://docs.rs/indexmap) rather than a `HashMap`.
+    ///
+    /// Like Python's `Counter` since 3.7, keys iterate in the order they were first seen, which
+    /// also decides how [`most_common`](#method.most_common) breaks ties. The order survives
+    /// updates and arithmetic: new keys are appended, and removing a key keeps the order of the
+    /// others. Requires the `indexmap` feature.
+    ///
+    /// ```rust
+    /// # use counter::InsertionCounter;
+    /// let counter = "abracadabra".chars().collect::<InsertionCounter<_>>();
+    /// let in_order = counter.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>();
+    /// assert_eq!(in_order, vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)]);
+    /// ```
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct InsertionCounter in IndexMap, indexmap::map where T: [Hash + Eq];
+    remove: shift_remove;
+    order: "insertion order";
+    most_common:
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Keys with equal counts appear in the order they were first seen, as in Python.
+    ///
+    /// ```rust
+    /// # use counter::InsertionCounter;
+    /// let mc = "cabbage".chars().collect::<InsertionCounter<_>>().most_common();
+    /// let expect = vec![('a', 2), ('b', 2), ('c', 1), ('g', 1), ('e', 1)];
+    /// assert_eq!(mc, expect);
+    /// ```
+    from_counter:
+    /// Convert a `Counter`; the keys start out in the counter's arbitrary iteration order.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insertion_order() {
+        let counter = "the quick the lazy"
+            .split_whitespace()
+            .collect::<InsertionCounter<_>>();
+        let keys = counter.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys, vec!["the", "quick", "lazy"]);
+    }
+
+    #[test]
+    fn test_order_survives_arithmetic() {
+        let c = "abbccc".chars().collect::<InsertionCounter<_>>();
+        let d = "dccbb".chars().collect::<InsertionCounter<_>>();
+
+        let sum = c.clone() + d.clone();
+        assert_eq!(sum.keys().collect::<String>(), "abcd");
+        assert_eq!(
+            sum.most_common(),
+            vec![('c', 5), ('b', 4), ('a', 1), ('d', 1)]
+        );
+
+        let diff = c.clone() - d.clone();
+        assert_eq!(
+            diff.into_iter().collect::<Vec<_>>(),
+            vec![('a', 1), ('c', 1)]
+        );
+
+        let union = d.clone() | c.clone();
+        assert_eq!(union.keys().collect::<String>(), "dcba");
+        let intersection = d & c;
+        assert_eq!(
+            intersection.into_iter().collect::<Vec<_>>(),
+            vec![('c', 2), ('b', 2)]
+        );
+    }
+
+    #[test]
+    fn test_subtract_keeps_order() {
+        let mut counter = "abcd".chars().collect::<InsertionCounter<_>>();
+        counter -= "b".chars();
+        counter += "eb".chars();
+        assert_eq!(counter.keys().collect::<String>(), "acdeb");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let counter = "abbccc".chars().collect::<Counter<_>>();
+        let ordered = InsertionCounter::from(counter.clone());
+        assert_eq!(Counter::from(ordered), counter);
+    }
+}
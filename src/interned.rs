@@ -0,0 +1,384 @@
+//! A counter of strings which stores each distinct string once, in an arena.
+
+use hashbrown::hash_table::{Entry, HashTable};
+use num_traits::{One, Zero};
+
+use compat::hash::BuildHasher;
+use compat::iter::{self, FromIterator};
+use compat::ops::{AddAssign, Index, SubAssign};
+use compat::slice;
+use compat::string::{String, ToString};
+use compat::vec::Vec;
+use compat::DefaultHashBuilder;
+
+use super::Counter;
+
+/// A counter of strings which interns its keys.
+///
+/// Every distinct string is copied once into a single growing buffer and given a symbol id;
+/// counts are kept in a vector indexed by that id. Counting an already-seen string, whether
+/// from a `&str` or a `String`, never allocates, and lookups by `&str` need no temporary
+/// `String`. Requires the `interning` feature.
+///
+/// Removing a string zeroes its count but keeps it interned, so memory is only reclaimed by
+/// dropping the counter.
+///
+/// ```rust
+/// # use counter::StrCounter;
+/// let text = "the cat and the hat and the bat";
+/// let counter = text.split_whitespace().collect::<StrCounter>();
+/// assert_eq!(counter["the"], 3);
+/// assert_eq!(counter["dog"], 0);
+/// assert_eq!(counter.most_common()[..2], [("the", 3), ("and", 2)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StrCounter<N = usize> {
+    // every distinct string, back to back
+    arena: String,
+    // the `(start, end)` of each symbol's string in `arena`, indexed by symbol id
+    spans: Vec<(usize, usize)>,
+    // the count of each symbol, indexed by symbol id; zero for removed strings
+    counts: Vec<N>,
+    // the symbol ids, hashed by their strings
+    symbols: HashTable<usize>,
+    hash_builder: DefaultHashBuilder,
+    // the number of nonzero counts
+    len: usize,
+    // necessary for `Index::index` since we cannot declare generic `static` variables.
+    zero: N,
+}
+
+impl<N> StrCounter<N>
+where
+    N: Zero,
+{
+    /// Create a new, empty `StrCounter`
+    pub fn new() -> StrCounter<N> {
+        StrCounter {
+            arena: String::new(),
+            spans: Vec::new(),
+            counts: Vec::new(),
+            symbols: HashTable::new(),
+            hash_builder: DefaultHashBuilder::default(),
+            len: 0,
+            zero: N::zero(),
+        }
+    }
+
+    /// The number of distinct strings with a nonzero count.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no string has a nonzero count.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the count of `key`, or zero if it isn't present.
+    pub fn get(&self, key: &str) -> &N {
+        self.symbol(key)
+            .map_or(&self.zero, |symbol| &self.counts[symbol])
+    }
+
+    /// Whether `key` has a nonzero count in this counter.
+    pub fn contains_key(&self, key: &str) -> bool {
+        !self.get(key).is_zero()
+    }
+
+    /// Set the count of `key` to zero, returning its count if it was present.
+    pub fn remove(&mut self, key: &str) -> Option<N> {
+        let symbol = self.symbol(key)?;
+        let count = ::compat::mem::replace(&mut self.counts[symbol], N::zero());
+        if count.is_zero() {
+            return None;
+        }
+        self.len -= 1;
+        Some(count)
+    }
+
+    /// Iterate over `(&str, &count)` pairs, in the order the strings were first counted.
+    pub fn iter(&self) -> StrIter<'_, N> {
+        StrIter {
+            arena: &self.arena,
+            entries: self.spans.iter().zip(self.counts.iter()),
+        }
+    }
+
+    /// Move the counts into a regular `Counter` of owned strings.
+    pub fn into_counter(self) -> Counter<String, N> {
+        let arena = self.arena;
+        let mut counter = Counter::default();
+        counter.map.extend(
+            self.spans
+                .into_iter()
+                .zip(self.counts)
+                .filter(|(_, count)| !count.is_zero())
+                .map(|((start, end), count)| (arena[start..end].to_string(), count)),
+        );
+        counter
+    }
+
+    /// The symbol id of `key`, if it has been interned.
+    fn symbol(&self, key: &str) -> Option<usize> {
+        let hash = self.hash_builder.hash_one(key);
+        let (arena, spans) = (&self.arena, &self.spans);
+        self.symbols
+            .find(hash, |&symbol| resolve(arena, spans, symbol) == key)
+            .cloned()
+    }
+
+    /// The symbol id of `key`, interning it with a zero count if it is new.
+    fn intern(&mut self, key: &str) -> usize {
+        let hash = self.hash_builder.hash_one(key);
+        let StrCounter {
+            arena,
+            spans,
+            counts,
+            symbols,
+            hash_builder,
+            ..
+        } = self;
+        let entry = symbols.entry(
+            hash,
+            |&symbol| resolve(arena, spans, symbol) == key,
+            |&symbol| hash_builder.hash_one(resolve(arena, spans, symbol)),
+        );
+        match entry {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let symbol = spans.len();
+                spans.push((arena.len(), arena.len() + key.len()));
+                arena.push_str(key);
+                counts.push(N::zero());
+                entry.insert(symbol);
+                symbol
+            }
+        }
+    }
+}
+
+impl<N> StrCounter<N>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new `StrCounter` initialized with the given iterable of strings
+    pub fn init<I>(iterable: I) -> StrCounter<N>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut counter = StrCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the strings from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for item in iterable.into_iter() {
+            self.add_count(item.as_ref(), N::one());
+        }
+    }
+
+    /// Add `count` to the count of `key`.
+    pub fn add_count(&mut self, key: &str, count: N) {
+        let symbol = self.intern(key);
+        let entry = &mut self.counts[symbol];
+        let was_zero = entry.is_zero();
+        *entry += count;
+        match (was_zero, entry.is_zero()) {
+            (true, false) => self.len += 1,
+            (false, true) => self.len -= 1,
+            _ => {}
+        }
+    }
+
+    /// Remove the counts of the strings from the given iterable from this counter
+    ///
+    /// Non-positive counts are automatically removed
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        for item in iterable.into_iter() {
+            if let Some(symbol) = self.symbol(item.as_ref()) {
+                let entry = &mut self.counts[symbol];
+                if *entry > N::zero() {
+                    *entry -= N::one();
+                    if entry.is_zero() {
+                        self.len -= 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<N> StrCounter<N>
+where
+    N: Clone + Zero + AddAssign,
+{
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for count in &self.counts {
+            total += count.clone();
+        }
+        total
+    }
+}
+
+impl<N> StrCounter<N>
+where
+    N: Clone + Zero + Ord,
+{
+    /// Create a vector of `(string, frequency)` pairs, sorted most to least common.
+    ///
+    /// Strings with equal counts appear in the order they were first counted.
+    pub fn most_common(&self) -> Vec<(&str, N)> {
+        let mut items = self
+            .iter()
+            .map(|(item, count)| (item, count.clone()))
+            .collect::<Vec<_>>();
+        items.sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+        items
+    }
+}
+
+fn resolve<'a>(arena: &'a str, spans: &[(usize, usize)], symbol: usize) -> &'a str {
+    let (start, end) = spans[symbol];
+    &arena[start..end]
+}
+
+/// An iterator over the `(&str, &count)` pairs of a [`StrCounter`](struct.StrCounter.html).
+pub struct StrIter<'a, N: 'a> {
+    arena: &'a str,
+    entries: iter::Zip<slice::Iter<'a, (usize, usize)>, slice::Iter<'a, N>>,
+}
+
+impl<'a, N> Iterator for StrIter<'a, N>
+where
+    N: Zero,
+{
+    type Item = (&'a str, &'a N);
+
+    fn next(&mut self) -> Option<(&'a str, &'a N)> {
+        let arena = self.arena;
+        self.entries
+            .by_ref()
+            .find(|(_, count)| !count.is_zero())
+            .map(|(&(start, end), count)| (&arena[start..end], count))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.entries.size_hint().1)
+    }
+}
+
+impl<'a, N> IntoIterator for &'a StrCounter<N>
+where
+    N: Zero,
+{
+    type Item = (&'a str, &'a N);
+    type IntoIter = StrIter<'a, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, N> Index<&'a str> for StrCounter<N>
+where
+    N: Zero,
+{
+    type Output = N;
+
+    /// Index in immutable contexts
+    ///
+    /// Returns a reference to a `zero` value for missing keys.
+    fn index(&self, key: &'a str) -> &N {
+        self.get(key)
+    }
+}
+
+impl<N> Default for StrCounter<N>
+where
+    N: Zero,
+{
+    fn default() -> StrCounter<N> {
+        StrCounter::new()
+    }
+}
+
+impl<S, N> FromIterator<S> for StrCounter<N>
+where
+    S: AsRef<str>,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        StrCounter::init(iter)
+    }
+}
+
+impl<S, N> Extend<S> for StrCounter<N>
+where
+    S: AsRef<str>,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings_are_interned_once() {
+        let words = vec!["a".to_string(), "bb".to_string(), "a".to_string()];
+        let mut counter = StrCounter::<usize>::init(&words);
+        counter.update(vec!["bb", "ccc"]);
+        assert_eq!(counter.arena, "abbccc");
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter["a"], 2);
+        assert_eq!(counter["bb"], 2);
+        assert_eq!(counter.total(), 5);
+    }
+
+    #[test]
+    fn test_remove_and_subtract() {
+        let mut counter = StrCounter::<usize>::init(vec!["x", "y", "y", "z"]);
+        counter.subtract(vec!["x", "y", "w"]);
+        assert_eq!(counter.remove("z"), Some(1));
+        assert_eq!(counter.remove("z"), None);
+        assert!(!counter.contains_key("x"));
+        assert_eq!(counter.len(), 1);
+        assert_eq!(counter.iter().collect::<Vec<_>>(), vec![("y", &1)]);
+
+        counter.add_count("x", 4);
+        assert_eq!(counter.len(), 2);
+        assert!(
+            counter.into_counter().into_map()
+                == hashmap!{
+                    "x".to_string() => 4,
+                    "y".to_string() => 1,
+                }
+        );
+    }
+
+    #[test]
+    fn test_signed_counts() {
+        let mut counter = StrCounter::<i32>::new();
+        counter.add_count("a", -2);
+        assert_eq!(counter.len(), 1);
+        counter.add_count("a", 2);
+        assert!(counter.is_empty());
+        assert_eq!(counter.iter().count(), 0);
+    }
+}
@@ -49,6 +49,20 @@
 //! assert!(by_common == expected);
 //! ```
 //!
+//! ## Print a report
+//!
+//! A counter displays as a table of keys and counts, most common first. Use the precision to
+//! show only the top rows.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! let counter = "eaddbbccc".chars().collect::<Counter<_>>();
+//! assert_eq!(format!("{:.3}", counter), "c  3\nb  2\nd  2");
+//! ```
+//!
+//! The full table can be parsed back with `str::parse`. To checkpoint a counter compactly,
+//! use `write_to` and `read_from`, which use the binary format of the `snapshot` module.
+//!
 //! ## Treat it like a Map
 //!
 //! `Counter<T, N>` implements `Deref<Target=HashMap<T, N>>` and
@@ -62,6 +76,70 @@
 //! assert!(counter == "aabbcc".chars().collect::<Counter<_>>());
 //! ```
 //!
+//! Writing to the map directly can leave zero counts behind. To adjust a single count,
+//! use `add_count`, `remove_count`, or `entry`, whose guard removes an item when its
+//! count drops to zero.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! let mut counter = "aab".chars().collect::<Counter<_>>();
+//! *counter.entry('b') -= 1;
+//! assert!(!counter.contains_key(&'b'));
+//! ```
+//!
+//! ## Keep the keys sorted
+//!
+//! `OrderedCounter` is backed by a `BTreeMap`, so its keys iterate in sorted order and
+//! support range queries, with the same counting and arithmetic operations.
+//!
+//! ```rust
+//! # use counter::OrderedCounter;
+//! let counter = "banana".chars().collect::<OrderedCounter<_>>();
+//! assert_eq!(counter.keys().collect::<String>(), "abn");
+//! ```
+//!
+//! To remember the order in which keys were first seen instead, as Python's `Counter` does,
+//! enable the `indexmap` feature and use `InsertionCounter`.
+//!
+//! ## Count small alphabets faster
+//!
+//! When the keys come from a small set, specialized counters avoid hashing altogether:
+//! `SmallCounter` scans a short vector, `ByteCounter` indexes a table of 256 counts, and
+//! `CharCounter` does the same for ASCII while falling back to a map for other chars. Each
+//! converts to and from the generic `Counter`.
+//!
+//! ```rust
+//! # use counter::{ByteCounter, CharCounter};
+//! let bytes = b"hello".iter().cloned().collect::<ByteCounter>();
+//! assert_eq!(bytes[&b'l'], 2);
+//! let chars = "héllo".chars().collect::<CharCounter>();
+//! assert_eq!(chars.most_common()[0], ('l', 2));
+//! ```
+//!
+//! `ByteCounter::from_slice` and `ByteCounter::from_reader` tally large inputs over several
+//! tables at once, which is faster still than counting one byte at a time.
+//!
+//! For many keys with mostly small counts, `CompactCounter` keeps each count in a `u8` and
+//! only widens the entries which outgrow it, to `u16`, `u32` and `u64` in turn. This saves memory
+//! for keys aligned to fewer than 8 bytes, such as `u32` or `char`; entries with `String` or
+//! `&str` keys are padded to the same size whatever the width of their count.
+//!
+//! A `char` is a code point, not necessarily a whole character: "é" may be an "e" followed by
+//! a combining accent. With the `unicode` feature enabled, `Counter::from_graphemes` counts
+//! the grapheme clusters of a string instead, which is what a reader sees as characters.
+//!
+//! To count words which differ only in case or Unicode encoding as one, pass them through a
+//! function of the `normalize` module with `Counter::update_normalized`.
+//!
+//! `Counter::char_ngrams` and `Counter::word_ngrams` count the overlapping runs of `n` chars
+//! of a string or `n` tokens of an iterator, as used in language detection and fuzzy matching.
+//!
+//! To count the lines or words of a file, pass a `BufRead` to `Counter::from_lines` or
+//! `Counter::from_words`, which stream it rather than reading it all into memory.
+//! With the `mmap` feature enabled, `Counter::from_file_bytes` and `Counter::from_file_lines`
+//! map a file into memory and count it in place, without copying it through a buffer. They are
+//! `unsafe`, since the file must not be modified while it is mapped.
+//!
 //! # Advanced Usage
 //!
 //! ## Count any iterable which is `Hash + Eq`
@@ -97,13 +175,36 @@
 //!
 //! let inty_counts = intys.iter().collect::<Counter<_>>();
 //! println!("{:?}", inty_counts);
-//! // {Inty { i: 8 }: 2, Inty { i: 0 }: 3, Inty { i: 9 }: 1, Inty { i: 3 }: 1,
-//! //  Inty { i: 7 }: 1, Inty { i: 6 }: 1, Inty { i: 5 }: 1}
+//! // {Inty { i: 0 }: 3, Inty { i: 8 }: 2, Inty { i: 3 }: 1, Inty { i: 5 }: 1,
+//! //  Inty { i: 6 }: 1, Inty { i: 7 }: 1, Inty { i: 9 }: 1}
 //! assert!(inty_counts.get(&Inty { i: 8 }) == Some(&2));
 //! assert!(inty_counts.get(&Inty { i: 0 }) == Some(&3));
 //! assert!(inty_counts.get(&Inty { i: 6 }) == Some(&1));
 //! ```
 //!
+//! ## Own or borrow the keys
+//!
+//! A `Counter` owns its keys, so it can outlive the data it was built from.
+//! When you'd rather not copy the keys, count references into the source instead.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! fn word_counts(text: &str) -> Counter<String> {
+//!     let owned = text.to_lowercase();
+//!     owned.split_whitespace().map(String::from).collect()
+//! }
+//! let counts = word_counts("The cat and the hat");
+//! assert_eq!(counts.get("the"), Some(&2));
+//!
+//! let words = vec!["zero", "copy", "zero"];
+//! let borrowed = words.iter().collect::<Counter<_>>();
+//! assert_eq!(borrowed.get(&&"zero"), Some(&2));
+//! ```
+//!
+//! With the `interning` feature enabled, `StrCounter` owns its strings without allocating one
+//! per key: it copies each distinct string once into a shared buffer and counts by symbol id.
+//! The feature works with or without `std`, and leaves `Counter` on its usual map.
+//!
 //! ## Use your own type for the count
 //!
 //! Sometimes `usize` just isn't enough. If you find yourself overflowing your
@@ -117,26 +218,345 @@
 //! let expected: HashMap<char, i8> = [('a', 1), ('b', 2), ('c', 3)].iter().cloned().collect();
 //! assert!(counter.into_map() == expected);
 //! ```
+//!
+//! With the `bigint` feature enabled, this crate re-exports
+//! [`num_bigint`](https://docs.rs/num-bigint), whose arbitrary-precision integers can never
+//! overflow.
+//!
+//! ```rust
+//! # #[cfg(feature = "bigint")] {
+//! # use counter::Counter;
+//! use counter::num_bigint::BigUint;
+//! let counter: Counter<_, BigUint> = "abbccc".chars().collect();
+//! assert_eq!(counter[&'c'], BigUint::from(3u32));
+//! # }
+//! ```
+//!
+//! ## Use your own hasher
+//!
+//! Like `HashMap`, a `Counter` takes a third type parameter for the
+//! [`BuildHasher`](https://doc.rust-lang.org/std/hash/trait.BuildHasher.html) used to hash its
+//! keys. It defaults to the standard `RandomState`; construct a counter with any other hasher
+//! using `with_hasher`, or by collecting when the hasher is `Default`.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! use std::collections::hash_map::DefaultHasher;
+//! use std::hash::BuildHasherDefault;
+//!
+//! type Fixed = BuildHasherDefault<DefaultHasher>;
+//! let counter: Counter<_, usize, Fixed> = "abbccc".chars().collect();
+//! assert_eq!(counter[&'c'], 3);
+//! ```
+//!
+//! When hashing dominates your profile, the `fast-hash` feature provides `FastCounter`,
+//! a `Counter` which hashes with [`ahash`](https://docs.rs/ahash).
+//!
+//! ## Compute statistics
+//!
+//! The `stats` module treats counters as empirical distributions. It provides entropy,
+//! divergences, similarity measures such as `jaccard` and `cosine_similarity`, and the
+//! `chi_squared` and `g_test` goodness-of-fit tests. `into_distribution` turns counts into
+//! probabilities.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! use counter::stats::Unit;
+//!
+//! let counter = "aabbccdd".chars().collect::<Counter<_>>();
+//! assert_eq!(counter.entropy(Unit::Bits), 2.0);
+//! assert_eq!(counter.into_distribution()[&'a'], 0.25);
+//! ```
+//!
+//! With the `rand` feature enabled, `sample` and `sample_n` draw keys at random in proportion to
+//! their counts, and `sample_n_without_replacement` draws the counted elements like balls from
+//! an urn. `resample` draws a whole new counter of `n` elements from the same distribution,
+//! for bootstrap confidence intervals on the frequencies.
+//!
+//! ## Count only recent items
+//!
+//! `WindowedCounter` counts the last `n` items pushed into it, taking items off the count as
+//! they fall out of the window, and `TimeWindowedCounter` does the same for the items of the
+//! last span of time, such as the top queries of the last minute.
+//! `DecayCounter` instead lets every count fade, halving it each half-life, so that recently
+//! counted keys rank above keys which were common long ago.
+//! `RateCounter` records counts in time buckets, and reports each key's rate per second over
+//! a recent window.
+//!
+//! ```rust
+//! # use counter::WindowedCounter;
+//! let mut recent = WindowedCounter::<_>::new(4);
+//! recent.update("aaaabcbc".chars());
+//! assert_eq!(recent.most_common_ordered(), vec![('b', 2), ('c', 2)]);
+//! ```
+//!
+//! ## Count approximately
+//!
+//! When a stream has too many distinct keys to count exactly, the `sketch` module offers
+//! fixed-size approximate counters. `CountMin` estimates every key's count from a table of
+//! `width × depth` cells, never undercounting, with the error bound of your choosing.
+//! `TopK` keeps only the most frequent items of a stream, each with bounds on its true count.
+//! `Hll` estimates the number of distinct items, and `Counter::approx_unique` builds one from a
+//! counter's keys, so the distinct keys of several counters can be estimated without merging them.
+//! `PrefilteredCounter` keeps the keys seen only once out of its map with a Bloom filter, and
+//! can make its counts exact with a second pass over the data.
+//!
+//! ```rust
+//! # use counter::sketch::CountMin;
+//! let mut sketch = CountMin::with_error(0.001, 0.01);
+//! sketch.update("abracadabra".chars());
+//! assert!(sketch.query(&'a') >= 5);
+//! ```
+//!
+//! ## Count in parallel
+//!
+//! With the `rayon` feature enabled, counters can be collected from parallel iterators, and
+//! `par_update` adds the items of one to an existing counter. Each worker thread counts into
+//! its own counter, and the results are merged at the end.
+//!
+//! To count from threads you manage yourself, share a `ConcurrentCounter`. Its keys are split
+//! between independently locked shards; `snapshot` copies the counts into a regular `Counter`.
+//! For a few hot keys, `AtomicCounter` increments existing keys without taking any lock.
+//! To avoid synchronization on the hot path altogether, each thread can count into its own
+//! `LocalCounter` from a `LocalCounterSet`, which merges it into the set when flushed.
+//! With the `dashmap` feature enabled, `DashCounter` offers the same counting and merging
+//! methods on top of a `DashMap`.
+//!
+//! ## Serialize a counter
+//!
+//! With the `serde` feature enabled, `Counter` implements `Serialize` and `Deserialize`
+//! as a map from keys to counts.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde")] {
+//! # extern crate serde_json;
+//! # extern crate counter;
+//! # use counter::Counter;
+//! let counter = "aab".chars().collect::<Counter<_>>();
+//! let json = serde_json::to_string(&counter).unwrap();
+//! assert_eq!(serde_json::from_str::<Counter<char>>(&json).unwrap(), counter);
+//! # }
+//! ```
+//!
+//! JSON maps only allow string keys; to serialize a counter of other keys, such as tuples,
+//! as a list of `(key, count)` pairs, annotate the field with
+//! `#[serde(with = "counter::as_pairs")]`.
+//!
+//! The `csv` feature adds `to_csv` and `from_csv`, which write and read `key,count` rows
+//! sorted most common first, for use with spreadsheets and other data tools.
+//!
+//! ## Use it without the standard library
+//!
+//! The default `std` feature can be disabled for `#![no_std]` targets with an allocator. Enable
+//! the `hashbrown` feature instead, and counters are backed by `hashbrown::HashMap` with its
+//! default hasher:
+//!
+//! ```toml
+//! [dependencies]
+//! counter = { version = "0.4", default-features = false, features = ["hashbrown"] }
+//! ```
+//!
+//! The core `Counter`, `OrderedCounter`, `SmallCounter`, `ByteCounter` and `CharCounter` keep
+//! working, as does `WindowedCounter`; the concurrent and time-based counters, statistics,
+//! sketches, snapshots, reading from a `BufRead`, hashing a `Counter` itself, and the optional
+//! integrations all need `std`.
+//!
+//! Where there is no heap at all, `FixedCounter<T, CAP>` holds up to `CAP` distinct keys in an
+//! inline array, and returns a `CapacityError` instead of growing when a new key doesn't fit.
 
-#[cfg(test)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+
+// The unit tests use `std` even when the crate itself doesn't, so that they also run against
+// the `hashbrown` backend: `cargo test --lib --no-default-features --features hashbrown`. The
+// doctests are written for `std` users.
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+#[cfg(all(test, feature = "std"))]
 #[macro_use]
 extern crate maplit;
 
+// `maplit` builds standard maps, but without `std` counters are backed by `hashbrown`
+#[cfg(all(test, not(feature = "std")))]
+macro_rules! hashmap {
+    ($($key:expr => $value:expr),* $(,)*) => {{
+        #[allow(unused_mut)]
+        let mut map = ::compat::collections::HashMap::<_, _, ::compat::DefaultHashBuilder>::default();
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+
+#[cfg(all(test, not(feature = "std")))]
+macro_rules! btreemap {
+    ($($key:expr => $value:expr),* $(,)*) => {{
+        let mut map = ::compat::collections::BTreeMap::new();
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+#[cfg(test)]
+#[macro_use]
+extern crate quickcheck;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+compile_error!("counter needs either the `std` or the `hashbrown` feature");
+
+#[cfg(feature = "fast-hash")]
+extern crate ahash;
+#[cfg(feature = "csv")]
+pub extern crate csv;
+#[cfg(feature = "dashmap")]
+extern crate dashmap;
+#[cfg(any(feature = "hashbrown", feature = "interning"))]
+extern crate hashbrown;
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+#[cfg(feature = "mmap")]
+extern crate memmap2;
+#[cfg(feature = "bigint")]
+pub extern crate num_bigint;
+extern crate num_integer;
 extern crate num_traits;
-use num_traits::{One, Zero};
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "unicode")]
+extern crate unicode_normalization;
+#[cfg(feature = "unicode")]
+extern crate unicode_segmentation;
+use num_integer::Integer;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use compat::borrow::{Borrow, ToOwned};
+use compat::collections::{BTreeMap, HashMap};
+use compat::hash::{BuildHasher, Hash};
+use compat::iter;
+use compat::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Deref, DerefMut, Div, DivAssign,
+    Index, IndexMut, Mul, MulAssign, Sub, SubAssign,
+};
+use compat::vec::Vec;
+use compat::DefaultHashBuilder;
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(feature = "std")]
+use std::hash::Hasher;
+
+// declared first so that `ordered` and `insertion` can use its macro
+#[macro_use]
+mod map_counter;
+mod byte;
+mod chars;
+mod checked;
+mod compact;
+mod compat;
+#[cfg(feature = "std")]
+mod concurrent;
+#[cfg(feature = "csv")]
+mod csv_io;
+#[cfg(feature = "dashmap")]
+mod dash;
+#[cfg(feature = "std")]
+mod decay;
+mod entry;
+mod fixed;
+#[cfg(feature = "unicode")]
+mod graphemes;
+#[cfg(feature = "indexmap")]
+mod insertion;
+#[cfg(feature = "interning")]
+mod interned;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod memory;
+mod ngrams;
+pub mod normalize;
+mod ordered;
+pub mod overflow;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "std")]
+mod rate;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "rand")]
+mod sampling;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "std")]
+pub mod sketch;
+mod small;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod stats;
+mod text;
+mod windowed;
 
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::iter;
-use std::ops::{Add, AddAssign, BitAnd, BitOr, Deref, DerefMut, Sub, SubAssign};
+pub use byte::{ByteCounter, ByteIter};
+pub use chars::{CharCounter, CharIter};
+pub use checked::OverflowError;
+pub use compact::{CompactCounter, CompactIter};
+#[cfg(feature = "std")]
+pub use concurrent::{AtomicCounter, ConcurrentCounter, LocalCounter, LocalCounterSet};
+#[cfg(feature = "dashmap")]
+pub use dash::DashCounter;
+#[cfg(feature = "std")]
+pub use decay::DecayCounter;
+pub use entry::Entry;
+pub use fixed::{CapacityError, FixedCounter, FixedIter};
+#[cfg(feature = "indexmap")]
+pub use insertion::InsertionCounter;
+#[cfg(feature = "interning")]
+pub use interned::{StrCounter, StrIter};
+pub use memory::HeapSize;
+pub use ordered::OrderedCounter;
+#[cfg(feature = "std")]
+pub use rate::RateCounter;
+#[cfg(feature = "serde")]
+pub use serde_impls::as_pairs;
+pub use small::{SmallCounter, SmallIter};
+pub use text::ParseCounterError;
+#[cfg(feature = "std")]
+pub use windowed::TimeWindowedCounter;
+pub use windowed::WindowedCounter;
 
-type CounterMap<T, N> = HashMap<T, N>;
+type CounterMap<T, N, S> = HashMap<T, N, S>;
 
-#[derive(Clone, PartialEq, Eq, Debug, Default)]
-pub struct Counter<T: Hash + Eq, N = usize> {
-    map: CounterMap<T, N>,
+#[derive(Clone)]
+pub struct Counter<T: Hash + Eq, N = usize, S = DefaultHashBuilder> {
+    map: CounterMap<T, N, S>,
+    // necessary for `Index::index` since we cannot declare generic `static` variables.
+    zero: N,
 }
 
+/// A `Counter` which hashes its keys with [`ahash`](https://docs.rs/ahash) instead of SipHash.
+///
+/// This is much faster for small keys such as `char`s, bytes, and short strings, at the cost of
+/// SipHash's resistance to HashDoS attacks. Requires the `fast-hash` feature.
+///
+/// `new` and `init` only construct counters with the default hasher; build a `FastCounter`
+/// with `default`, or by collecting an iterator.
+///
+/// ```rust
+/// # #[cfg(feature = "fast-hash")] {
+/// use counter::FastCounter;
+/// let counter: FastCounter<_> = "abbccc".chars().collect();
+/// assert_eq!(counter[&'c'], 3);
+/// # }
+/// ```
+#[cfg(feature = "fast-hash")]
+pub type FastCounter<T, N = usize> = Counter<T, N, ahash::RandomState>;
+
 impl<T, N> Counter<T, N>
 where
     T: Hash + Eq,
@@ -144,9 +564,7 @@ where
 {
     /// Create a new, empty `Counter`
     pub fn new() -> Counter<T, N> {
-        Counter {
-            map: HashMap::new(),
-        }
+        Counter::with_hasher(DefaultHashBuilder::default())
     }
 
     /// Create a new `Counter` initialized with the given iterable
@@ -154,7 +572,91 @@ where
     where
         I: IntoIterator<Item = T>,
     {
-        let mut counter = Counter::new();
+        Counter::init_with_hasher(iterable, DefaultHashBuilder::default())
+    }
+
+    /// Create a new, empty `Counter` with room for at least `capacity` distinct items before
+    /// it reallocates.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter: Counter<char> = Counter::with_capacity(100);
+    /// assert!(counter.capacity() >= 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Counter<T, N> {
+        Counter::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+
+    /// Create a new `Counter` which counts the items of the given iterable by the key `key`
+    /// extracts from each of them.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// struct Request {
+    ///     path: &'static str,
+    ///     status: u16,
+    /// }
+    ///
+    /// let log = vec![
+    ///     Request { path: "/", status: 200 },
+    ///     Request { path: "/missing", status: 404 },
+    ///     Request { path: "/about", status: 200 },
+    /// ];
+    /// let by_status: Counter<u16> = Counter::from_iter_by(&log, |request| request.status);
+    /// assert_eq!(by_status[&200], 2);
+    /// assert_eq!(by_status[&404], 1);
+    /// # assert_eq!(log[1].path, "/missing");
+    /// ```
+    pub fn from_iter_by<I, F>(iterable: I, key: F) -> Counter<T, N>
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> T,
+    {
+        Counter::init(iterable.into_iter().map(key))
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Create a new, empty `Counter` which will use the given hash builder to hash keys.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    ///
+    /// let mut counter: Counter<_, usize, BuildHasherDefault<DefaultHasher>> =
+    ///     Counter::with_hasher(BuildHasherDefault::default());
+    /// counter.update("abb".chars());
+    /// assert_eq!(counter[&'b'], 2);
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> Counter<T, N, S> {
+        Counter {
+            map: HashMap::with_hasher(hash_builder),
+            zero: N::zero(),
+        }
+    }
+
+    /// Create a new, empty `Counter` with room for at least `capacity` distinct items, which
+    /// will use the given hash builder to hash keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Counter<T, N, S> {
+        Counter {
+            map: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+            zero: N::zero(),
+        }
+    }
+
+    /// Create a new `Counter` initialized with the given iterable, which will use the given
+    /// hash builder to hash keys.
+    pub fn init_with_hasher<I>(iterable: I, hash_builder: S) -> Counter<T, N, S>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = Counter::with_hasher(hash_builder);
         counter.update(iterable);
         counter
     }
@@ -170,491 +672,2556 @@ where
         }
     }
 
-    /// Consumes this counter and returns a HashMap mapping the items to the counts.
-    pub fn into_map(self) -> HashMap<T, N> {
-        self.map
+    /// Add the counts of the elements from the given iterable to this counter, after passing
+    /// each one through `normalize`
+    ///
+    /// Use this to count keys which differ only in form as the same key, such as words in
+    /// different cases; the [`normalize`](normalize/index.html) module has some common
+    /// normalizations.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = Counter::<String>::new();
+    /// counter.update_normalized(vec!["The", "the", " THE "], |word| word.trim().to_lowercase());
+    /// assert_eq!(counter["the"], 3);
+    /// ```
+    pub fn update_normalized<I, F>(&mut self, iterable: I, normalize: F)
+    where
+        I: IntoIterator,
+        F: FnMut(I::Item) -> T,
+    {
+        self.update(iterable.into_iter().map(normalize));
     }
 
-    /// Remove the counts of the elements from the given iterable to this counter
+    /// Add the counts from the given iterable of `(item, count)` pairs to this counter
     ///
-    /// Non-positive counts are automatically removed
+    /// This merges pre-aggregated counts without expanding them into repeated elements.
+    /// The counts of repeated items are summed, and entries whose total comes to zero are
+    /// removed. Every reader of saved counters, from text, CSV, snapshots or serde, follows
+    /// this rule too.
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
     /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
-    /// counter.subtract("abba".chars());
-    /// let expect = [('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// counter.update_with_counts(vec![('a', 4), ('d', 2)]);
+    /// let expect = [('a', 5), ('b', 2), ('c', 3), ('d', 2)].iter()
+    ///     .cloned().collect::<HashMap<_, _>>();
     /// assert_eq!(counter.into_map(), expect);
     /// ```
-    pub fn subtract<I>(&mut self, iterable: I)
+    pub fn update_with_counts<I>(&mut self, iterable: I)
     where
-        I: IntoIterator<Item = T>,
+        I: IntoIterator<Item = (T, N)>,
     {
-        for item in iterable.into_iter() {
-            let mut remove = false;
-            if let Some(entry) = self.map.get_mut(&item) {
-                if *entry > N::zero() {
-                    *entry -= N::one();
-                }
-                remove = *entry == N::zero();
-            }
-            if remove {
-                self.map.remove(&item);
-            }
+        for (item, item_count) in iterable.into_iter() {
+            self.add_count_pruned(item, item_count);
         }
     }
-}
 
-impl<T, N> Counter<T, N>
-where
-    T: Hash + Eq + Clone,
-    N: Clone + Ord,
-{
-    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
+    /// Create an iterator which yields each element as many times as its count.
+    ///
+    /// Elements appear in arbitrary order, but repetitions of one element are adjacent.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// let mc = "pappaopolo".chars().collect::<Counter<_>>().most_common();
-    /// let expected = vec![('p', 4), ('o', 3), ('a', 2), ('l', 1)];
-    /// assert_eq!(mc, expected);
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let mut elements = counter.elements().cloned().collect::<Vec<_>>();
+    /// elements.sort();
+    /// assert_eq!(elements, vec!['a', 'b', 'b', 'c', 'c', 'c']);
     /// ```
-    ///
-    /// Note that the ordering of duplicates is unstable.
-    pub fn most_common(&self) -> Vec<(T, N)> {
-        use std::cmp::Ordering;
-        self.most_common_tiebreaker(|ref _a, ref _b| Ordering::Equal)
+    pub fn elements(&self) -> Elements<'_, T, N> {
+        Elements {
+            entries: self.map.iter(),
+            current: None,
+        }
     }
 
-    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
+    /// Get the count of `key`, or zero if it isn't present.
     ///
-    /// In the event that two keys have an equal frequency, use the supplied ordering function
-    /// to further arrange the results.
+    /// Unlike `HashMap::get`, this never returns `None`; like Python's `Counter`,
+    /// missing keys simply have a count of zero. The key is not inserted.
     ///
-    /// For example, we can sort reverse-alphabetically:
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "aaab".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.get_or_zero(&'a'), &3);
+    /// assert_eq!(counter.get_or_zero(&'z'), &0);
+    /// ```
+    ///
+    /// Like `HashMap::get`, the key may be any borrowed form of the counter's key type:
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// let counter = "eaddbbccc".chars().collect::<Counter<_>>();
-    /// let by_common = counter.most_common_tiebreaker(|&a, &b| b.cmp(&a));
-    /// let expected = vec![('c', 3), ('d', 2), ('b', 2), ('e', 1), ('a', 1)];
-    /// assert_eq!(by_common, expected);
+    /// let counter = Counter::<_>::init(vec!["a".to_string(), "a".to_string()]);
+    /// assert_eq!(counter.get_or_zero("a"), &2);
     /// ```
-    pub fn most_common_tiebreaker<F>(&self, tiebreaker: F) -> Vec<(T, N)>
+    pub fn get_or_zero<Q>(&self, key: &Q) -> &N
     where
-        F: Fn(&T, &T) -> ::std::cmp::Ordering,
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
     {
-        use std::cmp::Ordering;
+        self.map.get(key).unwrap_or(&self.zero)
+    }
 
-        let mut items = self.map
-            .iter()
-            .map(|(key, count)| (key.clone(), count.clone()))
-            .collect::<Vec<_>>();
-        items.sort_by(|&(ref a_item, ref a_count), &(ref b_item, ref b_count)| {
-            match b_count.cmp(&a_count) {
-                Ordering::Equal => tiebreaker(&a_item, &b_item),
-                unequal @ _ => unequal,
-            }
-        });
-        items
+    /// Consumes this counter and returns a HashMap mapping the items to the counts.
+    pub fn into_map(self) -> HashMap<T, N, S> {
+        self.map
     }
-}
 
-impl<T, N> Counter<T, N>
-where
-    T: Hash + Eq + Clone + Ord,
-    N: Clone + Ord,
-{
-    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
-    ///
-    /// In the event that two keys have an equal frequency, use the natural ordering of the keys
-    /// to further sort the results.
+    /// An iterator over the items of this counter, in arbitrary order.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// let mc = "abracadabra".chars().collect::<Counter<_>>().most_common_ordered();
-    /// let expect = vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)];
-    /// assert_eq!(mc, expect);
+    /// let counter = "abb".chars().collect::<Counter<_>>();
+    /// let mut keys = counter.keys().cloned().collect::<Vec<_>>();
+    /// keys.sort();
+    /// assert_eq!(keys, vec!['a', 'b']);
     /// ```
-    pub fn most_common_ordered(&self) -> Vec<(T, N)> {
-        self.most_common_tiebreaker(|ref a, ref b| a.cmp(&b))
+    pub fn keys(&self) -> ::compat::collections::hash_map::Keys<'_, T, N> {
+        self.map.keys()
     }
-}
 
-impl<T, N> AddAssign for Counter<T, N>
-where
-    T: Clone + Hash + Eq,
-    N: Clone + Zero + AddAssign,
-{
-    /// Add another counter to this counter
+    /// An iterator over the counts of this counter, in arbitrary order.
     ///
-    /// `c += d;` -> `c[x] += d[x]` for all `x`
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abb".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.values().sum::<usize>(), 3);
+    /// ```
+    pub fn values(&self) -> ::compat::collections::hash_map::Values<'_, T, N> {
+        self.map.values()
+    }
+
+    /// An iterator over `(&item, &count)` pairs, in arbitrary order.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let mut c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
+    /// let counter = "abb".chars().collect::<Counter<_>>();
+    /// let mut pairs = counter.iter().collect::<Vec<_>>();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(&'a', &1), (&'b', &2)]);
+    /// ```
+    pub fn iter(&self) -> ::compat::collections::hash_map::Iter<'_, T, N> {
+        self.map.iter()
+    }
+
+    /// An iterator over `(&item, &mut count)` pairs, in arbitrary order.
     ///
-    /// c += d;
+    /// Counts set to zero through this iterator are not removed; prefer
+    /// [`retain`](#method.retain) when some counts may reach zero.
     ///
-    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(c.into_map(), expect);
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = "abb".chars().collect::<Counter<_>>();
+    /// for (_, count) in counter.iter_mut() {
+    ///     *count *= 10;
+    /// }
+    /// assert_eq!(counter[&'b'], 20);
     /// ```
-    fn add_assign(&mut self, rhs: Self) {
-        for (key, value) in rhs.map.iter() {
-            let entry = self.map.entry(key.clone()).or_insert(N::zero());
-            *entry += value.clone();
-        }
+    pub fn iter_mut(&mut self) -> ::compat::collections::hash_map::IterMut<'_, T, N> {
+        self.map.iter_mut()
     }
-}
 
-impl<T, N> Add for Counter<T, N>
-where
-    T: Clone + Hash + Eq,
-    N: Clone + PartialOrd + PartialEq + AddAssign + Zero,
-{
-    type Output = Counter<T, N>;
+    /// The number of distinct items the counter can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
 
-    /// Add two counters together.
+    /// Reserve room for at least `additional` more distinct items.
     ///
-    /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`
+    /// Pre-sizing a counter before a large counting job avoids rehashing as it grows.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional)
+    }
+
+    /// Shrink the capacity of the counter as much as possible.
+    ///
+    /// This reclaims memory after pruning, for example with
+    /// [`filter_below`](#method.filter_below) or [`retain`](#method.retain).
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
+    /// let mut counter = Counter::<_>::init(0..1000);
+    /// counter.update(0..10);
+    /// counter.filter_below(2);
+    /// counter.shrink_to_fit();
+    /// assert_eq!(counter.len(), 10);
+    /// assert!(counter.capacity() < 1000);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.map.shrink_to_fit()
+    }
+
+    /// Transform every count with `f`, producing a counter with a possibly different count
+    /// type.
     ///
-    /// let e = c + d;
+    /// Items whose new count is zero are dropped.
     ///
-    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(e.into_map(), expect);
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbcccc".chars().collect::<Counter<_>>();
+    /// let logs = counter.map_counts(|count| (count as f64).log2());
+    /// assert_eq!(logs[&'c'], 2.0);
+    /// assert!(!logs.contains_key(&'a'));
     /// ```
-    fn add(self, rhs: Counter<T, N>) -> Self::Output {
-        let mut counter = self.clone();
-        counter += rhs;
+    pub fn map_counts<M, F>(self, mut f: F) -> Counter<T, M, S>
+    where
+        M: Zero,
+        F: FnMut(N) -> M,
+        S: Clone,
+    {
+        let mut map = HashMap::with_capacity_and_hasher(self.map.len(), self.map.hasher().clone());
+        for (item, count) in self.map {
+            let count = f(count);
+            if !count.is_zero() {
+                map.insert(item, count);
+            }
+        }
+        Counter {
+            map,
+            zero: M::zero(),
+        }
+    }
+
+    /// Re-key every item with `f`, summing the counts of items which map to the same key.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let words = "The cat saw the dog".split_whitespace().collect::<Counter<_>>();
+    /// let lower = words.map_keys(|word| word.to_lowercase());
+    /// assert_eq!(lower[&"the".to_string()], 2);
+    /// assert_eq!(lower.len(), 4);
+    /// ```
+    pub fn map_keys<U, F>(self, mut f: F) -> Counter<U, N, S>
+    where
+        U: Hash + Eq,
+        F: FnMut(T) -> U,
+        S: Clone,
+    {
+        let mut counter = Counter::with_hasher(self.map.hasher().clone());
+        counter.update_with_counts(self.map.into_iter().map(|(item, count)| (f(item), count)));
         counter
     }
-}
 
-impl<T, N> SubAssign for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
-{
-    /// Subtract (keeping only positive values).
+    /// Remove the counts of the elements from the given iterable to this counter
     ///
-    /// `c -= d;` -> `c[x] -= d[x]` for all `x`,
-    /// keeping only items with a value greater than N::zero().
+    /// Non-positive counts are automatically removed
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let mut c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// counter.subtract("abba".chars());
+    /// let expect = [('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            let mut remove = false;
+            if let Some(entry) = self.map.get_mut(&item) {
+                if *entry > N::zero() {
+                    *entry -= N::one();
+                }
+                remove = *entry == N::zero();
+            }
+            if remove {
+                self.map.remove(&item);
+            }
+        }
+    }
+
+    /// Remove the counts from the given iterable of `(item, count)` pairs from this counter
     ///
-    /// c -= d;
+    /// As with [`subtract`](#method.subtract), counts never go below zero: items which
+    /// aren't present are ignored, and entries which reach zero are removed.
     ///
-    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(c.into_map(), expect);
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// counter.subtract_with_counts(vec![('a', 4), ('c', 2), ('d', 2)]);
+    /// let expect = [('b', 2), ('c', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
     /// ```
-    fn sub_assign(&mut self, rhs: Self) {
-        for (key, value) in rhs.map.iter() {
+    pub fn subtract_with_counts<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = (T, N)>,
+    {
+        for (item, item_count) in iterable.into_iter() {
             let mut remove = false;
-            if let Some(entry) = self.map.get_mut(key) {
-                if *entry >= *value {
-                    *entry -= value.clone();
+            if let Some(entry) = self.map.get_mut(&item) {
+                if *entry > item_count {
+                    *entry -= item_count;
                 } else {
                     remove = true;
                 }
-                if *entry == N::zero() {
-                    remove = true;
-                }
             }
             if remove {
-                self.map.remove(key);
+                self.map.remove(&item);
             }
         }
     }
-}
-
-impl<T, N> Sub for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
-{
-    type Output = Counter<T, N>;
 
-    /// Subtract (keeping only positive values).
+    /// Add `count` to the count of a single item
     ///
-    /// `out = c - d;` -> `out[x] == c[x] - d[x]` for all `x`,
-    /// keeping only items with a value greater than N::zero().
+    /// An entry whose count comes to zero is removed, as with
+    /// [`update_with_counts`](#method.update_with_counts).
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
-    ///
-    /// let e = c - d;
-    ///
-    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(e.into_map(), expect);
+    /// let mut counter = "aab".chars().collect::<Counter<_>>();
+    /// counter.add_count('a', 3);
+    /// counter.add_count('z', 1);
+    /// assert_eq!(counter[&'a'], 5);
+    /// assert_eq!(counter[&'z'], 1);
     /// ```
-    fn sub(mut self, rhs: Counter<T, N>) -> Self::Output {
-        self -= rhs;
-        self
+    pub fn add_count(&mut self, key: T, count: N) {
+        self.update_with_counts(iter::once((key, count)));
     }
-}
-
-impl<T, N> BitAnd for Counter<T, N>
-where
-    T: Clone + Hash + Eq,
-    N: Clone + Ord + AddAssign + SubAssign + Zero + One,
-{
-    type Output = Counter<T, N>;
 
-    /// Intersection
+    /// Add `count` to the count of a single item, given in a borrowed form
     ///
-    /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+    /// The key is only copied into an owned `T` if it isn't in the counter yet, so counting
+    /// `&str`s into a `Counter<String>` allocates once per distinct string.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
-    ///
-    /// let e = c & d;
-    ///
-    /// let expect = [('a', 1), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(e.into_map(), expect);
+    /// let mut counter: Counter<String> = Counter::new();
+    /// for word in "a rose is a rose".split_whitespace() {
+    ///     counter.add_count_borrowed(word, 1);
+    /// }
+    /// assert_eq!(counter["rose"], 2);
+    /// assert!(counter.contains_key("is"));
     /// ```
-    fn bitand(self, rhs: Counter<T, N>) -> Self::Output {
-        use std::cmp::min;
-        use std::collections::HashSet;
-
-        let self_keys = self.map.keys().collect::<HashSet<_>>();
-        let other_keys = rhs.map.keys().collect::<HashSet<_>>();
-        let both_keys = self_keys.intersection(&other_keys);
-
-        let mut counter = Counter::new();
-        for key in both_keys {
-            counter.map.insert(
-                (*key).clone(),
-                min(self.map.get(*key).unwrap(), rhs.map.get(*key).unwrap()).clone(),
-            );
+    pub fn add_count_borrowed<Q>(&mut self, key: &Q, count: N)
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq + ToOwned<Owned = T>,
+    {
+        if let Some(entry) = self.map.get_mut(key) {
+            *entry += count;
+            if entry.is_zero() {
+                self.map.remove(key);
+            }
+        } else if !count.is_zero() {
+            self.map.insert(key.to_owned(), count);
         }
-
-        counter
     }
-}
-
-impl<T, N> BitOr for Counter<T, N>
-where
-    T: Clone + Hash + Eq,
-    N: Clone + Ord + Zero,
-{
-    type Output = Counter<T, N>;
 
-    /// Union
+    /// Remove `count` from the count of a single item, returning the count which remains
     ///
-    /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+    /// As with [`subtract`](#method.subtract), counts never go below zero and an entry
+    /// which reaches zero is removed. Removing from an absent item leaves the counter
+    /// unchanged and returns zero.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let c = "aaab".chars().collect::<Counter<_>>();
-    /// let d = "abb".chars().collect::<Counter<_>>();
-    ///
-    /// let e = c | d;
-    ///
-    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(e.into_map(), expect);
+    /// let mut counter = "aaab".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.remove_count(&'a', 2), 1);
+    /// assert_eq!(counter.remove_count(&'b', 5), 0);
+    /// assert!(!counter.contains_key(&'b'));
     /// ```
-    fn bitor(self, rhs: Counter<T, N>) -> Self::Output {
-        use std::cmp::max;
-
-        let mut counter = self.clone();
-        for (key, value) in rhs.map.iter() {
-            let entry = counter.map.entry(key.clone()).or_insert(N::zero());
-            *entry = max(&*entry, value).clone();
+    pub fn remove_count<Q>(&mut self, key: &Q, count: N) -> N
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        N: Clone,
+    {
+        let mut remaining = N::zero();
+        if let Some(entry) = self.map.get_mut(key) {
+            if *entry > count {
+                *entry -= count;
+                remaining = entry.clone();
+            }
         }
-        counter
-    }
-}
-
-impl<T, N> Deref for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: Clone,
-{
-    type Target = CounterMap<T, N>;
-    fn deref(&self) -> &CounterMap<T, N> {
-        &self.map
-    }
-}
-
-impl<T, N> DerefMut for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: Clone,
-{
-    fn deref_mut(&mut self) -> &mut CounterMap<T, N> {
-        &mut self.map
+        if remaining.is_zero() {
+            self.map.remove(key);
+        }
+        remaining
     }
-}
 
-impl<I, T, N> AddAssign<I> for Counter<T, N>
-where
-    I: IntoIterator<Item = T>,
-    T: Hash + Eq,
-    N: PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    /// Directly add the counts of the elements of `I` to `self`
+    /// Remove all entries whose count is not positive.
+    ///
+    /// This mirrors Python's unary `+counter`. It is mainly useful for signed count types,
+    /// after [`subtract_signed`](#method.subtract_signed) has driven some counts negative.
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let mut counter = Counter::init("abbccc".chars());
-    ///
-    /// counter += "aeeeee".chars();
-    /// let expected: HashMap<char, usize> = [('a', 2), ('b', 2), ('c', 3), ('e', 5)]
-    ///     .iter().cloned().collect();
-    /// assert_eq!(counter.into_map(), expected);
+    /// let mut counter = "abb".chars().collect::<Counter<_, i32>>();
+    /// counter.subtract_signed("aaab".chars());
+    /// counter.keep_positive();
+    /// let expect = [('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
     /// ```
-    fn add_assign(&mut self, rhs: I) {
-        self.update(rhs);
+    pub fn keep_positive(&mut self) {
+        self.map.retain(|_, count| *count > N::zero());
     }
-}
 
-impl<I, T, N> Add<I> for Counter<T, N>
-where
-    I: IntoIterator<Item = T>,
-    T: Hash + Eq,
-    N: PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    type Output = Self;
-    /// Consume self producing a Counter like self updated with the counts of
-    /// the elements of I.
+    /// Consume this counter, keeping only the entries whose count is positive.
+    ///
+    /// This is Python's unary `+counter`; see [`keep_positive`](#method.keep_positive) for
+    /// the in-place version.
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let counter = Counter::init("abbccc".chars());
-    ///
-    /// let new_counter = counter + "aeeeee".chars();
-    /// let expected: HashMap<char, usize> = [('a', 2), ('b', 2), ('c', 3), ('e', 5)]
-    ///     .iter().cloned().collect();
-    /// assert_eq!(new_counter.into_map(), expected);
+    /// let mut counter = "abb".chars().collect::<Counter<_, i32>>();
+    /// counter.subtract_signed("aaab".chars());
+    /// let expect = [('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_positive().into_map(), expect);
     /// ```
-    fn add(mut self, rhs: I) -> Self::Output {
-        self.update(rhs);
+    pub fn into_positive(mut self) -> Counter<T, N, S> {
+        self.keep_positive();
         self
     }
-}
 
-impl<I, T, N> SubAssign<I> for Counter<T, N>
-where
-    I: IntoIterator<Item = T>,
-    T: Hash + Eq,
-    N: PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    /// Directly subtract the counts of the elements of `I` from `self`,
-    /// keeping only items with a value greater than N::zero().
+    /// Remove every item from this counter, yielding the owned `(item, count)` pairs.
+    ///
+    /// The counter is empty afterwards, even if the iterator is dropped early; its allocated
+    /// memory is kept for reuse.
     ///
     /// ```rust
     /// # use counter::Counter;
-    /// # use std::collections::HashMap;
-    /// let mut c = "aaab".chars().collect::<Counter<_>>();
-    /// c -= "abb".chars();
-    ///
-    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(c.into_map(), expect);
+    /// let mut counter = "abb".chars().collect::<Counter<_>>();
+    /// let mut pairs = counter.drain().collect::<Vec<_>>();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![('a', 1), ('b', 2)]);
+    /// assert!(counter.is_empty());
     /// ```
-    fn sub_assign(&mut self, rhs: I) {
-        self.subtract(rhs);
+    pub fn drain(&mut self) -> ::compat::collections::hash_map::Drain<'_, T, N> {
+        self.map.drain()
     }
-}
 
-impl<I, T, N> Sub<I> for Counter<T, N>
-where
-    I: IntoIterator<Item = T>,
-    T: Clone + Hash + Eq,
-    N: Clone + PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    type Output = Self;
-    /// Consume self producing a Counter like self with the counts of the
-    /// elements of I subtracted, keeping only positive values.
+    /// Remove the items for which `pred(&item, &count)` returns `true`, yielding the owned
+    /// `(item, count)` pairs.
+    ///
+    /// Items are only examined as the iterator advances; if it is dropped early, the
+    /// remaining items stay in the counter.
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let c = "aaab".chars().collect::<Counter<_>>();
-    /// let e = c - "abb".chars();
-    ///
-    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
-    /// assert_eq!(e.into_map(), expect);
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let mut rare = counter.extract_if(|_, &count| count < 3).collect::<Vec<_>>();
+    /// rare.sort();
+    /// assert_eq!(rare, vec![('a', 1), ('b', 2)]);
+    /// let expect = [('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
     /// ```
-    fn sub(self, rhs: I) -> Self::Output {
-        let mut ctr = self.clone();
-        ctr.subtract(rhs);
-        ctr
+    pub fn extract_if<'a, F>(&'a mut self, mut pred: F) -> impl Iterator<Item = (T, N)> + 'a
+    where
+        F: FnMut(&T, &N) -> bool + 'a,
+    {
+        self.map.extract_if(move |item, count| pred(item, count))
     }
-}
 
-impl<T, N> iter::FromIterator<T> for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    /// Produce a Counter from an iterator of items. This is called automatically
-    /// by `iter.collect()`.
+    /// Keep only the items for which `f(&item, &mut count)` returns `true`.
+    ///
+    /// The predicate may also adjust counts in place; items whose count is left at zero are
+    /// removed either way.
     ///
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let counter = "abbccc".chars().collect::<Counter<_>>();
-    /// let expect = [('a', 1), ('b', 2), ('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// let mut counter = "aaabbc-".chars().collect::<Counter<_>>();
+    /// counter.retain(|&c, count| {
+    ///     *count -= 1;
+    ///     c.is_alphabetic()
+    /// });
+    /// let expect = [('a', 2), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
     /// assert_eq!(counter.into_map(), expect);
     /// ```
-    ///
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        Counter::<T, N>::init(iter)
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T, &mut N) -> bool,
+    {
+        self.map.retain(|item, count| f(item, count) && !count.is_zero());
     }
-}
 
-impl<T, N> iter::FromIterator<(T, N)> for Counter<T, N>
-where
-    T: Hash + Eq,
-    N: PartialOrd + AddAssign + SubAssign + Zero + One,
-{
-    /// `from_iter` creates a counter from `(item, count)` tuples.
+    /// Remove every item whose count is below `threshold`.
     ///
-    /// The counts of duplicate items are summed.
     /// ```rust
     /// # use counter::Counter;
     /// # use std::collections::HashMap;
-    /// let counter = [('a', 1), ('b', 2), ('c', 3), ('a', 4)].iter()
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// counter.filter_below(2);
+    /// let expect = [('b', 2), ('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    pub fn filter_below(&mut self, threshold: N) {
+        self.map.retain(|_, count| *count >= threshold);
+    }
+
+    /// The most frequent item, or `None` if the counter is empty.
+    ///
+    /// When several items share the highest count, an arbitrary one of them is returned;
+    /// use [`modes`](#method.modes) to get them all.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.mode(), Some(&'c'));
+    /// assert_eq!(Counter::<char>::new().mode(), None);
+    /// ```
+    pub fn mode(&self) -> Option<&T> {
+        let mut mode: Option<(&T, &N)> = None;
+        for (item, count) in self.map.iter() {
+            if mode.is_none_or(|(_, max)| count > max) {
+                mode = Some((item, count));
+            }
+        }
+        mode.map(|(item, _)| item)
+    }
+
+    /// All the items which share the highest count, in arbitrary order.
+    ///
+    /// This is empty if the counter is empty.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbcc".chars().collect::<Counter<_>>();
+    /// let mut modes = counter.modes();
+    /// modes.sort();
+    /// assert_eq!(modes, vec![&'b', &'c']);
+    /// ```
+    pub fn modes(&self) -> Vec<&T> {
+        let max = match self.mode() {
+            Some(item) => &self.map[item],
+            None => return Vec::new(),
+        };
+        self.map
+            .iter()
+            .filter(|&(_, count)| count == max)
+            .map(|(item, _)| item)
+            .collect()
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + Zero,
+    S: BuildHasher,
+{
+    /// Whether every count in this counter is at most the corresponding count in `other`.
+    ///
+    /// Absent items count as zero, so this is multiset inclusion.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let word = "bee".chars().collect::<Counter<_>>();
+    /// let tiles = "abeeq".chars().collect::<Counter<_>>();
+    /// assert!(word.is_subset(&tiles));
+    /// assert!(!"beep".chars().collect::<Counter<_>>().is_subset(&tiles));
+    /// ```
+    pub fn is_subset(&self, other: &Counter<T, N, S>) -> bool {
+        let zero = N::zero();
+        self.map
+            .iter()
+            .all(|(item, count)| count <= other.map.get(item).unwrap_or(&zero))
+            && other
+                .map
+                .iter()
+                .all(|(item, count)| self.map.get(item).unwrap_or(&zero) <= count)
+    }
+
+    /// Whether every count in this counter is at least the corresponding count in `other`.
+    ///
+    /// This is the same as `other.is_subset(self)`.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let tiles = "abeeq".chars().collect::<Counter<_>>();
+    /// assert!(tiles.is_superset(&"bee".chars().collect()));
+    /// ```
+    pub fn is_superset(&self, other: &Counter<T, N, S>) -> bool {
+        other.is_subset(self)
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone,
+    N: Zero,
+    S: BuildHasher + Clone,
+{
+    /// Merge two counters, combining the counts of each item with `f(&c[x], &d[x])`.
+    ///
+    /// `f` is called once for every item in either counter, with a count of zero for the
+    /// counter which lacks it. Items for which `f` returns zero are left out.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abbc".chars().collect::<Counter<_>>();
+    /// let products = c.merge_with(&d, |a, b| a * b);
+    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(products.into_map(), expect);
+    /// ```
+    pub fn merge_with<F>(&self, other: &Counter<T, N, S>, mut f: F) -> Counter<T, N, S>
+    where
+        F: FnMut(&N, &N) -> N,
+    {
+        let mut map = HashMap::with_hasher(self.map.hasher().clone());
+        for (item, count) in self.map.iter() {
+            let merged = f(count, other.map.get(item).unwrap_or(&other.zero));
+            if !merged.is_zero() {
+                map.insert(item.clone(), merged);
+            }
+        }
+        for (item, count) in other.map.iter() {
+            if !self.map.contains_key(item) {
+                let merged = f(&self.zero, count);
+                if !merged.is_zero() {
+                    map.insert(item.clone(), merged);
+                }
+            }
+        }
+        Counter {
+            map,
+            zero: N::zero(),
+        }
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone,
+    N: Clone + PartialOrd + SubAssign + Zero,
+    S: BuildHasher + Clone,
+{
+    /// The absolute difference of the counts of each item, `|c[x] - d[x]|`.
+    ///
+    /// This measures the total disagreement between two counters: its total is zero only
+    /// when they are equal.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abbc".chars().collect::<Counter<_>>();
+    /// let expect = [('a', 2), ('b', 1), ('c', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.symmetric_difference(&d).into_map(), expect);
+    /// ```
+    pub fn symmetric_difference(&self, other: &Counter<T, N, S>) -> Counter<T, N, S> {
+        self.merge_with(other, |a, b| {
+            let (larger, smaller) = if a > b { (a, b) } else { (b, a) };
+            let mut difference = larger.clone();
+            difference -= smaller.clone();
+            difference
+        })
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Integer + Clone,
+    S: BuildHasher,
+{
+    /// Divide every integer count by `divisor`, rounding towards negative infinity.
+    ///
+    /// Items whose count rounds to zero are removed. Panics if `divisor` is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = "abbbbbb".chars().collect::<Counter<_>>().div_floor(4);
+    /// let expect = [('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    ///
+    /// Only integer counts can be divided this way; a counter of floats has no rounding
+    /// division:
+    ///
+    /// ```compile_fail
+    /// # use counter::Counter;
+    /// let counter = ['a'].iter().map(|&c| (c, 3.0)).collect::<Counter<char, f64>>();
+    /// counter.div_floor(2.0);
+    /// ```
+    pub fn div_floor(self, divisor: N) -> Counter<T, N, S> {
+        self.divide(|count| count.div_floor(&divisor))
+    }
+
+    /// Divide every integer count by `divisor`, rounding towards positive infinity.
+    ///
+    /// Items whose count rounds to zero are removed. Panics if `divisor` is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = "abbbbbb".chars().collect::<Counter<_>>().div_ceil(4);
+    /// let expect = [('a', 1), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use counter::Counter;
+    /// let counter = ['a'].iter().map(|&c| (c, 3.0)).collect::<Counter<char, f64>>();
+    /// counter.div_ceil(2.0);
+    /// ```
+    pub fn div_ceil(self, divisor: N) -> Counter<T, N, S> {
+        self.divide(|count| Integer::div_ceil(count, &divisor))
+    }
+
+    /// Divide every integer count by `divisor`, rounding to the nearest integer and halves
+    /// away from zero.
+    ///
+    /// Items whose count rounds to zero are removed. Panics if `divisor` is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = "abbcccccc".chars().collect::<Counter<_>>().div_round(4);
+    /// let expect = [('b', 1), ('c', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    pub fn div_round(self, divisor: N) -> Counter<T, N, S> {
+        use compat::cmp::Ordering;
+
+        self.divide(|count| {
+            // The floored remainder lies between zero and the divisor; round up when it is
+            // nearer the divisor, or halfway there with a quotient on the positive side.
+            let (quotient, remainder) = count.div_mod_floor(&divisor);
+            let rest = divisor.clone() - remainder.clone();
+            let nearer = if divisor > N::zero() {
+                remainder.cmp(&rest)
+            } else {
+                rest.cmp(&remainder)
+            };
+            match nearer {
+                Ordering::Greater => quotient + N::one(),
+                Ordering::Equal if quotient >= N::zero() => quotient + N::one(),
+                _ => quotient,
+            }
+        })
+    }
+
+    // Replace each count by `divide(count)`, dropping those which come to zero.
+    fn divide<F>(mut self, mut divide: F) -> Counter<T, N, S>
+    where
+        F: FnMut(&N) -> N,
+    {
+        self.map.retain(|_, count| {
+            *count = divide(count);
+            !count.is_zero()
+        });
+        self
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Signed + SubAssign,
+    S: BuildHasher,
+{
+    /// Remove the counts of the elements from the given iterable, allowing counts to go negative
+    ///
+    /// Unlike [`subtract`](#method.subtract), this mirrors Python's `Counter.subtract`:
+    /// elements which aren't present get negative counts. Entries which reach exactly zero
+    /// are still removed.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abb".chars().collect::<Counter<_, i32>>();
+    /// counter.subtract_signed("aaab".chars());
+    /// let expect = [('a', -2), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    pub fn subtract_signed<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        use compat::collections::hash_map::Entry;
+
+        for item in iterable.into_iter() {
+            match self.map.entry(item) {
+                Entry::Occupied(mut entry) => {
+                    *entry.get_mut() -= N::one();
+                    if entry.get().is_zero() {
+                        entry.remove();
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(-N::one());
+                }
+            }
+        }
+    }
+
+    /// Consume this counter, negating every count and keeping only the results which are
+    /// positive.
+    ///
+    /// This mirrors Python's unary `-counter`: the result holds the items whose count was
+    /// negative.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abb".chars().collect::<Counter<_, i32>>();
+    /// counter.subtract_signed("aaab".chars());
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_negated().into_map(), expect);
+    /// ```
+    pub fn into_negated(mut self) -> Counter<T, N, S> {
+        self.map.retain(|_, count| {
+            let value = ::compat::mem::replace(count, N::zero());
+            *count = -value;
+            count.is_positive()
+        });
+        self
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + Zero + AddAssign,
+    S: BuildHasher,
+{
+    /// Sum of all the counts in this counter.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.total(), 6);
+    /// ```
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for count in self.map.values() {
+            total += count.clone();
+        }
+        total
+    }
+
+    /// Add `weight` to the count of each element from the given iterable
+    ///
+    /// This is useful with non-integer count types, to accumulate weights or scores rather
+    /// than occurrences.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut scores: Counter<&str, f64> = Counter::new();
+    /// scores.update_weighted("the cat".split_whitespace(), 0.5);
+    /// scores.update_weighted("the dog".split_whitespace(), 0.25);
+    /// assert_eq!(scores[&"the"], 0.75);
+    /// assert_eq!(scores[&"dog"], 0.25);
+    /// ```
+    pub fn update_weighted<I>(&mut self, iterable: I, weight: N)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            let entry = self.map.entry(item).or_insert_with(N::zero);
+            *entry += weight.clone();
+        }
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone,
+    N: Clone,
+    S: BuildHasher,
+{
+    /// Create a vector of `(elem, frequency)` pairs, sorted by the supplied comparison function.
+    ///
+    /// The comparator receives `(a_item, a_count, b_item, b_count)` and has full control of the
+    /// ordering. For example, we can order by descending count, breaking ties on shorter keys:
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "a bb ccc bb a dd".split_whitespace().collect::<Counter<_>>();
+    /// let by_common = counter.most_common_by(|a, a_count, b, b_count| {
+    ///     b_count.cmp(a_count).then(a.len().cmp(&b.len()))
+    /// });
+    /// let expected = vec![("a", 2), ("bb", 2), ("dd", 1), ("ccc", 1)];
+    /// assert_eq!(by_common, expected);
+    /// ```
+    pub fn most_common_by<F>(&self, mut compare: F) -> Vec<(T, N)>
+    where
+        F: FnMut(&T, &N, &T, &N) -> ::compat::cmp::Ordering,
+    {
+        let mut items = self
+            .map
+            .iter()
+            .map(|(key, count)| (key.clone(), count.clone()))
+            .collect::<Vec<_>>();
+        items.sort_by(|(a_item, a_count), (b_item, b_count)| {
+            compare(a_item, a_count, b_item, b_count)
+        });
+        items
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone,
+    N: Clone + Ord,
+    S: BuildHasher,
+{
+    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mc = "pappaopolo".chars().collect::<Counter<_>>().most_common();
+    /// let expected = vec![('p', 4), ('o', 3), ('a', 2), ('l', 1)];
+    /// assert_eq!(mc, expected);
+    /// ```
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, N)> {
+        use compat::cmp::Ordering;
+        self.most_common_tiebreaker(|_a, _b| Ordering::Equal)
+    }
+
+    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
+    ///
+    /// In the event that two keys have an equal frequency, use the supplied ordering function
+    /// to further arrange the results.
+    ///
+    /// For example, we can sort reverse-alphabetically:
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "eaddbbccc".chars().collect::<Counter<_>>();
+    /// let by_common = counter.most_common_tiebreaker(|&a, &b| b.cmp(&a));
+    /// let expected = vec![('c', 3), ('d', 2), ('b', 2), ('e', 1), ('a', 1)];
+    /// assert_eq!(by_common, expected);
+    /// ```
+    pub fn most_common_tiebreaker<F>(&self, tiebreaker: F) -> Vec<(T, N)>
+    where
+        F: Fn(&T, &T) -> ::compat::cmp::Ordering,
+    {
+        use compat::cmp::Ordering;
+
+        self.most_common_by(|a_item, a_count, b_item, b_count| {
+            match b_count.cmp(a_count) {
+                Ordering::Equal => tiebreaker(a_item, b_item),
+                unequal => unequal,
+            }
+        })
+    }
+
+    /// Create a lazy iterator over `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Unlike [`most_common`](#method.most_common), this doesn't sort up front: building the
+    /// iterator is linear in the number of keys, and each call to `next` costs a heap pop.
+    /// Consumers which only `take` a few items don't pay for a full sort.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "pappaopolo".chars().collect::<Counter<_>>();
+    /// let top = counter.most_common_iter().take(2).collect::<Vec<_>>();
+    /// let expected = vec![('p', 4), ('o', 3)];
+    /// assert_eq!(top, expected);
+    /// ```
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common_iter(&self) -> MostCommon<'_, T, N> {
+        let entries = self
+            .map
+            .iter()
+            .map(|(item, count)| ByCount { count, item })
+            .collect::<Vec<_>>();
+        MostCommon {
+            heap: entries.into(),
+        }
+    }
+
+    /// Create a vector of the `n` most common `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Rather than sorting every entry, this keeps a bounded heap of the `n` best entries seen
+    /// so far, which is much cheaper when `n` is small relative to the number of keys.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mc = "pappaopolo".chars().collect::<Counter<_>>().most_common_n(2);
+    /// let expected = vec![('p', 4), ('o', 3)];
+    /// assert_eq!(mc, expected);
+    /// ```
+    ///
+    /// Note that the ordering of duplicates is unstable, as is the choice between keys which
+    /// tie for the last place.
+    pub fn most_common_n(&self, n: usize) -> Vec<(T, N)> {
+        use compat::cmp::Reverse;
+        use compat::collections::BinaryHeap;
+
+        if n == 0 {
+            return Vec::new();
+        }
+
+        // a min-heap, so that the least common of the retained entries is on top
+        let mut heap = BinaryHeap::with_capacity(n);
+        for (item, count) in self.map.iter() {
+            if heap.len() < n {
+                heap.push(Reverse(ByCount { count, item }));
+            } else if let Some(mut least) = heap.peek_mut() {
+                if count > least.0.count {
+                    *least = Reverse(ByCount { count, item });
+                }
+            }
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(entry)| (entry.item.clone(), entry.count.clone()))
+            .collect()
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone + Ord,
+    N: Clone + Ord,
+    S: BuildHasher,
+{
+    /// Create an iterator over `(frequency, elem)` pairs, sorted most to least common.
+    ///
+    /// In the event that two keys have an equal frequency, use the natural ordering of the keys
+    /// to further sort the results.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mc = "abracadabra".chars().collect::<Counter<_>>().most_common_ordered();
+    /// let expect = vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)];
+    /// assert_eq!(mc, expect);
+    /// ```
+    pub fn most_common_ordered(&self) -> Vec<(T, N)> {
+        self.most_common_tiebreaker(|a, b| a.cmp(b))
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Ord,
+    N: Ord,
+    S: BuildHasher,
+{
+    /// Keep only the `k` most common items, removing the rest.
+    ///
+    /// In the event that two keys have an equal frequency, the smaller key is kept, as in
+    /// [`most_common_ordered`](#method.most_common_ordered).
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abracadabra".chars().collect::<Counter<_>>();
+    /// counter.keep_top(2);
+    /// let expect = [('a', 5), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    pub fn keep_top(&mut self, k: usize) {
+        if k >= self.map.len() {
+            return;
+        }
+        let mut items = self.map.drain().collect::<Vec<_>>();
+        sort_most_common(&mut items);
+        items.truncate(k);
+        self.map.extend(items);
+    }
+
+    /// Consume this counter, returning its `(elem, frequency)` pairs sorted most to least
+    /// common.
+    ///
+    /// Unlike [`most_common_ordered`](#method.most_common_ordered), this moves the items out
+    /// rather than cloning them. In the event that two keys have an equal frequency, the
+    /// smaller key comes first.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abracadabra".chars().collect::<Counter<_>>();
+    /// let expect = vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)];
+    /// assert_eq!(counter.into_sorted_vec(), expect);
+    /// ```
+    pub fn into_sorted_vec(self) -> Vec<(T, N)> {
+        let mut items = self.map.into_iter().collect::<Vec<_>>();
+        sort_most_common(&mut items);
+        items
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: ToPrimitive,
+    S: BuildHasher + Clone,
+{
+    /// Consume this counter, returning the empirical probability of each item.
+    ///
+    /// Each count is divided by the total, so the probabilities sum to one; an empty counter
+    /// gives an empty distribution. Counts which can't be represented as an `f64` become NaN.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "aaab".chars().collect::<Counter<_>>();
+    /// let distribution = counter.into_distribution();
+    /// assert_eq!(distribution[&'a'], 0.75);
+    /// assert_eq!(distribution[&'b'], 0.25);
+    /// ```
+    pub fn into_distribution(self) -> Counter<T, f64, S> {
+        let total = self.map.values().map(to_f64).sum::<f64>();
+        let mut map = HashMap::with_capacity_and_hasher(self.map.len(), self.map.hasher().clone());
+        for (item, count) in self.map {
+            map.insert(item, to_f64(&count) / total);
+        }
+        Counter { map, zero: 0.0 }
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero + AddAssign,
+    S: BuildHasher,
+{
+    // Add `count` to the count of `key`, keeping no entry whose count is zero. Adding counters
+    // and reading serialized ones both use this, since signed counts, or zero counts in the
+    // input, can sum to zero.
+    fn add_count_pruned(&mut self, key: T, count: N) {
+        use compat::collections::hash_map::Entry;
+
+        match self.map.entry(key) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += count;
+                if entry.get().is_zero() {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                if !count.is_zero() {
+                    entry.insert(count);
+                }
+            }
+        }
+    }
+}
+
+fn to_f64<N: ToPrimitive>(count: &N) -> f64 {
+    count.to_f64().unwrap_or(f64::NAN)
+}
+
+// Compare counts for listing the most common first: larger counts first, then those which
+// don't compare with themselves, such as NaN, all together at the end, so that sorting by
+// this never meets an inconsistent order.
+fn cmp_counts_descending<N: PartialOrd>(a: &N, b: &N) -> ::compat::cmp::Ordering {
+    let unordered = |count: &N| count.partial_cmp(count).is_none();
+    match (unordered(a), unordered(b)) {
+        (false, false) => b.partial_cmp(a).unwrap_or(::compat::cmp::Ordering::Equal),
+        (a_unordered, b_unordered) => a_unordered.cmp(&b_unordered),
+    }
+}
+
+// Sort `(item, count)` pairs by descending count, then ascending item.
+fn sort_most_common<T: Ord, N: Ord>(items: &mut [(T, N)]) {
+    items.sort_unstable_by(|(a_item, a_count), (b_item, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_item.cmp(b_item))
+    });
+}
+
+/// An iterator over the elements of a `Counter`, each repeated as many times as its count.
+///
+/// This struct is created by [`Counter::elements`](struct.Counter.html#method.elements).
+pub struct Elements<'a, T: 'a, N: 'a> {
+    entries: ::compat::collections::hash_map::Iter<'a, T, N>,
+    // the element being repeated, its count, and how often it has been yielded so far
+    current: Option<(&'a T, &'a N, N)>,
+}
+
+impl<'a, T, N> Iterator for Elements<'a, T, N>
+where
+    N: PartialOrd + AddAssign + Zero + One,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some((item, count, ref mut emitted)) = self.current {
+                if *emitted < *count {
+                    *emitted += N::one();
+                    return Some(item);
+                }
+            }
+            let (item, count) = self.entries.next()?;
+            self.current = Some((item, count, N::zero()));
+        }
+    }
+}
+
+/// An entry ordered only by its count, so that heaps of entries don't require `T: Ord`.
+struct ByCount<T, N> {
+    count: N,
+    item: T,
+}
+
+impl<T, N: PartialEq> PartialEq for ByCount<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.count == other.count
+    }
+}
+
+impl<T, N: Eq> Eq for ByCount<T, N> {}
+
+impl<T, N: Ord> PartialOrd for ByCount<T, N> {
+    fn partial_cmp(&self, other: &Self) -> Option<::compat::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, N: Ord> Ord for ByCount<T, N> {
+    fn cmp(&self, other: &Self) -> ::compat::cmp::Ordering {
+        self.count.cmp(&other.count)
+    }
+}
+
+/// A lazy iterator over the entries of a `Counter`, from most to least common.
+///
+/// This struct is created by [`Counter::most_common_iter`](struct.Counter.html#method.most_common_iter).
+pub struct MostCommon<'a, T: 'a, N: 'a> {
+    heap: ::compat::collections::BinaryHeap<ByCount<&'a T, &'a N>>,
+}
+
+impl<'a, T, N> Iterator for MostCommon<'a, T, N>
+where
+    T: Clone,
+    N: Clone + Ord,
+{
+    type Item = (T, N);
+
+    fn next(&mut self) -> Option<(T, N)> {
+        self.heap
+            .pop()
+            .map(|entry| (entry.item.clone(), entry.count.clone()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<'a, T, N> ExactSizeIterator for MostCommon<'a, T, N>
+where
+    T: Clone,
+    N: Clone + Ord,
+{
+}
+
+impl<T, N, S> AddAssign for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero + AddAssign,
+    S: BuildHasher,
+{
+    /// Add another counter to this counter
+    ///
+    /// `c += d;` -> `c[x] += d[x]` for all `x`, removing items whose count becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c += d;
+    ///
+    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn add_assign(&mut self, rhs: Self) {
+        for (key, value) in rhs.map {
+            self.add_count_pruned(key, value);
+        }
+    }
+}
+
+impl<'a, T, N, S> AddAssign<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Zero + AddAssign,
+    S: BuildHasher,
+{
+    /// Add another counter to this counter, without consuming it
+    ///
+    /// `c += &d;` -> `c[x] += d[x]` for all `x`, removing items whose count becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c += &d;
+    ///
+    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn add_assign(&mut self, rhs: &'a Counter<T, N, S>) {
+        for (key, value) in rhs.map.iter() {
+            self.add_count_pruned(key.clone(), value.clone());
+        }
+    }
+}
+
+impl<T, N, S> Add for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero + AddAssign,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Add two counters together.
+    ///
+    /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`, removing items whose count
+    /// becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = c + d;
+    ///
+    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn add(mut self, rhs: Counter<T, N, S>) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T, N, S> SubAssign for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher,
+{
+    /// Subtract (keeping only positive values).
+    ///
+    /// `c -= d;` -> `c[x] -= d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c -= d;
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn sub_assign(&mut self, rhs: Self) {
+        *self -= &rhs;
+    }
+}
+
+impl<'a, T, N, S> SubAssign<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher,
+{
+    /// Subtract another counter without consuming it (keeping only positive values).
+    ///
+    /// `c -= &d;` -> `c[x] -= d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c -= &d;
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn sub_assign(&mut self, rhs: &'a Counter<T, N, S>) {
+        for (key, value) in rhs.map.iter() {
+            let mut remove = false;
+            if let Some(entry) = self.map.get_mut(key) {
+                if *entry >= *value {
+                    *entry -= value.clone();
+                } else {
+                    remove = true;
+                }
+                if *entry == N::zero() {
+                    remove = true;
+                }
+            }
+            if remove {
+                self.map.remove(key);
+            }
+        }
+    }
+}
+
+impl<T, N, S> Sub for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Subtract (keeping only positive values).
+    ///
+    /// `out = c - d;` -> `out[x] == c[x] - d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = c - d;
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn sub(mut self, rhs: Counter<T, N, S>) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T, N, S> BitAnd for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Intersection
+    ///
+    /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+    ///
+    /// Only keys present in both counters appear in the output.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = c & d;
+    ///
+    /// let expect = [('a', 1), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn bitand(mut self, rhs: Counter<T, N, S>) -> Self::Output {
+        self &= &rhs;
+        self
+    }
+}
+
+impl<T, N, S> BitAndAssign for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    /// In-place intersection
+    ///
+    /// `c &= d;` -> `c[x] = min(c[x], d[x])`
+    ///
+    /// Keys which are not present in `d` are removed from `c`.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c &= d;
+    ///
+    /// let expect = [('a', 1), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn bitand_assign(&mut self, rhs: Counter<T, N, S>) {
+        *self &= &rhs;
+    }
+}
+
+impl<'a, T, N, S> BitAndAssign<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    /// In-place intersection, without consuming the other counter
+    ///
+    /// `c &= &d;` -> `c[x] = min(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c &= &d;
+    ///
+    /// let expect = [('a', 1), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn bitand_assign(&mut self, rhs: &'a Counter<T, N, S>) {
+        self.map.retain(|key, count| match rhs.map.get(key) {
+            Some(other) => {
+                if *other < *count {
+                    *count = other.clone();
+                }
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl<T, N, S> BitOr for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Union
+    ///
+    /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = c | d;
+    ///
+    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn bitor(mut self, rhs: Counter<T, N, S>) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+
+impl<T, N, S> BitOrAssign for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd,
+    S: BuildHasher,
+{
+    /// In-place union
+    ///
+    /// `c |= d;` -> `c[x] = max(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c |= d;
+    ///
+    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn bitor_assign(&mut self, rhs: Counter<T, N, S>) {
+        use compat::collections::hash_map::Entry;
+
+        for (key, value) in rhs.map {
+            match self.map.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    if value > *entry.get() {
+                        entry.insert(value);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, N, S> BitOrAssign<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    /// In-place union, without consuming the other counter
+    ///
+    /// `c |= &d;` -> `c[x] = max(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// c |= &d;
+    ///
+    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn bitor_assign(&mut self, rhs: &'a Counter<T, N, S>) {
+        for (key, value) in rhs.map.iter() {
+            match self.map.get_mut(key) {
+                Some(count) => {
+                    if *value > *count {
+                        *count = value.clone();
+                    }
+                }
+                None => {
+                    self.map.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+impl<T, N, S> MulAssign<N> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + MulAssign + Zero,
+    S: BuildHasher,
+{
+    /// Scale every count by a scalar
+    ///
+    /// `c *= k;` -> `c[x] *= k` for all `x`, removing items whose count becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aab".chars().collect::<Counter<_>>();
+    ///
+    /// c *= 3;
+    ///
+    /// let expect = [('a', 6), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn mul_assign(&mut self, rhs: N) {
+        self.map.retain(|_, count| {
+            *count *= rhs.clone();
+            !count.is_zero()
+        });
+    }
+}
+
+impl<T, N, S> Mul<N> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + MulAssign + Zero,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Scale every count by a scalar
+    ///
+    /// `out = c * k;` -> `out[x] == c[x] * k` for all `x`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let c = "aab".chars().collect::<Counter<_>>();
+    /// let d = "b".chars().collect::<Counter<_>>();
+    /// let weighted = c * 3 + d;
+    /// assert_eq!(weighted[&'a'], 6);
+    /// assert_eq!(weighted[&'b'], 4);
+    /// ```
+    fn mul(mut self, rhs: N) -> Counter<T, N, S> {
+        self *= rhs;
+        self
+    }
+}
+
+impl<T, N, S> DivAssign<N> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + DivAssign + Zero,
+    S: BuildHasher,
+{
+    /// Divide every count by a scalar
+    ///
+    /// `c /= k;` -> `c[x] /= k` for all `x`, removing items whose count becomes zero.
+    /// Integer counts are truncated, as with integer division; see
+    /// [`div_floor`](struct.Counter.html#method.div_floor) and its siblings for other
+    /// rounding.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "abbbbbb".chars().collect::<Counter<_>>();
+    ///
+    /// c /= 4;
+    ///
+    /// let expect = [('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn div_assign(&mut self, rhs: N) {
+        self.map.retain(|_, count| {
+            *count /= rhs.clone();
+            !count.is_zero()
+        });
+    }
+}
+
+impl<T, N, S> Div<N> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone + DivAssign + Zero,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Divide every count by a scalar
+    ///
+    /// `out = c / k;` -> `out[x] == c[x] / k` for all `x`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let c = "aaaaab".chars().collect::<Counter<_, f64>>();
+    /// let halved = c / 2.0;
+    /// assert_eq!(halved[&'a'], 2.5);
+    /// assert_eq!(halved[&'b'], 0.5);
+    /// ```
+    fn div(mut self, rhs: N) -> Counter<T, N, S> {
+        self /= rhs;
+        self
+    }
+}
+
+impl<'a, T, N, S> Add<&'a Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Zero + AddAssign,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Add two counters together, without consuming either counter.
+    ///
+    /// `out = &c + &d;` -> `out[x] == c[x] + d[x]` for all `x`, removing items whose count
+    /// becomes zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = &c + &d;
+    ///
+    /// let expect = [('a', 4), ('b', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn add(self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        let mut counter = self.clone();
+        counter += rhs;
+        counter
+    }
+}
+
+impl<'a, T, N, S> Add<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Zero + AddAssign,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Add two counters together, consuming only the left-hand counter.
+    ///
+    /// `out = c + &d;` is equivalent to `&c + &d`, but reuses the storage of `c`.
+    fn add(mut self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T, N, S> Add<Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + Zero + AddAssign,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Add two counters together, consuming only the right-hand counter.
+    ///
+    /// `out = &c + d;` is equivalent to `&c + &d`, but reuses the storage of `d`.
+    fn add(self, mut rhs: Counter<T, N, S>) -> Self::Output {
+        rhs += self;
+        rhs
+    }
+}
+
+impl<'a, T, N, S> Sub<&'a Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Subtract (keeping only positive values), without consuming either counter.
+    ///
+    /// `out = &c - &d;` -> `out[x] == c[x] - d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero()
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = &c - &d;
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn sub(self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        let mut counter = self.clone();
+        counter -= rhs;
+        counter
+    }
+}
+
+impl<'a, T, N, S> Sub<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Subtract (keeping only positive values), consuming only the left-hand counter.
+    ///
+    /// `out = c - &d;` is equivalent to `&c - &d`, but reuses the storage of `c`.
+    fn sub(mut self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T, N, S> Sub<Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd + PartialEq + SubAssign + Zero,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Subtract (keeping only positive values), consuming only the right-hand counter.
+    ///
+    /// `out = &c - d;` is equivalent to `&c - &d`.
+    fn sub(self, rhs: Counter<T, N, S>) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl<'a, T, N, S> BitAnd<&'a Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Intersection, without consuming either counter.
+    ///
+    /// `out = &c & &d;` -> `out[x] == min(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = &c & &d;
+    ///
+    /// let expect = [('a', 1), ('b', 1)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn bitand(self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        let mut counter = self.clone();
+        counter &= rhs;
+        counter
+    }
+}
+
+impl<'a, T, N, S> BitAnd<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Intersection, consuming only the left-hand counter.
+    ///
+    /// `out = c & &d;` is equivalent to `&c & &d`, but reuses the storage of `c`.
+    fn bitand(mut self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        self &= rhs;
+        self
+    }
+}
+
+impl<T, N, S> BitAnd<Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Intersection, consuming only the right-hand counter.
+    ///
+    /// `out = &c & d;` is equivalent to `&c & &d`, but reuses the storage of `d`.
+    fn bitand(self, mut rhs: Counter<T, N, S>) -> Self::Output {
+        rhs &= self;
+        rhs
+    }
+}
+
+impl<'a, T, N, S> BitOr<&'a Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Union, without consuming either counter.
+    ///
+    /// `out = &c | &d;` -> `out[x] == max(c[x], d[x])`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    ///
+    /// let e = &c | &d;
+    ///
+    /// let expect = [('a', 3), ('b', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn bitor(self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        let mut counter = self.clone();
+        counter |= rhs;
+        counter
+    }
+}
+
+impl<'a, T, N, S> BitOr<&'a Counter<T, N, S>> for Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Union, consuming only the left-hand counter.
+    ///
+    /// `out = c | &d;` is equivalent to `&c | &d`, but reuses the storage of `c`.
+    fn bitor(mut self, rhs: &'a Counter<T, N, S>) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+
+impl<T, N, S> BitOr<Counter<T, N, S>> for &Counter<T, N, S>
+where
+    T: Clone + Hash + Eq,
+    N: Clone + PartialOrd,
+    S: BuildHasher + Clone,
+{
+    type Output = Counter<T, N, S>;
+
+    /// Union, consuming only the right-hand counter.
+    ///
+    /// `out = &c | d;` is equivalent to `&c | &d`, but reuses the storage of `d`.
+    fn bitor(self, mut rhs: Counter<T, N, S>) -> Self::Output {
+        rhs |= self;
+        rhs
+    }
+}
+
+impl<T, N, S> PartialEq for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialEq + Zero,
+    S: BuildHasher,
+{
+    /// Counters are equal when every item has the same count in both.
+    ///
+    /// Absent items count as zero, so entries with a count of zero, such as those left by
+    /// writing to the map directly, don't affect equality.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let c = "aab".chars().collect::<Counter<_>>();
+    /// let mut d = "aba".chars().collect::<Counter<_>>();
+    /// assert_eq!(c, d);
+    /// d.insert('z', 0);
+    /// assert_eq!(c, d);
+    /// ```
+    fn eq(&self, other: &Counter<T, N, S>) -> bool {
+        self.map
+            .iter()
+            .all(|(item, count)| other.map.get(item).unwrap_or(&other.zero) == count)
+            && other
+                .map
+                .iter()
+                .all(|(item, count)| self.map.contains_key(item) || count.is_zero())
+    }
+}
+
+impl<T, N, S> Eq for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Eq + Zero,
+    S: BuildHasher,
+{
+}
+
+#[cfg(feature = "std")]
+impl<T, N, S> Hash for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Hash + Zero,
+    S: BuildHasher,
+{
+    /// Hash the `(item, count)` pairs independently of their order, skipping zero counts so
+    /// that equal counters hash equally.
+    ///
+    /// This makes it possible to use counters as keys, for example to group anagrams:
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut groups: HashMap<Counter<char>, Vec<&str>> = HashMap::new();
+    /// for word in vec!["listen", "google", "silent", "enlist"] {
+    ///     groups.entry(word.chars().collect()).or_default().push(word);
+    /// }
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups[&"tinsel".chars().collect()], vec!["listen", "silent", "enlist"]);
+    /// ```
+    ///
+    /// Requires the `std` feature, for a fixed-key hasher.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Sum a fixed-key hash of each entry, so that iteration order doesn't matter.
+        let mut len = 0usize;
+        let mut sum = 0u64;
+        for entry in self.map.iter().filter(|&(_, count)| !count.is_zero()) {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+            len += 1;
+        }
+        state.write_usize(len);
+        state.write_u64(sum);
+    }
+}
+
+impl<T, N, S> PartialOrd for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + Zero,
+    S: BuildHasher,
+{
+    /// Compare counters by multiset inclusion, as Python's `Counter` does.
+    ///
+    /// `c <= d` if and only if `c.is_subset(&d)`. Counters where each has an item with a
+    /// higher count than in the other are incomparable.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let c = "ab".chars().collect::<Counter<_>>();
+    /// let d = "abb".chars().collect::<Counter<_>>();
+    /// let e = "aac".chars().collect::<Counter<_>>();
+    /// assert!(c < d);
+    /// assert!(d >= c);
+    /// assert_eq!(d.partial_cmp(&e), None);
+    /// ```
+    fn partial_cmp(&self, other: &Counter<T, N, S>) -> Option<::compat::cmp::Ordering> {
+        use compat::cmp::Ordering;
+
+        match (self.is_subset(other), other.is_subset(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl<T, N, S> Default for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero,
+    S: BuildHasher + Default,
+{
+    fn default() -> Self {
+        Counter {
+            map: HashMap::default(),
+            zero: N::zero(),
+        }
+    }
+}
+
+impl<'a, Q, T, N, S> Index<&'a Q> for Counter<T, N, S>
+where
+    T: Hash + Eq + Borrow<Q>,
+    Q: ?Sized + Hash + Eq,
+    S: BuildHasher,
+{
+    type Output = N;
+
+    /// Index in immutable contexts
+    ///
+    /// Returns a reference to a zero count if the key isn't present, rather than panicking
+    /// like `HashMap`'s `Index` implementation does. The key is not inserted.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "aaab".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter[&'a'], 3);
+    /// assert_eq!(counter[&'b'], 1);
+    /// assert_eq!(counter[&'c'], 0);
+    /// ```
+    ///
+    /// As with `HashMap`, a `Counter<String>` can be indexed by `&str`, without allocating.
+    fn index(&self, key: &'a Q) -> &N {
+        self.map.get(key).unwrap_or(&self.zero)
+    }
+}
+
+impl<'a, T, N, S> IndexMut<&'a T> for Counter<T, N, S>
+where
+    T: Hash + Eq + Clone,
+    N: Zero,
+    S: BuildHasher,
+{
+    /// Index in mutable contexts
+    ///
+    /// If the given key is not present, creates a new entry and initializes it with a zero count.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = "aaab".chars().collect::<Counter<_>>();
+    /// counter[&'c'] += 3;
+    /// assert_eq!(counter[&'c'], 3);
+    /// ```
+    ///
+    /// The entry is not removed again if it is left at zero, so it's up to the caller to
+    /// uphold the invariant that a counter contains no zero counts. Equality ignores such
+    /// entries, but `len` and iteration see them. Use
+    /// [`entry`](struct.Counter.html#method.entry) instead where a count may come to zero,
+    /// and it is removed once the update is done:
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = "ab".chars().collect::<Counter<_>>();
+    /// counter[&'a'] -= 1;
+    /// assert!(counter.contains_key(&'a'));
+    /// *counter.entry('b') -= 1;
+    /// assert!(!counter.contains_key(&'b'));
+    /// ```
+    fn index_mut(&mut self, key: &'a T) -> &mut N {
+        self.map.entry(key.clone()).or_insert_with(N::zero)
+    }
+}
+
+impl<T, N, S> Deref for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone,
+    S: BuildHasher,
+{
+    type Target = CounterMap<T, N, S>;
+    fn deref(&self) -> &CounterMap<T, N, S> {
+        &self.map
+    }
+}
+
+impl<T, N, S> DerefMut for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Clone,
+    S: BuildHasher,
+{
+    fn deref_mut(&mut self) -> &mut CounterMap<T, N, S> {
+        &mut self.map
+    }
+}
+
+impl<T, N, S> IntoIterator for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (T, N);
+    type IntoIter = ::compat::collections::hash_map::IntoIter<T, N>;
+
+    /// Consumes the Counter to produce an iterator that yields `(item, count)` tuples.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "aaab".chars().collect::<Counter<_>>();
+    /// let mut items = counter.into_iter().collect::<Vec<_>>();
+    /// items.sort();
+    /// assert_eq!(items, vec![('a', 3), ('b', 1)]);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_iter()
+    }
+}
+
+impl<'a, T, N, S> IntoIterator for &'a Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a T, &'a N);
+    type IntoIter = ::compat::collections::hash_map::Iter<'a, T, N>;
+
+    /// Iterate over `(&item, &count)` tuples, for example in a `for` loop.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "aaab".chars().collect::<Counter<_>>();
+    /// let mut total = 0;
+    /// for (_, count) in &counter {
+    ///     total += count;
+    /// }
+    /// assert_eq!(total, 4);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter()
+    }
+}
+
+impl<'a, T, N, S> IntoIterator for &'a mut Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (&'a T, &'a mut N);
+    type IntoIter = ::compat::collections::hash_map::IterMut<'a, T, N>;
+
+    /// Iterate over `(&item, &mut count)` tuples, for example in a `for` loop.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = "aaab".chars().collect::<Counter<_>>();
+    /// for (_, count) in &mut counter {
+    ///     *count *= 2;
+    /// }
+    /// assert_eq!(counter[&'a'], 6);
+    /// ```
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.iter_mut()
+    }
+}
+
+impl<I, T, N, S> AddAssign<I> for Counter<T, N, S>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Directly add the counts of the elements of `I` to `self`
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = Counter::init("abbccc".chars());
+    ///
+    /// counter += "aeeeee".chars();
+    /// let expected: HashMap<char, usize> = [('a', 2), ('b', 2), ('c', 3), ('e', 5)]
+    ///     .iter().cloned().collect();
+    /// assert_eq!(counter.into_map(), expected);
+    /// ```
+    fn add_assign(&mut self, rhs: I) {
+        self.update(rhs);
+    }
+}
+
+impl<I, T, N, S> Add<I> for Counter<T, N, S>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    type Output = Self;
+    /// Consume self producing a Counter like self updated with the counts of
+    /// the elements of I.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = Counter::init("abbccc".chars());
+    ///
+    /// let new_counter = counter + "aeeeee".chars();
+    /// let expected: HashMap<char, usize> = [('a', 2), ('b', 2), ('c', 3), ('e', 5)]
+    ///     .iter().cloned().collect();
+    /// assert_eq!(new_counter.into_map(), expected);
+    /// ```
+    fn add(mut self, rhs: I) -> Self::Output {
+        self.update(rhs);
+        self
+    }
+}
+
+impl<I, T, N, S> SubAssign<I> for Counter<T, N, S>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Directly subtract the counts of the elements of `I` from `self`,
+    /// keeping only items with a value greater than N::zero().
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut c = "aaab".chars().collect::<Counter<_>>();
+    /// c -= "abb".chars();
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(c.into_map(), expect);
+    /// ```
+    fn sub_assign(&mut self, rhs: I) {
+        self.subtract(rhs);
+    }
+}
+
+impl<I, T, N, S> Sub<I> for Counter<T, N, S>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    type Output = Self;
+    /// Consume self producing a Counter like self with the counts of the
+    /// elements of I subtracted, keeping only positive values.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let c = "aaab".chars().collect::<Counter<_>>();
+    /// let e = c - "abb".chars();
+    ///
+    /// let expect = [('a', 2)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(e.into_map(), expect);
+    /// ```
+    fn sub(mut self, rhs: I) -> Self::Output {
+        self.subtract(rhs);
+        self
+    }
+}
+
+impl<T, N, S> iter::FromIterator<T> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// Produce a Counter from an iterator of items. This is called automatically
+    /// by `iter.collect()`.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let expect = [('a', 1), ('b', 2), ('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    ///
+    /// Collecting an iterator of references produces a counter of references; use
+    /// `.cloned()` first to count owned keys instead.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let words = vec!["a", "b", "a"];
+    /// let borrowed: Counter<&&str> = words.iter().collect();
+    /// let owned: Counter<&str> = words.iter().cloned().collect();
+    /// assert_eq!(borrowed[&&"a"], owned[&"a"]);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Counter::init_with_hasher(iter, S::default())
+    }
+}
+
+impl<T, N, S> iter::FromIterator<(T, N)> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// `from_iter` creates a counter from `(item, count)` tuples.
+    ///
+    /// The counts of duplicate items are summed.
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let counter = [('a', 1), ('b', 2), ('c', 3), ('a', 4)].iter()
     ///     .cloned().collect::<Counter<_>>();
     /// let expect = [('a', 5), ('b', 2), ('c', 3)].iter()
     ///     .cloned().collect::<HashMap<_, _>>();
     /// assert_eq!(counter.into_map(), expect);
     /// ```
-    fn from_iter<I: IntoIterator<Item = (T, N)>>(iter: I) -> Self {
-        let mut cnt = Counter::new();
-        for (item, item_count) in iter.into_iter() {
-            let entry = cnt.map.entry(item).or_insert(N::zero());
-            *entry += item_count;
+    fn from_iter<I: IntoIterator<Item = (T, N)>>(iter: I) -> Self {
+        let mut cnt = Counter::default();
+        cnt.extend(iter);
+        cnt
+    }
+}
+
+impl<T, N, S> Extend<T> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Extend a Counter with an iterator of items, exactly like [`update`](#method.update).
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// counter.extend("ab".chars());
+    /// let expect = [('a', 2), ('b', 3), ('c', 3)].iter().cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl<T, N, S> Extend<(T, N)> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher,
+{
+    /// Extend a Counter with `(item, count)` tuples, adding each count to the item's total.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let mut counter = "abbccc".chars().collect::<Counter<_>>();
+    /// counter.extend(vec![('a', 4), ('d', 2)]);
+    /// let expect = [('a', 5), ('b', 2), ('c', 3), ('d', 2)].iter()
+    ///     .cloned().collect::<HashMap<_, _>>();
+    /// assert_eq!(counter.into_map(), expect);
+    /// ```
+    fn extend<I: IntoIterator<Item = (T, N)>>(&mut self, iter: I) {
+        self.update_with_counts(iter);
+    }
+}
+
+impl<T, N, S> From<HashMap<T, N, S>> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: Zero,
+    S: BuildHasher,
+{
+    /// Wrap a map of counts, dropping any items whose count is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::HashMap;
+    /// let map = [('a', 1), ('b', 0)].iter().cloned().collect::<HashMap<_, usize>>();
+    /// let counter = Counter::from(map);
+    /// assert_eq!(counter.len(), 1);
+    /// assert_eq!(counter[&'a'], 1);
+    /// ```
+    fn from(mut map: HashMap<T, N, S>) -> Counter<T, N, S> {
+        map.retain(|_, count| !count.is_zero());
+        Counter {
+            map,
+            zero: N::zero(),
         }
-        cnt
+    }
+}
+
+impl<T, N, S> From<Counter<T, N, S>> for HashMap<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Unwrap a counter into its map of counts, exactly like
+    /// [`into_map`](struct.Counter.html#method.into_map).
+    fn from(counter: Counter<T, N, S>) -> HashMap<T, N, S> {
+        counter.map
+    }
+}
+
+impl<T, N, S> From<BTreeMap<T, N>> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// Build a counter from a sorted map of counts, dropping any items whose count is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// # use std::collections::BTreeMap;
+    /// let map = [('a', 1), ('b', 2)].iter().cloned().collect::<BTreeMap<_, _>>();
+    /// let counter: Counter<_> = Counter::from(map);
+    /// assert_eq!(counter[&'b'], 2);
+    /// ```
+    fn from(map: BTreeMap<T, N>) -> Counter<T, N, S> {
+        map.into_iter().collect()
+    }
+}
+
+impl<T, N, S> From<Vec<(T, N)>> for Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// Build a counter from `(item, count)` pairs, summing the counts of duplicate items.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter: Counter<_> = Counter::from(vec![('a', 1), ('b', 2), ('a', 3)]);
+    /// assert_eq!(counter[&'a'], 4);
+    /// ```
+    fn from(pairs: Vec<(T, N)>) -> Counter<T, N, S> {
+        pairs.into_iter().collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use compat::collections::HashMap;
+    use compat::string::{String, ToString};
 
     #[test]
     fn test_creation() {
@@ -663,77 +3230,501 @@ mod tests {
         let initializer = &[1];
         let counter = Counter::init(initializer);
 
-        let mut expected = HashMap::new();
-        static ONE: usize = 1;
-        expected.insert(&ONE, 1);
-        assert!(counter.map == expected);
+        let mut expected = HashMap::new();
+        static ONE: usize = 1;
+        expected.insert(&ONE, 1);
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_update() {
+        let mut counter = Counter::init("abbccc".chars());
+        let expected = hashmap!{
+            'a' => 1,
+            'b' => 2,
+            'c' => 3,
+        };
+        assert!(counter.map == expected);
+
+        counter.update("aeeeee".chars());
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_add_update_iterable() {
+        let mut counter = Counter::init("abbccc".chars());
+        let expected = hashmap!{
+            'a' => 1,
+            'b' => 2,
+            'c' => 3,
+        };
+        assert!(counter.map == expected);
+
+        counter += "aeeeee".chars();
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_add_iterable() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        let out = counter + "aeeeee".chars();
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert_eq!(out.map, expected);
+    }
+
+    #[test]
+    fn test_sub_iterable() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        let out = counter - "bbccddd".chars();
+        let expected = hashmap!{
+            'a' => 1,
+            'c' => 1,
+        };
+        assert_eq!(out.map, expected);
+    }
+
+    #[test]
+    fn test_add_update_counter() {
+        let mut counter = Counter::init("abbccc".chars());
+        let expected = hashmap!{
+            'a' => 1,
+            'b' => 2,
+            'c' => 3,
+        };
+        assert!(counter.map == expected);
+
+        let other = Counter::init("aeeeee".chars());
+        counter += other;
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_add_update_counter_ref() {
+        let mut counter = Counter::init("abbccc".chars());
+        let other = Counter::init("aeeeee".chars());
+        counter += &other;
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert_eq!(counter.map, expected);
+        // `other` is still usable
+        assert_eq!(other.map.len(), 2);
+    }
+
+    #[test]
+    fn test_update_with_counts() {
+        let mut counter = Counter::<_, i32>::init("abbccc".chars());
+        counter.update_with_counts(vec![('a', 2), ('b', -2), ('d', 0), ('e', 1)]);
+        let expected = hashmap!{
+            'a' => 3,
+            'c' => 3,
+            'e' => 1,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_subtract() {
+        let mut counter = Counter::init("abbccc".chars());
+        counter.subtract("bbccddd".chars());
+        let expected = hashmap!{
+            'a' => 1,
+            'c' => 1,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_subtract_with_counts() {
+        let mut counter = Counter::init("abbccc".chars());
+        counter.subtract_with_counts(vec![('a', 1), ('b', 3), ('c', 1), ('c', 1), ('d', 5)]);
+        let expected = hashmap!{
+            'c' => 1,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_add_and_remove_count() {
+        let mut counter = Counter::init("abbccc".chars());
+        counter.add_count('a', 2);
+        counter.add_count('d', 1);
+        counter.add_count('e', 0);
+        assert_eq!(counter.remove_count(&'c', 1), 2);
+        assert_eq!(counter.remove_count(&'b', 2), 0);
+        assert_eq!(counter.remove_count(&'z', 1), 0);
+        let expected = hashmap!{
+            'a' => 3,
+            'c' => 2,
+            'd' => 1,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut counter = Counter::init("abbcccdddd".chars());
+        counter.retain(|&c, &mut count| c != 'd' && count > 1);
+        let expected = hashmap!{
+            'b' => 2,
+            'c' => 3,
+        };
+        assert!(counter.map == expected);
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut counter: Counter<String> = "a b b c".split_whitespace().map(String::from).collect();
+        assert_eq!(counter["b"], 2);
+        assert_eq!(counter.get_or_zero("z"), &0);
+        assert_eq!(counter.remove_count("b", 1), 1);
+        counter.add_count_borrowed("c", 2);
+        counter.add_count_borrowed("d", 1);
+        counter.add_count_borrowed("e", 0);
+        assert!(counter.contains_key("d"));
+        assert_eq!(counter.remove("a"), Some(1));
+        let expected = hashmap!{
+            "b".to_string() => 1,
+            "c".to_string() => 3,
+            "d".to_string() => 1,
+        };
+        assert!(counter.into_map() == expected);
+    }
+
+    #[test]
+    fn test_capacity_management() {
+        let mut counter: Counter<u32> = Counter::with_capacity(8);
+        assert!(counter.capacity() >= 8);
+        counter.reserve(100);
+        assert!(counter.capacity() >= 100);
+        counter.update(0..4);
+        counter.shrink_to_fit();
+        assert!(counter.capacity() >= 4 && counter.capacity() < 100);
+        assert_eq!(counter.total(), 4);
+    }
+
+    #[test]
+    fn test_drain_and_extract_if() {
+        let mut counter = Counter::init("abbcccdddd".chars());
+        let mut even = counter
+            .extract_if(|_, &count| count % 2 == 0)
+            .collect::<Vec<_>>();
+        even.sort();
+        assert_eq!(even, vec![('b', 2), ('d', 4)]);
+
+        let mut odd = counter.drain().collect::<Vec<_>>();
+        odd.sort();
+        assert_eq!(odd, vec![('a', 1), ('c', 3)]);
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_map_counts() {
+        let counter = Counter::<_>::init("abbcccdddd".chars());
+        let halved = counter.map_counts(|count| count as u8 / 2);
+        let expected = hashmap!{
+            'b' => 1,
+            'c' => 1,
+            'd' => 2,
+        };
+        assert!(halved.map == expected);
+    }
+
+    #[test]
+    fn test_map_keys() {
+        let counter = Counter::<_>::init("aAbBBc".chars());
+        let folded = counter.map_keys(|c| c.to_ascii_lowercase());
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 3,
+            'c' => 1,
+        };
+        assert!(folded.map == expected);
+    }
+
+    #[test]
+    fn test_filter_below_and_keep_top() {
+        let mut counter = Counter::init("abbcccddddeeee".chars());
+        counter.filter_below(2);
+        assert_eq!(counter.len(), 4);
+        counter.keep_top(2);
+        let expected = hashmap!{
+            'd' => 4,
+            'e' => 4,
+        };
+        assert!(counter.map == expected);
+        counter.keep_top(5);
+        assert_eq!(counter.len(), 2);
+        counter.keep_top(0);
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_map_conversions() {
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 1,
+        };
+        let from_vec: Counter<_> = Counter::from(vec![('a', 1), ('b', 1), ('a', 1), ('c', 0)]);
+        assert!(from_vec.map == expected);
+
+        let from_btree: Counter<_> = Counter::from(
+            vec![('a', 2), ('b', 1), ('c', 0)]
+                .into_iter()
+                .collect::<BTreeMap<_, _>>(),
+        );
+        assert!(from_btree == from_vec);
+
+        let mut map = HashMap::from(from_btree);
+        assert!(map == expected);
+        map.insert('c', 0);
+        assert!(Counter::from(map) == from_vec);
+    }
+
+    #[test]
+    fn test_into_sorted_vec() {
+        let counter = Counter::<_>::init(vec![
+            "b".to_string(),
+            "a".to_string(),
+            "c".to_string(),
+            "c".to_string(),
+        ]);
+        assert_eq!(
+            counter.into_sorted_vec(),
+            vec![
+                ("c".to_string(), 2),
+                ("a".to_string(), 1),
+                ("b".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_distribution() {
+        let counter = Counter::<_, u8>::init("abbbcccc".chars());
+        let distribution = counter.into_distribution();
+        let expected = hashmap!{
+            'a' => 0.125,
+            'b' => 0.375,
+            'c' => 0.5,
+        };
+        assert!(distribution.map == expected);
+
+        let empty = Counter::<char>::new().into_distribution();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_mode_and_modes() {
+        let counter = Counter::<_>::init("abbccdd".chars());
+        assert!(['b', 'c', 'd'].contains(counter.mode().unwrap()));
+        let mut modes = counter.modes();
+        modes.sort();
+        assert_eq!(modes, vec![&'b', &'c', &'d']);
+
+        let empty = Counter::<char>::new();
+        assert_eq!(empty.mode(), None);
+        assert!(empty.modes().is_empty());
+    }
+
+    #[test]
+    fn test_is_subset() {
+        let small = Counter::<_>::init("abb".chars());
+        let large = Counter::<_>::init("abbbc".chars());
+        assert!(small.is_subset(&large));
+        assert!(large.is_superset(&small));
+        assert!(!large.is_subset(&small));
+        assert!(small.is_subset(&small));
+        assert!(Counter::new().is_subset(&small));
+
+        // a negative count in `other` is below the implicit zero in `self`
+        let mut signed = Counter::<_, i32>::init("a".chars());
+        signed.subtract_signed("bb".chars());
+        assert!(!Counter::<_, i32>::init("a".chars()).is_subset(&signed));
+        assert!(signed.is_subset(&Counter::init("a".chars())));
+    }
+
+    #[test]
+    fn test_eq_ignores_zero_counts() {
+        let c = Counter::<_>::init("abb".chars());
+        let mut d = Counter::<_>::init("bab".chars());
+        assert_eq!(c, d);
+        d.map.insert('z', 0);
+        assert_eq!(c, d);
+        assert_eq!(d, c);
+        d.map.insert('y', 1);
+        assert_ne!(c, d);
+        assert_ne!(d, c);
+        assert_ne!(c, Counter::init("ab".chars()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hash() {
+        fn hash_of(counter: &Counter<char>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            counter.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let c = Counter::init("abbccc".chars());
+        let mut d = Counter::init("cbcacb".chars());
+        assert_eq!(hash_of(&c), hash_of(&d));
+        d.map.insert('z', 0);
+        assert_eq!(hash_of(&c), hash_of(&d));
+        assert_ne!(hash_of(&c), hash_of(&Counter::init("abbcc".chars())));
     }
 
     #[test]
-    fn test_update() {
-        let mut counter = Counter::init("abbccc".chars());
+    fn test_symmetric_difference() {
+        let c = Counter::<_, i32>::init("aaabbc".chars());
+        let mut d = Counter::<_, i32>::init("abbbbd".chars());
+        d.subtract_signed("ee".chars());
         let expected = hashmap!{
-            'a' => 1,
+            'a' => 2,
             'b' => 2,
-            'c' => 3,
+            'c' => 1,
+            'd' => 1,
+            'e' => 2,
         };
-        assert!(counter.map == expected);
+        assert!(c.symmetric_difference(&d).map == expected);
+        assert!(c.symmetric_difference(&c).is_empty());
+    }
 
-        counter.update("aeeeee".chars());
+    #[test]
+    fn test_mul() {
+        let c = Counter::<_>::init("abbccc".chars());
         let expected = hashmap!{
             'a' => 2,
-            'b' => 2,
-            'c' => 3,
-            'e' => 5,
+            'b' => 4,
+            'c' => 6,
         };
-        assert!(counter.map == expected);
+        let mut c = c * 2;
+        assert!(c.map == expected);
+        c *= 0;
+        assert!(c.is_empty());
     }
 
     #[test]
-    fn test_add_update_iterable() {
-        let mut counter = Counter::init("abbccc".chars());
+    fn test_div() {
+        let c = Counter::<_>::init("abbccccccc".chars());
+        assert!((c.clone() / 2).map == hashmap!{'b' => 1, 'c' => 3});
+        assert!(c.clone().div_floor(2).map == hashmap!{'b' => 1, 'c' => 3});
+        assert!(c.clone().div_ceil(2).map == hashmap!{'a' => 1, 'b' => 1, 'c' => 4});
+        assert!(c.div_round(2).map == hashmap!{'a' => 1, 'b' => 1, 'c' => 4});
+    }
+
+    #[test]
+    fn test_div_signed() {
+        let mut c = Counter::<_, i32>::init("aaaaaaa".chars());
+        c.subtract_signed("bbbbbbbcccccc".chars());
+        // a: 7, b: -7, c: -6
+        assert!(c.clone().div_floor(2).map == hashmap!{'a' => 3, 'b' => -4, 'c' => -3});
+        assert!(c.clone().div_ceil(2).map == hashmap!{'a' => 4, 'b' => -3, 'c' => -3});
+        assert!(c.clone().div_round(2).map == hashmap!{'a' => 4, 'b' => -4, 'c' => -3});
+        assert!(c.clone().div_round(4).map == hashmap!{'a' => 2, 'b' => -2, 'c' => -2});
+        assert!(c.clone().div_ceil(-4).map == hashmap!{'a' => -1, 'b' => 2, 'c' => 2});
+        assert!(c.clone().div_round(-2).map == hashmap!{'a' => -4, 'b' => 4, 'c' => 3});
+        assert!(c.div_floor(-4).map == hashmap!{'a' => -2, 'b' => 1, 'c' => 1});
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_div_by_zero() {
+        Counter::<_>::init("ab".chars()).div_floor(0);
+    }
+
+    #[test]
+    fn test_into_positive_and_negated() {
+        let mut counter = Counter::<_, i32>::init("aaab".chars());
+        counter.subtract_signed("bbcccd".chars());
+        // a: 3, b: -1, c: -3, d: -1
+        assert!(counter.clone().into_positive().map == hashmap!{'a' => 3});
         let expected = hashmap!{
-            'a' => 1,
-            'b' => 2,
+            'b' => 1,
             'c' => 3,
+            'd' => 1,
         };
-        assert!(counter.map == expected);
+        assert!(counter.into_negated().map == expected);
+    }
 
-        counter += "aeeeee".chars();
+    #[test]
+    fn test_merge_with() {
+        let c = Counter::<_>::init("aaabbc".chars());
+        let d = Counter::<_>::init("abbbbd".chars());
         let expected = hashmap!{
-            'a' => 2,
-            'b' => 2,
-            'c' => 3,
-            'e' => 5,
+            'a' => 3,
+            'b' => 4,
+            'c' => 1,
+            'd' => 1,
         };
-        assert!(counter.map == expected);
+        assert!(c.merge_with(&d, |&a, &b| a.max(b)).map == expected);
+        assert!(c.merge_with(&d, |&a, &b| a.min(b)) == (c.clone() & d.clone()));
+        assert!(c.merge_with(&d, |&a, &b| a + b) == c + d);
     }
 
     #[test]
-    fn test_add_update_counter() {
-        let mut counter = Counter::init("abbccc".chars());
+    fn test_partial_ord() {
+        let small = Counter::<_>::init("abb".chars());
+        let large = Counter::<_>::init("abbbc".chars());
+        let other = Counter::<_>::init("aab".chars());
+        assert!(small < large);
+        assert!(small <= small);
+        assert!(large > small);
+        assert_eq!(small.partial_cmp(&other), None);
+        assert_eq!(other.partial_cmp(&large), None);
+        assert!(small != other);
+    }
+
+    #[test]
+    fn test_subtract_signed() {
+        let mut counter: Counter<_, i32> = Counter::init("abbccc".chars());
+        counter.subtract_signed("bbccddd".chars());
         let expected = hashmap!{
             'a' => 1,
-            'b' => 2,
-            'c' => 3,
+            'c' => 1,
+            'd' => -3,
         };
-        assert!(counter.map == expected);
+        assert_eq!(counter.map, expected);
 
-        let other = Counter::init("aeeeee".chars());
-        counter += other;
+        counter.keep_positive();
         let expected = hashmap!{
-            'a' => 2,
-            'b' => 2,
-            'c' => 3,
-            'e' => 5,
+            'a' => 1,
+            'c' => 1,
         };
-        assert!(counter.map == expected);
+        assert_eq!(counter.map, expected);
     }
 
     #[test]
-    fn test_subtract() {
+    fn test_sub_update_iterable() {
         let mut counter = Counter::init("abbccc".chars());
-        counter.subtract("bbccddd".chars());
+        counter -= "bbccddd".chars();
         let expected = hashmap!{
             'a' => 1,
             'c' => 1,
@@ -742,9 +3733,10 @@ mod tests {
     }
 
     #[test]
-    fn test_sub_update_iterable() {
+    fn test_sub_update_counter() {
         let mut counter = Counter::init("abbccc".chars());
-        counter -= "bbccddd".chars();
+        let other = Counter::init("bbccddd".chars());
+        counter -= other;
         let expected = hashmap!{
             'a' => 1,
             'c' => 1,
@@ -753,15 +3745,16 @@ mod tests {
     }
 
     #[test]
-    fn test_sub_update_counter() {
+    fn test_sub_update_counter_ref() {
         let mut counter = Counter::init("abbccc".chars());
         let other = Counter::init("bbccddd".chars());
-        counter -= other;
+        counter -= &other;
         let expected = hashmap!{
             'a' => 1,
             'c' => 1,
         };
-        assert!(counter.map == expected);
+        assert_eq!(counter.map, expected);
+        assert_eq!(other.map.len(), 3);
     }
 
     #[test]
@@ -776,6 +3769,29 @@ mod tests {
         let _diff = counts - other_counts;
     }
 
+    #[test]
+    fn test_elements() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        let mut elements = counter.elements().cloned().collect::<Vec<_>>();
+        elements.sort();
+        assert_eq!(elements, "abbccc".chars().collect::<Vec<_>>());
+
+        let rebuilt = counter.elements().cloned().collect::<Counter<_>>();
+        assert_eq!(rebuilt, counter);
+
+        let empty: Counter<char> = Counter::new();
+        assert_eq!(empty.elements().count(), 0);
+    }
+
+    #[test]
+    fn test_total() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        assert_eq!(counter.total(), 6);
+
+        let empty: Counter<char, i8> = Counter::new();
+        assert_eq!(empty.total(), 0);
+    }
+
     #[test]
     fn test_most_common() {
         let counter = Counter::init("abbccc".chars());
@@ -784,6 +3800,35 @@ mod tests {
         assert!(by_common == expected);
     }
 
+    #[test]
+    fn test_most_common_by() {
+        let counter = Counter::<_>::init("eaddbbccc".chars());
+        // least common first, ties broken reverse alphabetically
+        let by_rare = counter.most_common_by(|a, a_count, b, b_count| {
+            a_count.cmp(b_count).then(b.cmp(a))
+        });
+        let expected = vec![('e', 1), ('a', 1), ('d', 2), ('b', 2), ('c', 3)];
+        assert_eq!(by_rare, expected);
+    }
+
+    #[test]
+    fn test_most_common_iter() {
+        let counter = Counter::init("abbcccdddd".chars());
+        let mut iter = counter.most_common_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next(), Some(('d', 4)));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), vec![('c', 3), ('b', 2), ('a', 1)]);
+    }
+
+    #[test]
+    fn test_most_common_n() {
+        let counter = Counter::init("abbcccdddd".chars());
+        assert_eq!(counter.most_common_n(2), vec![('d', 4), ('c', 3)]);
+        assert_eq!(counter.most_common_n(0), vec![]);
+        assert_eq!(counter.most_common_n(10), counter.most_common());
+    }
+
     #[test]
     fn test_most_common_tiebreaker() {
         let counter = Counter::init("eaddbbccc".chars());
@@ -808,6 +3853,17 @@ mod tests {
         assert!(by_common == expected);
     }
 
+    #[test]
+    fn test_most_common_ordered_is_deterministic() {
+        // every counter gets its own random hash state, so iteration orders differ
+        let text = "the quick brown fox jumps over the lazy dog";
+        let expected = Counter::<_>::init(text.chars()).most_common_ordered();
+        for _ in 0..16 {
+            let reversed = Counter::<_>::init(text.chars().rev());
+            assert_eq!(reversed.most_common_ordered(), expected);
+        }
+    }
+
     #[test]
     fn test_add() {
         let d = Counter::<_>::init("abbccc".chars());
@@ -818,6 +3874,25 @@ mod tests {
         assert!(out == expected);
     }
 
+    #[test]
+    fn test_add_signed_drops_zero_sums() {
+        let c = Counter::from(hashmap!{'a' => 1});
+        let d = Counter::from(hashmap!{'a' => -1, 'b' => 2});
+
+        let mut by_value = c.clone();
+        by_value += d.clone();
+        let mut by_ref = c.clone();
+        by_ref += &d;
+        for out in &[by_value, by_ref, &c + &d, c.clone() + d.clone()] {
+            assert!(out.map == hashmap!{'b' => 2});
+            assert_eq!(out.most_common(), vec![('b', 2)]);
+        }
+
+        let mut updated = c.clone();
+        updated.update_with_counts(d.clone());
+        assert!(updated.map == hashmap!{'b' => 2});
+    }
+
     #[test]
     fn test_sub() {
         let d = Counter::<_>::init("abbccc".chars());
@@ -828,6 +3903,28 @@ mod tests {
         assert!(out == expected);
     }
 
+    #[test]
+    fn test_sub_keys_only_in_one_side() {
+        // keys only on the left are kept as-is, keys only on the right are ignored
+        let d = Counter::<_>::init("aab".chars());
+        let e = Counter::<_>::init("bcc".chars());
+
+        let out = d - e;
+        let expected = hashmap!{
+            'a' => 2,
+        };
+        assert_eq!(out.map, expected);
+    }
+
+    #[test]
+    fn test_sub_drops_non_positive() {
+        let d = Counter::<_>::init("abbccc".chars());
+        let e = Counter::<_>::init("abbbcccc".chars());
+
+        let out = d - e;
+        assert!(out.map.is_empty());
+    }
+
     #[test]
     fn test_intersection() {
         let d = Counter::<_>::init("abbccc".chars());
@@ -838,6 +3935,15 @@ mod tests {
         assert!(out == expected);
     }
 
+    #[test]
+    fn test_intersection_keys_only_in_one_side() {
+        let d = Counter::<_>::init("aab".chars());
+        let e = Counter::<_>::init("cdd".chars());
+
+        let out = d & e;
+        assert!(out.map.is_empty());
+    }
+
     #[test]
     fn test_union() {
         let d = Counter::<_>::init("abbccc".chars());
@@ -848,6 +3954,82 @@ mod tests {
         assert!(out == expected);
     }
 
+    #[test]
+    fn test_intersection_assign() {
+        let mut d = Counter::<_>::init("abbccc".chars());
+        d &= Counter::init("bccddd".chars());
+        assert_eq!(d, Counter::init("bcc".chars()));
+
+        let mut d = Counter::<_>::init("abbccc".chars());
+        let e = Counter::init("bccddd".chars());
+        d &= &e;
+        assert_eq!(d, Counter::init("bcc".chars()));
+    }
+
+    #[test]
+    fn test_union_assign() {
+        let mut d = Counter::<_>::init("abbccc".chars());
+        d |= Counter::init("bccddd".chars());
+        assert_eq!(d, Counter::init("abbcccddd".chars()));
+
+        let mut d = Counter::<_>::init("abbccc".chars());
+        let e = Counter::init("bccddd".chars());
+        d |= &e;
+        assert_eq!(d, Counter::init("abbcccddd".chars()));
+    }
+
+    #[test]
+    fn test_union_keeps_negative_counts() {
+        let mut d: Counter<char, i32> = vec![('a', -2)].into_iter().collect();
+        d |= vec![('b', -1)].into_iter().collect::<Counter<_, _>>();
+        let expected = hashmap!{
+            'a' => -2,
+            'b' => -1,
+        };
+        assert_eq!(d.map, expected);
+    }
+
+    quickcheck! {
+        fn prop_union_idempotent(input: Vec<u8>) -> bool {
+            let c = Counter::<_>::init(input);
+            c.clone() | c.clone() == c
+        }
+
+        fn prop_union_commutative(a: Vec<u8>, b: Vec<u8>) -> bool {
+            let c = Counter::<_>::init(a);
+            let d = Counter::<_>::init(b);
+            c.clone() | d.clone() == d | c
+        }
+    }
+
+    #[test]
+    fn test_ops_on_references() {
+        let d = Counter::<_>::init("abbccc".chars());
+        let e = Counter::<_>::init("bccddd".chars());
+
+        assert_eq!(&d + &e, Counter::init("abbbcccccddd".chars()));
+        assert_eq!(&d - &e, Counter::init("abc".chars()));
+        assert_eq!(&d & &e, Counter::init("bcc".chars()));
+        assert_eq!(&d | &e, Counter::init("abbcccddd".chars()));
+
+        // both operands are still usable
+        assert_eq!(d, Counter::init("abbccc".chars()));
+        assert_eq!(e, Counter::init("bccddd".chars()));
+    }
+
+    #[test]
+    fn test_ops_on_mixed_operands() {
+        let d = Counter::<_>::init("abbccc".chars());
+        let e = Counter::<_>::init("bccddd".chars());
+
+        assert_eq!(d.clone() + &e, &d + e.clone());
+        assert_eq!(d.clone() - &e, &d - e.clone());
+        assert_eq!(d.clone() & &e, &d & e.clone());
+        assert_eq!(d.clone() | &e, &d | e.clone());
+        assert_eq!(d.clone() - &e, Counter::init("abc".chars()));
+        assert_eq!(&e - d.clone(), Counter::init("ddd".chars()));
+    }
+
     #[test]
     fn test_delete_key_from_backing_map() {
         let mut counter = Counter::<_>::init("aa-bb-cc".chars());
@@ -883,10 +4065,104 @@ mod tests {
             .take(items.len() * 2)
             .cloned()
             .collect::<Counter<_>>();
-        let expected: HashMap<char, usize> = items.into_iter().map(|(c, n)| (*c, n * 2)).collect();
+        let expected: HashMap<char, usize> = items.iter().map(|(c, n)| (*c, n * 2)).collect();
+        assert_eq!(counter.map, expected);
+    }
+
+    #[test]
+    fn test_extend_items() {
+        let mut counter = Counter::<_>::init("abbccc".chars());
+        counter.extend("aeeeee".chars());
+        let expected = hashmap!{
+            'a' => 2,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert_eq!(counter.map, expected);
+    }
+
+    #[test]
+    fn test_extend_tuples() {
+        let mut counter = Counter::<_>::init("abbccc".chars());
+        counter.extend(vec![('a', 1), ('e', 5), ('a', 1)]);
+        let expected = hashmap!{
+            'a' => 3,
+            'b' => 2,
+            'c' => 3,
+            'e' => 5,
+        };
+        assert_eq!(counter.map, expected);
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        let map = counter.clone().into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(map, counter.map);
+    }
+
+    #[test]
+    fn test_into_iter_ref() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        let mut items = Vec::new();
+        for (item, count) in &counter {
+            items.push((*item, *count));
+        }
+        items.sort();
+        assert_eq!(items, vec![('a', 1), ('b', 2), ('c', 3)]);
+    }
+
+    #[test]
+    fn test_into_iter_mut() {
+        let mut counter = Counter::<_>::init("abbccc".chars());
+        for (_, count) in &mut counter {
+            *count += 1;
+        }
+        assert_eq!(counter, Counter::init("aabbbcccc".chars()));
+    }
+
+    #[test]
+    fn test_index_missing_key_is_zero() {
+        let counter = Counter::<_>::init("abbccc".chars());
+        assert_eq!(counter[&'c'], 3);
+        assert_eq!(counter[&'z'], 0);
+        assert_eq!(counter.get_or_zero(&'z'), &0);
+        assert!(!counter.map.contains_key(&'z'));
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut counter = Counter::<_>::init("abbccc".chars());
+        counter[&'a'] += 2;
+        counter[&'z'] += 1;
+        let expected = hashmap!{
+            'a' => 3,
+            'b' => 2,
+            'c' => 3,
+            'z' => 1,
+        };
         assert_eq!(counter.map, expected);
     }
 
+    #[test]
+    fn test_index_mut_leaves_zero_entries() {
+        let mut counter = Counter::<_>::init("abb".chars());
+        counter[&'a'] -= 1;
+        let _ = counter[&'z'];
+        counter[&'y'] += 0;
+        assert!(counter.map == hashmap!{'a' => 0, 'b' => 2, 'y' => 0});
+        assert_eq!(counter, Counter::init("bb".chars()));
+        assert_eq!(Counter::init("bb".chars()), counter);
+    }
+
+    #[test]
+    fn test_default() {
+        let counter: Counter<char> = Counter::default();
+        assert!(counter.map.is_empty());
+        assert_eq!(counter, Counter::new());
+    }
+
     #[test]
     fn test_count_minimal_type() {
         #[derive(Debug, Hash, PartialEq, Eq)]
@@ -896,7 +4172,7 @@ mod tests {
 
         impl Inty {
             pub fn new(i: usize) -> Inty {
-                Inty { i: i }
+                Inty { i }
             }
         }
 
@@ -923,6 +4199,21 @@ mod tests {
         assert!(inty_counts.map.get(&Inty { i: 6 }) == Some(&1));
     }
 
+    #[test]
+    fn test_owned_keys_outlive_source() {
+        fn build() -> Counter<String> {
+            let source = String::from("a b a");
+            source.split_whitespace().map(String::from).collect()
+        }
+
+        let counter = build();
+        let expected = hashmap!{
+            "a".to_string() => 2,
+            "b".to_string() => 1,
+        };
+        assert_eq!(counter.map, expected);
+    }
+
     #[test]
     fn test_collect() {
         let counter: Counter<_> = "abbccc".chars().collect();
@@ -934,6 +4225,88 @@ mod tests {
         assert!(counter.map == expected);
     }
 
+    #[test]
+    fn test_collect_borrowed() {
+        let items = vec!['a', 'b', 'b'];
+        let borrowed = items.iter().collect::<Counter<_>>();
+        let expected = hashmap!{
+            &'a' => 1,
+            &'b' => 2,
+        };
+        assert_eq!(borrowed.map, expected);
+
+        let owned = items.iter().cloned().collect::<Counter<_>>();
+        assert_eq!(owned, Counter::init(items.clone()));
+    }
+
+    #[test]
+    fn test_count_types() {
+        let small: Counter<_, u8> = "abbccc".chars().collect();
+        let small = small.clone() + small;
+        assert_eq!(small.most_common(), vec![('c', 6), ('b', 4), ('a', 2)]);
+
+        let signed: Counter<_, i64> = "abbccc".chars().collect();
+        let signed = signed - "bc".chars();
+        assert_eq!(signed.most_common_ordered(), vec![('c', 2), ('a', 1), ('b', 1)]);
+        assert_eq!(signed.total(), 4);
+
+        let wide: Counter<_, u128> = "abbccc".chars().collect();
+        assert_eq!((&wide | &wide).total(), 6);
+    }
+
+    #[test]
+    fn test_float_counts() {
+        let mut c: Counter<char, f64> = Counter::new();
+        c.update_weighted("aab".chars(), 0.5);
+        let d: Counter<char, f64> = vec![('a', 0.25), ('c', 2.0)].into_iter().collect();
+
+        let expected = hashmap!{
+            'a' => 1.25,
+            'b' => 0.5,
+            'c' => 2.0,
+        };
+        assert_eq!((&c + &d).map, expected);
+
+        let expected = hashmap!{
+            'a' => 0.75,
+            'b' => 0.5,
+        };
+        assert_eq!((&c - &d).map, expected);
+
+        let expected = hashmap!{
+            'a' => 0.25,
+        };
+        assert_eq!((&c & &d).map, expected);
+
+        let expected = hashmap!{
+            'a' => 1.0,
+            'b' => 0.5,
+            'c' => 2.0,
+        };
+        assert_eq!((&c | &d).map, expected);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_bigint_counts() {
+        use num_bigint::BigUint;
+
+        let c: Counter<_, BigUint> = "abbccc".chars().collect();
+        let d: Counter<_, BigUint> = "bccddd".chars().collect();
+        let big = |n: u32| BigUint::from(n);
+
+        assert_eq!(
+            (&c + &d).most_common_ordered(),
+            vec![('c', big(5)), ('b', big(3)), ('d', big(3)), ('a', big(1))]
+        );
+        assert_eq!(
+            (&c - &d).most_common_ordered(),
+            vec![('a', big(1)), ('b', big(1)), ('c', big(1))]
+        );
+        assert_eq!((&c & &d).total(), big(3));
+        assert_eq!((&c | &d).total(), big(9));
+    }
+
     #[test]
     fn test_non_usize_count() {
         let counter: Counter<_, i8> = "abbccc".chars().collect();
@@ -944,4 +4317,44 @@ mod tests {
         };
         assert!(counter.map == expected);
     }
+
+    #[test]
+    fn test_custom_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type Fixed = BuildHasherDefault<DefaultHasher>;
+
+        let mut c: Counter<_, usize, Fixed> = Counter::with_hasher(Fixed::default());
+        c.update("abbccc".chars());
+        let d: Counter<_, usize, Fixed> = "bccddd".chars().collect();
+
+        let e = &c + &d;
+        assert_eq!(e.most_common_ordered(), vec![('c', 5), ('b', 3), ('d', 3), ('a', 1)]);
+        assert_eq!(
+            c.clone() - d.clone(),
+            Counter::init_with_hasher("abc".chars(), Fixed::default())
+        );
+        assert!(Counter::<char, usize, Fixed>::default().is_empty());
+    }
+
+    #[cfg(feature = "fast-hash")]
+    #[test]
+    fn test_fast_counter() {
+        let mut c: FastCounter<_> = "abbccc".chars().collect();
+        c += "cd".chars();
+        assert_eq!(c.most_common_ordered(), vec![('c', 4), ('b', 2), ('a', 1), ('d', 1)]);
+        assert_eq!(FastCounter::<char>::default(), &c - &c);
+    }
+
+    #[test]
+    fn test_from_iter_by() {
+        let words = vec!["apple", "avocado", "banana", "blueberry", "cherry"];
+        let by_initial: Counter<char> =
+            Counter::from_iter_by(words.iter(), |word| word.chars().next().unwrap());
+        assert_eq!(by_initial.into_map(), hashmap!{'a' => 2, 'b' => 2, 'c' => 1});
+
+        let by_len: Counter<usize> = Counter::from_iter_by(words, str::len);
+        assert_eq!(by_len[&6], 2);
+    }
 }
@@ -0,0 +1,525 @@
+//! The counters backed by maps other than `HashMap`.
+
+/// Define a counter type backed by `$map` instead of a `HashMap`.
+///
+/// `OrderedCounter` and `InsertionCounter` differ only in their map, the bound on their keys,
+/// the method that removes a key, and the docs that describe the order of their keys, so both
+/// are generated here. `$module` is the module holding the map's `Entry` and iterator types.
+/// The invoking module imports the traits and types named in the expansion.
+macro_rules! map_counter {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident in $map:ident, $($module:ident)::+ where T: [$($bound:tt)+];
+        remove: $remove:ident;
+        order: $order:literal;
+        most_common: $(#[$most_common:meta])*
+        from_counter: $(#[$from_counter:meta])*
+    ) => {
+        $(#[$attr])*
+    pub struct $name<T: $($bound)+, N = usize> {
+        map: $map<T, N>,
+        // necessary for `Index::index` since we cannot declare generic `static` variables.
+        zero: N,
+    }
+
+    impl<T, N> $name<T, N>
+    where
+        T: $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        #[doc = concat!(" Create a new, empty `", stringify!($name), "`")]
+        pub fn new() -> $name<T, N> {
+            $name {
+                map: $map::new(),
+                zero: N::zero(),
+            }
+        }
+
+        #[doc = concat!(" Create a new `", stringify!($name), "` initialized with the given iterable")]
+        pub fn init<I>(iterable: I) -> $name<T, N>
+        where
+            I: IntoIterator<Item = T>,
+        {
+            let mut counter = $name::new();
+            counter.update(iterable);
+            counter
+        }
+
+        /// Add the counts of the elements from the given iterable to this counter
+        pub fn update<I>(&mut self, iterable: I)
+        where
+            I: IntoIterator<Item = T>,
+        {
+            for item in iterable.into_iter() {
+                let entry = self.map.entry(item).or_insert_with(N::zero);
+                *entry += N::one();
+            }
+        }
+
+        /// Remove the counts of the elements from the given iterable to this counter
+        ///
+        /// Non-positive counts are automatically removed
+        ///
+        /// ```rust
+        #[doc = concat!(" # use counter::", stringify!($name), ";")]
+        #[doc = concat!(" let mut counter = \"abbccc\".chars().collect::<", stringify!($name), "<_>>();")]
+        /// counter.subtract("abba".chars());
+        /// assert_eq!(counter.into_iter().collect::<Vec<_>>(), vec![('c', 3)]);
+        /// ```
+        pub fn subtract<I>(&mut self, iterable: I)
+        where
+            I: IntoIterator<Item = T>,
+        {
+            for item in iterable.into_iter() {
+                let mut remove = false;
+                if let Some(entry) = self.map.get_mut(&item) {
+                    if *entry > N::zero() {
+                        *entry -= N::one();
+                    }
+                    remove = *entry == N::zero();
+                }
+                if remove {
+                    self.map.$remove(&item);
+                }
+            }
+        }
+
+        /// Get the count of `key`, or zero if it isn't present.
+        pub fn get_or_zero(&self, key: &T) -> &N {
+            self.map.get(key).unwrap_or(&self.zero)
+        }
+
+        #[doc = concat!(" Consumes this counter and returns the `", stringify!($map), "` mapping the items to the counts.")]
+        pub fn into_map(self) -> $map<T, N> {
+            self.map
+        }
+    }
+
+    impl<T, N> $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + Zero + AddAssign,
+    {
+        /// Sum of all the counts in this counter.
+        pub fn total(&self) -> N {
+            let mut total = N::zero();
+            for count in self.map.values() {
+                total += count.clone();
+            }
+            total
+        }
+    }
+
+    impl<T, N> $name<T, N>
+    where
+        T: $($bound)+ + Clone,
+        N: Clone + Ord,
+    {
+        $(#[$most_common])*
+        pub fn most_common(&self) -> Vec<(T, N)> {
+            let mut items = self
+                .map
+                .iter()
+                .map(|(key, count)| (key.clone(), count.clone()))
+                .collect::<Vec<_>>();
+            // the sort is stable, so ties keep the order of the map
+            items.sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+            items
+        }
+    }
+
+    impl<T, N> AddAssign for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Zero + AddAssign,
+    {
+        /// Add another counter to this counter
+        ///
+        /// `c += d;` -> `c[x] += d[x]` for all `x`
+        fn add_assign(&mut self, rhs: Self) {
+            for (key, value) in rhs.map {
+                let entry = self.map.entry(key).or_insert_with(N::zero);
+                *entry += value;
+            }
+        }
+    }
+
+    impl<'a, T, N> AddAssign<&'a $name<T, N>> for $name<T, N>
+    where
+        T: $($bound)+ + Clone,
+        N: Clone + Zero + AddAssign,
+    {
+        /// Add another counter to this counter, without consuming it
+        ///
+        /// `c += &d;` -> `c[x] += d[x]` for all `x`
+        fn add_assign(&mut self, rhs: &'a $name<T, N>) {
+            for (key, value) in rhs.map.iter() {
+                let entry = self.map.entry(key.clone()).or_insert_with(N::zero);
+                *entry += value.clone();
+            }
+        }
+    }
+
+    impl<T, N> Add for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Zero + AddAssign,
+    {
+        type Output = $name<T, N>;
+
+        /// Add two counters together.
+        ///
+        /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`
+        ///
+        /// ```rust
+        #[doc = concat!(" # use counter::", stringify!($name), ";")]
+        #[doc = concat!(" let c = \"aaab\".chars().collect::<", stringify!($name), "<_>>();")]
+        #[doc = concat!(" let d = \"abb\".chars().collect::<", stringify!($name), "<_>>();")]
+        ///
+        /// let e = c + d;
+        /// assert_eq!(e.into_iter().collect::<Vec<_>>(), vec![('a', 4), ('b', 3)]);
+        /// ```
+        fn add(mut self, rhs: $name<T, N>) -> Self::Output {
+            self += rhs;
+            self
+        }
+    }
+
+    impl<T, N> SubAssign for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + PartialOrd + SubAssign + Zero,
+    {
+        /// Subtract (keeping only positive values).
+        ///
+        /// `c -= d;` -> `c[x] -= d[x]` for all `x`,
+        /// keeping only items with a value greater than N::zero().
+        fn sub_assign(&mut self, rhs: Self) {
+            *self -= &rhs;
+        }
+    }
+
+    impl<'a, T, N> SubAssign<&'a $name<T, N>> for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + PartialOrd + SubAssign + Zero,
+    {
+        /// Subtract another counter without consuming it (keeping only positive values).
+        ///
+        /// `c -= &d;` -> `c[x] -= d[x]` for all `x`,
+        /// keeping only items with a value greater than N::zero().
+        fn sub_assign(&mut self, rhs: &'a $name<T, N>) {
+            for (key, value) in rhs.map.iter() {
+                let mut remove = false;
+                if let Some(entry) = self.map.get_mut(key) {
+                    if *entry >= *value {
+                        *entry -= value.clone();
+                    } else {
+                        remove = true;
+                    }
+                    if *entry == N::zero() {
+                        remove = true;
+                    }
+                }
+                if remove {
+                    self.map.$remove(key);
+                }
+            }
+        }
+    }
+
+    impl<T, N> Sub for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + PartialOrd + SubAssign + Zero,
+    {
+        type Output = $name<T, N>;
+
+        /// Subtract (keeping only positive values).
+        ///
+        /// `out = c - d;` -> `out[x] == c[x] - d[x]` for all `x`,
+        /// keeping only items with a value greater than N::zero().
+        ///
+        /// ```rust
+        #[doc = concat!(" # use counter::", stringify!($name), ";")]
+        #[doc = concat!(" let c = \"aaab\".chars().collect::<", stringify!($name), "<_>>();")]
+        #[doc = concat!(" let d = \"abb\".chars().collect::<", stringify!($name), "<_>>();")]
+        ///
+        /// let e = c - d;
+        /// assert_eq!(e.into_iter().collect::<Vec<_>>(), vec![('a', 2)]);
+        /// ```
+        fn sub(mut self, rhs: $name<T, N>) -> Self::Output {
+            self -= rhs;
+            self
+        }
+    }
+
+    impl<T, N> BitAnd for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + PartialOrd,
+    {
+        type Output = $name<T, N>;
+
+        /// Intersection
+        ///
+        /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+        ///
+        /// ```rust
+        #[doc = concat!(" # use counter::", stringify!($name), ";")]
+        #[doc = concat!(" let c = \"aaab\".chars().collect::<", stringify!($name), "<_>>();")]
+        #[doc = concat!(" let d = \"abb\".chars().collect::<", stringify!($name), "<_>>();")]
+        ///
+        /// let e = c & d;
+        /// assert_eq!(e.into_iter().collect::<Vec<_>>(), vec![('a', 1), ('b', 1)]);
+        /// ```
+        fn bitand(mut self, rhs: $name<T, N>) -> Self::Output {
+            self &= rhs;
+            self
+        }
+    }
+
+    impl<T, N> BitAndAssign for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Clone + PartialOrd,
+    {
+        /// In-place intersection
+        ///
+        /// `c &= d;` -> `c[x] = min(c[x], d[x])`
+        fn bitand_assign(&mut self, rhs: $name<T, N>) {
+            self.map.retain(|key, count| match rhs.map.get(key) {
+                Some(other) => {
+                    if *other < *count {
+                        *count = other.clone();
+                    }
+                    true
+                }
+                None => false,
+            });
+        }
+    }
+
+    impl<T, N> BitOr for $name<T, N>
+    where
+        T: $($bound)+,
+        N: PartialOrd,
+    {
+        type Output = $name<T, N>;
+
+        /// Union
+        ///
+        /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+        ///
+        /// ```rust
+        #[doc = concat!(" # use counter::", stringify!($name), ";")]
+        #[doc = concat!(" let c = \"aaab\".chars().collect::<", stringify!($name), "<_>>();")]
+        #[doc = concat!(" let d = \"abb\".chars().collect::<", stringify!($name), "<_>>();")]
+        ///
+        /// let e = c | d;
+        /// assert_eq!(e.into_iter().collect::<Vec<_>>(), vec![('a', 3), ('b', 2)]);
+        /// ```
+        fn bitor(mut self, rhs: $name<T, N>) -> Self::Output {
+            self |= rhs;
+            self
+        }
+    }
+
+    impl<T, N> BitOrAssign for $name<T, N>
+    where
+        T: $($bound)+,
+        N: PartialOrd,
+    {
+        /// In-place union
+        ///
+        /// `c |= d;` -> `c[x] = max(c[x], d[x])`
+        fn bitor_assign(&mut self, rhs: $name<T, N>) {
+            for (key, value) in rhs.map {
+                match self.map.entry(key) {
+                    $($module)::+::Entry::Occupied(mut entry) => {
+                        if value > *entry.get() {
+                            entry.insert(value);
+                        }
+                    }
+                    $($module)::+::Entry::Vacant(entry) => {
+                        entry.insert(value);
+                    }
+                }
+            }
+        }
+    }
+
+    impl<I, T, N> AddAssign<I> for $name<T, N>
+    where
+        I: IntoIterator<Item = T>,
+        T: $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        /// Directly add the counts of the elements of `I` to `self`
+        fn add_assign(&mut self, rhs: I) {
+            self.update(rhs);
+        }
+    }
+
+    impl<I, T, N> SubAssign<I> for $name<T, N>
+    where
+        I: IntoIterator<Item = T>,
+        T: $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        /// Directly subtract the counts of the elements of `I` from `self`,
+        /// keeping only items with a value greater than N::zero().
+        fn sub_assign(&mut self, rhs: I) {
+            self.subtract(rhs);
+        }
+    }
+
+    impl<T, N> Default for $name<T, N>
+    where
+        T: $($bound)+,
+        N: Zero,
+    {
+        fn default() -> Self {
+            $name {
+                map: $map::new(),
+                zero: N::zero(),
+            }
+        }
+    }
+
+    impl<'a, T, N> Index<&'a T> for $name<T, N>
+    where
+        T: $($bound)+,
+    {
+        type Output = N;
+
+        /// Index in immutable contexts
+        ///
+        /// Returns a reference to a zero count if the key isn't present.
+        fn index(&self, key: &'a T) -> &N {
+            self.map.get(key).unwrap_or(&self.zero)
+        }
+    }
+
+    impl<T, N> Deref for $name<T, N>
+    where
+        T: $($bound)+,
+    {
+        type Target = $map<T, N>;
+        fn deref(&self) -> &$map<T, N> {
+            &self.map
+        }
+    }
+
+    impl<T, N> DerefMut for $name<T, N>
+    where
+        T: $($bound)+,
+    {
+        fn deref_mut(&mut self) -> &mut $map<T, N> {
+            &mut self.map
+        }
+    }
+
+    impl<T, N> IntoIterator for $name<T, N>
+    where
+        T: $($bound)+,
+    {
+        type Item = (T, N);
+        type IntoIter = $($module)::+::IntoIter<T, N>;
+
+        /// Consumes the counter to produce an iterator that yields `(item, count)` tuples
+        #[doc = concat!(" in ", $order, ".")]
+        fn into_iter(self) -> Self::IntoIter {
+            self.map.into_iter()
+        }
+    }
+
+    impl<'a, T, N> IntoIterator for &'a $name<T, N>
+    where
+        T: $($bound)+,
+    {
+        type Item = (&'a T, &'a N);
+        type IntoIter = $($module)::+::Iter<'a, T, N>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.map.iter()
+        }
+    }
+
+    impl<T, N> iter::FromIterator<T> for $name<T, N>
+    where
+        T: $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        #[doc = concat!(" Produce an `", stringify!($name), "` from an iterator of items. This is called automatically")]
+        /// by `iter.collect()`.
+        fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+            $name::init(iter)
+        }
+    }
+
+    impl<T, N> iter::FromIterator<(T, N)> for $name<T, N>
+    where
+        T: $($bound)+,
+        N: AddAssign + Zero,
+    {
+        /// `from_iter` creates a counter from `(item, count)` tuples.
+        ///
+        /// The counts of duplicate items are summed.
+        fn from_iter<I: IntoIterator<Item = (T, N)>>(iter: I) -> Self {
+            let mut cnt = $name::default();
+            cnt.extend(iter);
+            cnt
+        }
+    }
+
+    impl<T, N> Extend<T> for $name<T, N>
+    where
+        T: $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        /// Extend a counter with an iterator of items, exactly like [`update`](#method.update).
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            self.update(iter);
+        }
+    }
+
+    impl<T, N> Extend<(T, N)> for $name<T, N>
+    where
+        T: $($bound)+,
+        N: AddAssign + Zero,
+    {
+        /// Extend a counter with `(item, count)` tuples.
+        ///
+        /// The counts of duplicate items are summed.
+        fn extend<I: IntoIterator<Item = (T, N)>>(&mut self, iter: I) {
+            for (item, item_count) in iter {
+                let entry = self.map.entry(item).or_insert_with(N::zero);
+                *entry += item_count;
+            }
+        }
+    }
+
+    impl<T, N, S> From<Counter<T, N, S>> for $name<T, N>
+    where
+        T: Hash + $($bound)+,
+        N: AddAssign + Zero,
+        S: BuildHasher,
+    {
+        $(#[$from_counter])*
+        fn from(counter: Counter<T, N, S>) -> $name<T, N> {
+            counter.into_iter().collect()
+        }
+    }
+
+    impl<T, N> From<$name<T, N>> for Counter<T, N>
+    where
+        T: Hash + $($bound)+,
+        N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    {
+        fn from(counter: $name<T, N>) -> Counter<T, N> {
+            counter.into_iter().collect()
+        }
+    }
+    };
+}
@@ -0,0 +1,130 @@
+//! Counting the contents of files by mapping them into memory, behind the `mmap` feature.
+
+use memmap2::Mmap;
+use num_traits::{One, Zero};
+
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io;
+use std::ops::{AddAssign, SubAssign};
+use std::path::Path;
+use std::str;
+
+use super::{ByteCounter, Counter};
+
+/// Map the whole of the file at `path` into memory, read-only.
+///
+/// The caller must ensure the file isn't truncated or written to while the map is alive.
+unsafe fn map(path: &Path) -> io::Result<Mmap> {
+    let file = File::open(path)?;
+    Mmap::map(&file)
+}
+
+impl Counter<u8, u64> {
+    /// Create a new `Counter` of the bytes of the file at `path`.
+    ///
+    /// The file is mapped into memory rather than read into a buffer, and counted with
+    /// [`ByteCounter::from_slice`](struct.ByteCounter.html#method.from_slice), which avoids
+    /// copying very large inputs. Requires the `mmap` feature.
+    ///
+    /// # Safety
+    ///
+    /// The contents of a mapped file can change underneath the program, so behaviour is
+    /// undefined if the file is truncated or written to, by this or any other process, while
+    /// it is being counted. The caller must ensure nothing modifies the file meanwhile.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let path = std::env::temp_dir().join("counter-from-file-bytes.txt");
+    /// std::fs::write(&path, "hello").unwrap();
+    /// // Nothing else knows about this file, so nothing can modify it.
+    /// let counter = unsafe { Counter::from_file_bytes(&path) }.unwrap();
+    /// assert_eq!(counter[&b'l'], 2);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub unsafe fn from_file_bytes<P: AsRef<Path>>(path: P) -> io::Result<Counter<u8, u64>> {
+        let map = map(path.as_ref())?;
+        Ok(ByteCounter::from_slice(&map).into())
+    }
+}
+
+impl<N, S> Counter<String, N, S>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// Create a new `Counter` of the lines of the file at `path`.
+    ///
+    /// Lines are split as by [`str::lines`], without their `\n` or `\r\n` ending, just as by
+    /// [`from_lines`](#method.from_lines). The file is mapped into memory instead of read, so
+    /// only lines not seen before are copied, into new keys. Fails if the file can't be
+    /// opened or mapped, or isn't UTF-8. Requires the `mmap` feature.
+    ///
+    /// # Safety
+    ///
+    /// As with [`from_file_bytes`](#method.from_file_bytes), the caller must ensure the file
+    /// isn't truncated or written to, by this or any other process, while it is being
+    /// counted; otherwise behaviour is undefined.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let path = std::env::temp_dir().join("counter-from-file-lines.txt");
+    /// std::fs::write(&path, "GET /\nGET /about\nGET /\n").unwrap();
+    /// // Nothing else knows about this file, so nothing can modify it.
+    /// let counter = unsafe { Counter::<_>::from_file_lines(&path) }.unwrap();
+    /// assert_eq!(counter["GET /"], 2);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub unsafe fn from_file_lines<P: AsRef<Path>>(path: P) -> io::Result<Counter<String, N, S>> {
+        let map = map(path.as_ref())?;
+        let text = str::from_utf8(&map)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let mut counter = Counter::default();
+        for line in text.lines() {
+            counter.add_count_borrowed(line, N::one());
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = env::temp_dir().join(format!("counter-mapped-{}", name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_empty_and_missing_files() {
+        let path = temp_file("empty", b"");
+        unsafe {
+            assert!(Counter::from_file_bytes(&path).unwrap().is_empty());
+            assert!(Counter::<String>::from_file_lines(&path)
+                .unwrap()
+                .is_empty());
+        }
+        fs::remove_file(&path).unwrap();
+
+        let error = unsafe { Counter::from_file_bytes(&path) }.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_lines_must_be_utf8() {
+        let path = temp_file("lines", b"a\r\nb\na");
+        let counter = unsafe { Counter::<String>::from_file_lines(&path) }.unwrap();
+        assert!(counter.into_map() == hashmap!{"a".to_string() => 2, "b".to_string() => 1});
+
+        fs::write(&path, b"a\n\xff\n").unwrap();
+        let error = unsafe { Counter::<String>::from_file_lines(&path) }.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+//! Estimates of how much memory a counter occupies.
+
+use compat::boxed::Box;
+use compat::hash::{BuildHasher, Hash};
+use compat::mem;
+use compat::num::Wrapping;
+use compat::string::String;
+use compat::vec::Vec;
+
+use super::Counter;
+
+/// The number of control bytes the hash table keeps past its last bucket.
+const GROUP_WIDTH: usize = 16;
+
+/// Values which may own memory on the heap, beyond their own `size_of`.
+///
+/// Implement this for key types to include their heap data in
+/// [`Counter::estimated_heap_bytes`](struct.Counter.html#method.estimated_heap_bytes).
+///
+/// ```rust
+/// # use counter::{Counter, HeapSize};
+/// #[derive(PartialEq, Eq, Hash)]
+/// struct Word(String);
+///
+/// impl HeapSize for Word {
+///     fn heap_size(&self) -> usize {
+///         self.0.heap_size()
+///     }
+/// }
+///
+/// let counter = Counter::<_>::init(vec![Word("hello".to_string())]);
+/// assert!(counter.estimated_heap_bytes() >= counter.estimated_memory_bytes() + 5);
+/// ```
+pub trait HeapSize {
+    /// The number of bytes this value owns on the heap, not counting `size_of::<Self>()`.
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_heap_size_inline {
+    ($($t:ty),*) => {
+        $(
+            impl HeapSize for $t {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_heap_size_inline!(u8, u16, u32, u64, u128, usize);
+impl_heap_size_inline!(i8, i16, i32, i64, i128, isize);
+impl_heap_size_inline!(f32, f64, bool, char, ());
+
+impl<T: ?Sized> HeapSize for &T {
+    /// Borrowed data isn't owned by the counter, so it counts for nothing.
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        mem::size_of::<T>() + (**self).heap_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Wrapping<T> {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize, C: HeapSize> HeapSize for (A, B, C) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size() + self.2.heap_size()
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// The approximate number of bytes this counter occupies: the counter itself, plus the
+    /// hash table holding its entries.
+    ///
+    /// This counts every allocated bucket, whether occupied or not, but not any memory owned
+    /// by the keys and counts themselves; see
+    /// [`estimated_heap_bytes`](#method.estimated_heap_bytes) for that. The estimate follows
+    /// the layout of the `hashbrown` table behind the standard `HashMap`, working back from
+    /// its [`capacity`](#method.capacity). Removing items can leave buckets behind which the
+    /// capacity no longer reports, so after heavy pruning this underestimates until the
+    /// counter grows or is shrunk with [`shrink_to_fit`](#method.shrink_to_fit).
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let mut counter = Counter::<u64>::new();
+    /// let empty = counter.estimated_memory_bytes();
+    /// counter.update(0..1000);
+    /// // at least 16 bytes for every key and count
+    /// assert!(counter.estimated_memory_bytes() >= empty + 1000 * 16);
+    /// ```
+    pub fn estimated_memory_bytes(&self) -> usize {
+        mem::size_of::<Self>() + table_bytes::<T, N>(self.map.capacity())
+    }
+}
+
+/// The approximate size of the allocation behind a hash table of `(K, V)` entries with the
+/// given capacity.
+pub(crate) fn table_bytes<K, V>(capacity: usize) -> usize {
+    let buckets = match capacity {
+        0 => return 0,
+        // small tables leave one bucket empty; larger ones are kept at most 7/8 full
+        1..=7 => capacity + 1,
+        _ => capacity / 7 * 8,
+    };
+    buckets * (mem::size_of::<(K, V)>() + 1) + GROUP_WIDTH
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + HeapSize,
+    N: HeapSize,
+    S: BuildHasher,
+{
+    /// The approximate number of bytes this counter occupies, including the heap memory owned
+    /// by its keys and counts.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let words = Counter::<_>::init(vec!["a".repeat(100), "b".repeat(100)]);
+    /// assert!(words.estimated_heap_bytes() >= words.estimated_memory_bytes() + 200);
+    /// ```
+    pub fn estimated_heap_bytes(&self) -> usize {
+        let owned = self
+            .map
+            .iter()
+            .map(|(item, count)| item.heap_size() + count.heap_size())
+            .sum::<usize>();
+        self.estimated_memory_bytes() + owned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_counter_has_no_table() {
+        let counter = Counter::<char>::new();
+        assert_eq!(
+            counter.estimated_memory_bytes(),
+            mem::size_of::<Counter<char>>()
+        );
+    }
+
+    #[test]
+    fn test_estimate_tracks_capacity() {
+        let mut counter = Counter::<u32, u32>::new();
+        counter.update(0..100);
+        let full = counter.estimated_memory_bytes();
+        assert!(full >= 100 * 8);
+        counter.filter_below(100);
+        assert!(counter.estimated_memory_bytes() <= full);
+        counter.shrink_to_fit();
+        assert_eq!(
+            counter.estimated_memory_bytes(),
+            mem::size_of::<Counter<u32, u32>>()
+        );
+    }
+
+    #[test]
+    fn test_heap_size_of_keys() {
+        let mut key = String::with_capacity(32);
+        key.push_str("abc");
+        assert_eq!(key.heap_size(), 32);
+        assert_eq!(vec![1u32, 2].heap_size(), 8);
+        assert_eq!((String::from("ab"), 1u8).heap_size(), 2);
+        assert_eq!(Some(Box::<str>::from("abcd")).heap_size(), 4);
+        assert_eq!("borrowed".heap_size(), 0);
+    }
+}
@@ -0,0 +1,116 @@
+//! Counting the n-grams of a text.
+
+use num_traits::{One, Zero};
+
+use compat::hash::Hash;
+use compat::iter;
+use compat::ops::{AddAssign, SubAssign};
+use compat::vec::Vec;
+
+use super::Counter;
+
+impl<'a, N> Counter<&'a str, N>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new `Counter` of every run of `n` consecutive chars in `text`.
+    ///
+    /// The n-grams overlap, so a text of `k` chars has `k - n + 1` of them, and none if it is
+    /// shorter than `n`. Each n-gram is a slice of `text`, so nothing is copied.
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let trigrams = Counter::<_>::char_ngrams("banana", 3);
+    /// assert_eq!(trigrams[&"ana"], 2);
+    /// assert_eq!(trigrams[&"ban"], 1);
+    /// assert_eq!(trigrams.total(), 4);
+    /// ```
+    pub fn char_ngrams(text: &'a str, n: usize) -> Counter<&'a str, N> {
+        assert!(n != 0, "n-grams must be at least one char long");
+        let starts = text.char_indices().map(|(index, _)| index);
+        let ends = text
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain(iter::once(text.len()))
+            .skip(n);
+        Counter::init(starts.zip(ends).map(|(start, end)| &text[start..end]))
+    }
+}
+
+impl<T, N> Counter<Vec<T>, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new `Counter` of every run of `n` consecutive tokens, such as the bigrams or
+    /// trigrams of a text split into words.
+    ///
+    /// The tokens are read one at a time, so any iterator works, and each n-gram is the
+    /// vector of its tokens: `&str` tokens borrow the text they came from, while `String`
+    /// tokens make the counter own its keys. Look n-grams up by slice.
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let text = "the cat sat on the cat mat";
+    /// let bigrams = Counter::<_>::word_ngrams(text.split_whitespace(), 2);
+    /// assert_eq!(bigrams[&["the", "cat"][..]], 2);
+    /// assert_eq!(bigrams[&["cat", "mat"][..]], 1);
+    /// assert_eq!(bigrams.len(), 5);
+    /// ```
+    pub fn word_ngrams<I>(tokens: I, n: usize) -> Counter<Vec<T>, N>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        assert!(n != 0, "n-grams must be at least one token long");
+        let mut counter = Counter::default();
+        let mut window = Vec::with_capacity(n);
+        for token in tokens {
+            if window.len() == n {
+                window.remove(0);
+            }
+            window.push(token);
+            if window.len() == n {
+                counter.add_count(window.clone(), N::one());
+            }
+        }
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compat::string::{String, ToString};
+
+    #[test]
+    fn test_char_ngrams_of_multibyte_text() {
+        let counter = Counter::<_>::char_ngrams("héhé", 2);
+        assert!(counter.into_map() == hashmap!{"hé" => 2, "éh" => 1});
+    }
+
+    #[test]
+    fn test_char_ngrams_of_short_text() {
+        assert!(Counter::<_>::char_ngrams("ab", 3).is_empty());
+        assert_eq!(Counter::<_>::char_ngrams("ab", 2).into_map().len(), 1);
+        assert!(Counter::<&str>::char_ngrams("", 1).is_empty());
+    }
+
+    #[test]
+    fn test_word_ngrams() {
+        let tokens = vec!["a", "b", "a", "b"];
+        let counter = Counter::<_>::word_ngrams(tokens.iter().map(|t| t.to_string()), 3);
+        assert_eq!(counter.len(), 2);
+        assert_eq!(counter[&["b", "a", "b"].map(String::from)[..]], 1);
+        assert!(Counter::<Vec<&str>>::word_ngrams(tokens, 5).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one char")]
+    fn test_zero_length_ngrams_panic() {
+        Counter::<&str>::char_ngrams("abc", 0);
+    }
+}
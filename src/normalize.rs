@@ -0,0 +1,77 @@
+//! Functions which put keys in a canonical form before they are counted.
+//!
+//! Each of these fits [`Counter::update_normalized`](../struct.Counter.html#method.update_normalized)
+//! and [`Counter::from_iter_by`](../struct.Counter.html#method.from_iter_by), so that keys which
+//! differ only in case, surrounding whitespace or Unicode encoding are counted together,
+//! without first collecting a normalized copy of the data.
+//!
+//! ```rust
+//! # use counter::{normalize, Counter};
+//! let mut counter = Counter::<String>::new();
+//! counter.update_normalized("The cat saw the THE".split(' '), normalize::lowercase);
+//! assert_eq!(counter["the"], 3);
+//! ```
+
+#[cfg(feature = "unicode")]
+use unicode_normalization::UnicodeNormalization;
+
+use compat::string::String;
+
+/// Lowercase `key`, by the Unicode rules of [`str::to_lowercase`].
+pub fn lowercase<S: AsRef<str>>(key: S) -> String {
+    key.as_ref().to_lowercase()
+}
+
+/// Strip leading and trailing whitespace from `key`, without copying it.
+///
+/// ```rust
+/// # use counter::{normalize, Counter};
+/// let counter = Counter::<_>::from_iter_by(" a,a ,b".split(','), normalize::trim);
+/// assert_eq!(counter[&"a"], 2);
+/// ```
+pub fn trim(key: &str) -> &str {
+    key.trim()
+}
+
+/// Put `key` in Unicode Normalization Form C, where accented letters are composed.
+///
+/// A precomposed "é" and an "e" followed by a combining accent look the same but are different
+/// strings; after this they are equal. Requires the `unicode` feature.
+///
+/// ```rust
+/// # use counter::{normalize, Counter};
+/// let counter = Counter::<_>::from_iter_by(vec!["caf\u{e9}", "cafe\u{301}"], normalize::nfc);
+/// assert_eq!(counter["caf\u{e9}"], 2);
+/// ```
+#[cfg(feature = "unicode")]
+pub fn nfc<S: AsRef<str>>(key: S) -> String {
+    key.as_ref().nfc().collect()
+}
+
+/// Trim, lowercase and NFC-normalize `key`, so that words compare as a reader would.
+///
+/// Requires the `unicode` feature.
+#[cfg(feature = "unicode")]
+pub fn caseless<S: AsRef<str>>(key: S) -> String {
+    key.as_ref().trim().to_lowercase().nfc().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lowercase_and_trim() {
+        assert_eq!(lowercase("ÉTÉ"), "été");
+        assert_eq!(lowercase(String::from("Mixed")), "mixed");
+        assert_eq!(trim("\t word \n"), "word");
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_caseless() {
+        assert_eq!(nfc("A\u{30a}"), "\u{c5}");
+        assert_eq!(caseless("  CAFE\u{301} "), "caf\u{e9}");
+        assert_eq!(caseless("caf\u{e9}"), caseless("Cafe\u{301}"));
+    }
+}
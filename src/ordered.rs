@@ -0,0 +1,106 @@
+//! A counter which keeps its keys sorted.
+
+use num_traits::{One, Zero};
+
+use compat::collections::{btree_map, BTreeMap};
+use compat::hash::{BuildHasher, Hash};
+use compat::iter;
+use compat::ops::{
+    Add, AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, Deref, DerefMut, Index, Sub,
+    SubAssign,
+};
+use compat::vec::Vec;
+
+use super::Counter;
+
+map_counter! {
+    /// A counter backed by a `BTreeMap` rather than a `HashMap`.
+    ///
+    /// Keys iterate in sorted order, and because `OrderedCounter<T, N>` implements
+    /// `Deref<Target=BTreeMap<T, N>>`, range queries work directly on the counter.
+    /// Otherwise it supports the same counting and arithmetic as [`Counter`](struct.Counter.html).
+    ///
+    /// ```rust
+    /// # use counter::OrderedCounter;
+    /// let counter = "abracadabra".chars().collect::<OrderedCounter<_>>();
+    /// let in_order = counter.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>();
+    /// assert_eq!(in_order, vec![('a', 5), ('b', 2), ('c', 1), ('d', 1), ('r', 2)]);
+    ///
+    /// let early = counter.range('b'..'d').map(|(&k, &v)| (k, v)).collect::<Vec<_>>();
+    /// assert_eq!(early, vec![('b', 2), ('c', 1)]);
+    /// ```
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct OrderedCounter in BTreeMap, btree_map where T: [Ord];
+    remove: remove;
+    order: "key order";
+    most_common:
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// Keys with equal counts appear in their natural order, as with
+    /// [`Counter::most_common_ordered`](struct.Counter.html#method.most_common_ordered).
+    ///
+    /// ```rust
+    /// # use counter::OrderedCounter;
+    /// let mc = "abracadabra".chars().collect::<OrderedCounter<_>>().most_common();
+    /// let expect = vec![('a', 5), ('b', 2), ('r', 2), ('c', 1), ('d', 1)];
+    /// assert_eq!(mc, expect);
+    /// ```
+    from_counter:
+    /// Sort the keys of a `Counter`.
+    ///
+    /// ```rust
+    /// # use counter::{Counter, OrderedCounter};
+    /// let counter = "cab".chars().collect::<Counter<_>>();
+    /// let ordered = OrderedCounter::from(counter);
+    /// assert_eq!(ordered.keys().collect::<String>(), "abc");
+    /// ```
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_iteration() {
+        let counter = "the quick brown fox"
+            .split_whitespace()
+            .collect::<OrderedCounter<_>>();
+        let keys = counter.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys, vec!["brown", "fox", "quick", "the"]);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let c = "abbccc".chars().collect::<OrderedCounter<_>>();
+        let d = "bccddd".chars().collect::<OrderedCounter<_>>();
+
+        let sum = c.clone() + d.clone();
+        assert_eq!(
+            sum.most_common(),
+            vec![('c', 5), ('b', 3), ('d', 3), ('a', 1)]
+        );
+
+        let diff = c.clone() - d.clone();
+        assert_eq!(diff.into_map(), btreemap!{'a' => 1, 'b' => 1, 'c' => 1});
+
+        assert_eq!((c.clone() & d.clone()).total(), 3);
+        assert_eq!((c | d).total(), 9);
+    }
+
+    #[test]
+    fn test_update_and_subtract() {
+        let mut counter: OrderedCounter<char> = OrderedCounter::new();
+        counter += "aab".chars();
+        counter -= "bc".chars();
+        assert_eq!(counter[&'a'], 2);
+        assert_eq!(counter[&'b'], 0);
+        assert!(!counter.contains_key(&'b'));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let counter = "abbccc".chars().collect::<Counter<_>>();
+        let ordered = OrderedCounter::from(counter.clone());
+        assert_eq!(Counter::from(ordered), counter);
+    }
+}
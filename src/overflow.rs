@@ -0,0 +1,388 @@
+//! Count types which choose what happens when a count overflows.
+//!
+//! A `Counter` performs all of its arithmetic through its count type `N`, so the overflow
+//! behaviour is a property of `N`: every mutation path, from `update` to the arithmetic
+//! operators, applies the same policy.
+//!
+//! - Plain integers panic on overflow in debug builds and wrap in release builds.
+//! - [`Panicking`](struct.Panicking.html) always panics, whatever the build profile.
+//! - [`Saturating`](struct.Saturating.html) clamps counts at the bounds of the inner type,
+//!   which is usually what metrics want.
+//! - [`std::num::Wrapping`](https://doc.rust-lang.org/std/num/struct.Wrapping.html) always wraps.
+//! - [`Promoting`](enum.Promoting.html) keeps each count in a `u64` until that count outgrows
+//!   it, then promotes just that count to a `u128`.
+//!
+//! When no fixed width is enough, use an arbitrary-precision count type such as the one
+//! re-exported by the `bigint` feature.
+//!
+//! ```rust
+//! # use counter::Counter;
+//! use counter::overflow::Saturating;
+//!
+//! let mut counter: Counter<_, Saturating<u8>> = Counter::new();
+//! counter.update(::std::iter::repeat_n('a', 300));
+//! assert_eq!(counter[&'a'], Saturating(255));
+//! ```
+
+use num_traits::{
+    CheckedAdd, CheckedMul, CheckedSub, One, SaturatingAdd, SaturatingMul, SaturatingSub, Zero,
+};
+
+use compat::convert::TryFrom;
+use compat::fmt;
+use compat::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+/// A count which saturates at the bounds of `N` instead of overflowing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Saturating<N>(pub N);
+
+impl<N: SaturatingAdd> Add for Saturating<N> {
+    type Output = Saturating<N>;
+
+    fn add(self, rhs: Saturating<N>) -> Saturating<N> {
+        Saturating(self.0.saturating_add(&rhs.0))
+    }
+}
+
+impl<N: SaturatingSub> Sub for Saturating<N> {
+    type Output = Saturating<N>;
+
+    fn sub(self, rhs: Saturating<N>) -> Saturating<N> {
+        Saturating(self.0.saturating_sub(&rhs.0))
+    }
+}
+
+impl<N: SaturatingMul> Mul for Saturating<N> {
+    type Output = Saturating<N>;
+
+    fn mul(self, rhs: Saturating<N>) -> Saturating<N> {
+        Saturating(self.0.saturating_mul(&rhs.0))
+    }
+}
+
+impl<N: SaturatingAdd> AddAssign for Saturating<N> {
+    fn add_assign(&mut self, rhs: Saturating<N>) {
+        self.0 = self.0.saturating_add(&rhs.0);
+    }
+}
+
+impl<N: SaturatingSub> SubAssign for Saturating<N> {
+    fn sub_assign(&mut self, rhs: Saturating<N>) {
+        self.0 = self.0.saturating_sub(&rhs.0);
+    }
+}
+
+impl<N: SaturatingMul> MulAssign for Saturating<N> {
+    fn mul_assign(&mut self, rhs: Saturating<N>) {
+        self.0 = self.0.saturating_mul(&rhs.0);
+    }
+}
+
+impl<N: Zero + SaturatingAdd> Zero for Saturating<N> {
+    fn zero() -> Saturating<N> {
+        Saturating(N::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl<N: One + SaturatingMul> One for Saturating<N> {
+    fn one() -> Saturating<N> {
+        Saturating(N::one())
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Saturating<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A count which panics on overflow, even in release builds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Panicking<N>(pub N);
+
+const OVERFLOW: &str = "count overflowed";
+
+impl<N: CheckedAdd> Add for Panicking<N> {
+    type Output = Panicking<N>;
+
+    fn add(self, rhs: Panicking<N>) -> Panicking<N> {
+        Panicking(self.0.checked_add(&rhs.0).expect(OVERFLOW))
+    }
+}
+
+impl<N: CheckedSub> Sub for Panicking<N> {
+    type Output = Panicking<N>;
+
+    fn sub(self, rhs: Panicking<N>) -> Panicking<N> {
+        Panicking(self.0.checked_sub(&rhs.0).expect(OVERFLOW))
+    }
+}
+
+impl<N: CheckedMul> Mul for Panicking<N> {
+    type Output = Panicking<N>;
+
+    fn mul(self, rhs: Panicking<N>) -> Panicking<N> {
+        Panicking(self.0.checked_mul(&rhs.0).expect(OVERFLOW))
+    }
+}
+
+impl<N: CheckedAdd> AddAssign for Panicking<N> {
+    fn add_assign(&mut self, rhs: Panicking<N>) {
+        self.0 = self.0.checked_add(&rhs.0).expect(OVERFLOW);
+    }
+}
+
+impl<N: CheckedSub> SubAssign for Panicking<N> {
+    fn sub_assign(&mut self, rhs: Panicking<N>) {
+        self.0 = self.0.checked_sub(&rhs.0).expect(OVERFLOW);
+    }
+}
+
+impl<N: CheckedMul> MulAssign for Panicking<N> {
+    fn mul_assign(&mut self, rhs: Panicking<N>) {
+        self.0 = self.0.checked_mul(&rhs.0).expect(OVERFLOW);
+    }
+}
+
+impl<N: Zero + CheckedAdd> Zero for Panicking<N> {
+    fn zero() -> Panicking<N> {
+        Panicking(N::zero())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl<N: One + CheckedMul> One for Panicking<N> {
+    fn one() -> Panicking<N> {
+        Panicking(N::one())
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Panicking<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// An unsigned count which is promoted from a `u64` to a `u128` when it overflows.
+///
+/// Arithmetic on two narrow counts stays in `u64` until a result doesn't fit, and only the
+/// count which overflowed is promoted. A count which shrinks back into range is narrowed
+/// again, so equal counts always compare equal whatever their history. Panics if a count
+/// overflows `u128` or goes below zero.
+///
+/// ```rust
+/// # use counter::Counter;
+/// use counter::overflow::Promoting;
+///
+/// let mut counter: Counter<_, Promoting> = Counter::new();
+/// counter[&'a'] = Promoting::Narrow(u64::MAX);
+/// counter.update("ab".chars());
+/// assert_eq!(counter[&'a'], Promoting::Wide(u128::from(u64::MAX) + 1));
+/// assert_eq!(counter[&'b'], Promoting::Narrow(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Promoting {
+    /// A count which fits in a `u64`.
+    Narrow(u64),
+    /// A count too large for a `u64`.
+    Wide(u128),
+}
+
+impl Promoting {
+    /// The count, widened to a `u128`.
+    pub fn get(self) -> u128 {
+        match self {
+            Promoting::Narrow(count) => u128::from(count),
+            Promoting::Wide(count) => count,
+        }
+    }
+
+    fn from_wide(count: u128) -> Promoting {
+        match u64::try_from(count) {
+            Ok(count) => Promoting::Narrow(count),
+            Err(_) => Promoting::Wide(count),
+        }
+    }
+
+    fn combine<F, G>(self, rhs: Promoting, narrow: F, wide: G) -> Promoting
+    where
+        F: FnOnce(u64, u64) -> Option<u64>,
+        G: FnOnce(u128, u128) -> Option<u128>,
+    {
+        if let (Promoting::Narrow(a), Promoting::Narrow(b)) = (self, rhs) {
+            if let Some(count) = narrow(a, b) {
+                return Promoting::Narrow(count);
+            }
+        }
+        Promoting::from_wide(wide(self.get(), rhs.get()).expect(OVERFLOW))
+    }
+}
+
+impl Default for Promoting {
+    fn default() -> Promoting {
+        Promoting::Narrow(0)
+    }
+}
+
+impl From<u64> for Promoting {
+    fn from(count: u64) -> Promoting {
+        Promoting::Narrow(count)
+    }
+}
+
+impl From<u128> for Promoting {
+    fn from(count: u128) -> Promoting {
+        Promoting::from_wide(count)
+    }
+}
+
+impl Add for Promoting {
+    type Output = Promoting;
+
+    fn add(self, rhs: Promoting) -> Promoting {
+        self.combine(rhs, u64::checked_add, u128::checked_add)
+    }
+}
+
+impl Sub for Promoting {
+    type Output = Promoting;
+
+    fn sub(self, rhs: Promoting) -> Promoting {
+        self.combine(rhs, u64::checked_sub, u128::checked_sub)
+    }
+}
+
+impl Mul for Promoting {
+    type Output = Promoting;
+
+    fn mul(self, rhs: Promoting) -> Promoting {
+        self.combine(rhs, u64::checked_mul, u128::checked_mul)
+    }
+}
+
+impl AddAssign for Promoting {
+    fn add_assign(&mut self, rhs: Promoting) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Promoting {
+    fn sub_assign(&mut self, rhs: Promoting) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Promoting {
+    fn mul_assign(&mut self, rhs: Promoting) {
+        *self = *self * rhs;
+    }
+}
+
+impl Zero for Promoting {
+    fn zero() -> Promoting {
+        Promoting::Narrow(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Promoting::Narrow(0)
+    }
+}
+
+impl One for Promoting {
+    fn one() -> Promoting {
+        Promoting::Narrow(1)
+    }
+}
+
+impl fmt::Display for Promoting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Promoting::Narrow(count) => count.fmt(f),
+            Promoting::Wide(count) => count.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compat::string::ToString;
+    use std::iter;
+    use std::num::Wrapping;
+    use Counter;
+
+    #[test]
+    fn test_saturating_update() {
+        let mut counter: Counter<_, Saturating<u8>> = Counter::new();
+        counter.update(iter::repeat_n('a', 300));
+        assert_eq!(counter[&'a'], Saturating(255));
+    }
+
+    #[test]
+    fn test_saturating_add() {
+        let c: Counter<_, Saturating<u8>> = iter::repeat_n('a', 200).collect();
+        let out = &c + &c;
+        assert_eq!(out[&'a'], Saturating(255));
+    }
+
+    #[test]
+    #[should_panic(expected = "count overflowed")]
+    fn test_panicking_update() {
+        let mut counter: Counter<_, Panicking<u8>> = Counter::new();
+        counter.update(iter::repeat_n('a', 256));
+    }
+
+    #[test]
+    #[should_panic(expected = "count overflowed")]
+    fn test_panicking_add() {
+        let c: Counter<_, Panicking<u8>> = iter::repeat_n('a', 200).collect();
+        let _ = &c + &c;
+    }
+
+    #[test]
+    fn test_wrapping_update() {
+        let mut counter: Counter<_, Wrapping<u8>> = Counter::new();
+        counter.update(iter::repeat_n('a', 257));
+        assert_eq!(counter[&'a'], Wrapping(1));
+    }
+
+    #[test]
+    fn test_promoting_update() {
+        let mut counter: Counter<_, Promoting> = Counter::new();
+        counter[&'a'] = Promoting::Narrow(u64::MAX - 1);
+        counter.update("aab".chars());
+        assert_eq!(counter[&'a'], Promoting::Wide(u128::from(u64::MAX) + 1));
+        assert_eq!(counter[&'b'], Promoting::Narrow(1));
+        assert_eq!(counter[&'a'].to_string(), "18446744073709551616");
+    }
+
+    #[test]
+    fn test_promoting_narrows_again() {
+        let wide = Promoting::Narrow(u64::MAX) + Promoting::Narrow(2);
+        assert_eq!(wide, Promoting::Wide(u128::from(u64::MAX) + 2));
+        assert_eq!(wide - Promoting::Narrow(2), Promoting::Narrow(u64::MAX));
+        assert!((wide - wide).is_zero());
+    }
+
+    #[test]
+    fn test_promoting_add() {
+        let c: Counter<_, Promoting> = iter::repeat_n('a', 3).collect();
+        let mut big = c.clone();
+        big[&'a'] = Promoting::Narrow(u64::MAX);
+        let out = &c + &big;
+        assert_eq!(out[&'a'].get(), u128::from(u64::MAX) + 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "count overflowed")]
+    fn test_promoting_underflow() {
+        let _ = Promoting::Narrow(1) - Promoting::Narrow(2);
+    }
+}
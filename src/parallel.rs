@@ -0,0 +1,419 @@
+//! Parallel counting for `Counter`, behind the `rayon` feature.
+
+use num_traits::{One, Zero};
+use rayon::prelude::*;
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::hash::{BuildHasher, Hash};
+use std::ops::{AddAssign, SubAssign};
+
+use super::{ByCount, Counter};
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+{
+    /// Add the counts of the elements from the given parallel iterable to this counter
+    ///
+    /// Each of rayon's worker threads counts into its own local counter, and the local
+    /// counters are then merged, so no locking happens while counting.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rayon;
+    /// # use counter::Counter;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut counter = Counter::<_>::new();
+    /// counter.par_update((0..100_000u32).into_par_iter().map(|n| n % 3));
+    /// assert_eq!(counter[&0], 33_334);
+    /// # }
+    /// ```
+    pub fn par_update<I>(&mut self, iterable: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        *self += par_count(iterable, |counter, item| counter.update(Some(item)));
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq + Clone + Send + Sync,
+    N: Clone + Ord + Send + Sync,
+    S: BuildHasher,
+{
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common, sorting
+    /// in parallel.
+    ///
+    /// This gives the same result as [`most_common`](struct.Counter.html#method.most_common),
+    /// including the instability of the ordering of duplicates, but is faster for counters
+    /// with millions of keys.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "pappaopolo".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.par_most_common()[0], ('p', 4));
+    /// ```
+    pub fn par_most_common(&self) -> Vec<(T, N)> {
+        let mut items = self
+            .map
+            .par_iter()
+            .map(|(item, count)| (item.clone(), count.clone()))
+            .collect::<Vec<_>>();
+        items.par_sort_unstable_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+        items
+    }
+
+    /// Create a vector of the `n` most common `(elem, frequency)` pairs, sorted most to least
+    /// common, selecting them in parallel.
+    ///
+    /// Each worker thread keeps a bounded heap of the best entries it has seen, as
+    /// [`most_common_n`](struct.Counter.html#method.most_common_n) does, and the heaps are
+    /// then merged.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "pappaopolo".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.par_most_common_n(2), vec![('p', 4), ('o', 3)]);
+    /// ```
+    ///
+    /// Note that the ordering of duplicates is unstable, as is the choice between keys which
+    /// tie for the last place.
+    pub fn par_most_common_n(&self, n: usize) -> Vec<(T, N)> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        // min-heaps, so that the least common of the retained entries is on top
+        let heap = self
+            .map
+            .par_iter()
+            .fold(BinaryHeap::new, |mut heap, (item, count)| {
+                push_bounded(&mut heap, ByCount { count, item }, n);
+                heap
+            })
+            .reduce(BinaryHeap::new, |mut a, mut b| {
+                if a.len() < b.len() {
+                    ::std::mem::swap(&mut a, &mut b);
+                }
+                for Reverse(entry) in b {
+                    push_bounded(&mut a, entry, n);
+                }
+                a
+            });
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(entry)| (entry.item.clone(), entry.count.clone()))
+            .collect()
+    }
+}
+
+// Add an entry to a min-heap holding at most `n` of the most common entries.
+fn push_bounded<T, N: Ord>(
+    heap: &mut BinaryHeap<Reverse<ByCount<T, N>>>,
+    entry: ByCount<T, N>,
+    n: usize,
+) {
+    if heap.len() < n {
+        heap.push(Reverse(entry));
+    } else if let Some(mut least) = heap.peek_mut() {
+        if entry.count > least.0.count {
+            *least = Reverse(entry);
+        }
+    }
+}
+
+impl<T, N, S> FromParallelIterator<T> for Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+{
+    /// Produce a Counter from a parallel iterator of items.
+    ///
+    /// Items are folded into per-thread counters, which are then merged pairwise; nothing
+    /// is buffered before counting.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rayon;
+    /// # use counter::Counter;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    /// let counter = "abbccc".par_chars().collect::<Counter<_>>();
+    /// assert_eq!(counter, "abbccc".chars().collect::<Counter<_>>());
+    /// # }
+    /// ```
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        par_count(par_iter, |counter, item| counter.update(Some(item)))
+    }
+}
+
+impl<T, N, S> FromParallelIterator<(T, N)> for Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+{
+    /// Produce a Counter from a parallel iterator of `(item, count)` tuples.
+    ///
+    /// The counts of duplicate items are summed.
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = (T, N)>,
+    {
+        par_count(par_iter, |counter, (item, count)| {
+            counter.update_with_counts(Some((item, count)))
+        })
+    }
+}
+
+impl<T, N, S> ParallelExtend<T> for Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+{
+    /// Extend a Counter with a parallel iterator of items, exactly like
+    /// [`par_update`](struct.Counter.html#method.par_update).
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        self.par_update(par_iter);
+    }
+}
+
+impl<T, N, S> ParallelExtend<(T, N)> for Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+{
+    /// Extend a Counter with a parallel iterator of `(item, count)` tuples, adding each
+    /// count to the item's total.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rayon;
+    /// # use counter::Counter;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    /// let mut counter = "ab".chars().collect::<Counter<_>>();
+    /// counter.par_extend(vec![('a', 2), ('c', 3)]);
+    /// assert_eq!(counter[&'a'], 3);
+    /// assert_eq!(counter[&'c'], 3);
+    /// # }
+    /// ```
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = (T, N)>,
+    {
+        *self += par_count(par_iter, |counter, (item, count)| {
+            counter.update_with_counts(Some((item, count)))
+        });
+    }
+}
+
+impl<T, N, S> IntoParallelIterator for Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: Send,
+    S: BuildHasher,
+{
+    type Item = (T, N);
+    type Iter = ::rayon::collections::hash_map::IntoIter<T, N>;
+
+    /// Consumes the Counter to produce a parallel iterator over `(item, count)` pairs.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rayon;
+    /// # use counter::Counter;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let heavy = counter.into_par_iter().filter(|&(_, count)| count > 1).count();
+    /// assert_eq!(heavy, 2);
+    /// # }
+    /// ```
+    fn into_par_iter(self) -> Self::Iter {
+        self.map.into_par_iter()
+    }
+}
+
+impl<'a, T, N, S> IntoParallelIterator for &'a Counter<T, N, S>
+where
+    T: Hash + Eq + Sync,
+    N: Sync,
+    S: BuildHasher,
+{
+    type Item = (&'a T, &'a N);
+    type Iter = ::rayon::collections::hash_map::Iter<'a, T, N>;
+
+    /// A parallel iterator over `(&item, &count)` pairs, also available as `par_iter()`.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rayon;
+    /// # use counter::Counter;
+    /// use rayon::prelude::*;
+    ///
+    /// # fn main() {
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let weighted = counter.par_iter().map(|(_, &count)| count * count).sum::<usize>();
+    /// assert_eq!(weighted, 14);
+    /// # }
+    /// ```
+    fn into_par_iter(self) -> Self::Iter {
+        self.map.par_iter()
+    }
+}
+
+impl<'a, T, N, S> IntoParallelIterator for &'a mut Counter<T, N, S>
+where
+    T: Hash + Eq + Sync,
+    N: Send,
+    S: BuildHasher,
+{
+    type Item = (&'a T, &'a mut N);
+    type Iter = ::rayon::collections::hash_map::IterMut<'a, T, N>;
+
+    /// A parallel iterator over `(&item, &mut count)` pairs, also available as
+    /// `par_iter_mut()`.
+    ///
+    /// As with [`iter_mut`](struct.Counter.html#method.iter_mut), counts set to zero
+    /// through this iterator are not removed.
+    fn into_par_iter(self) -> Self::Iter {
+        self.map.par_iter_mut()
+    }
+}
+
+// Count a parallel iterable by folding into per-thread counters with `add`, then merging
+// them pairwise.
+fn par_count<T, N, S, I, F>(iterable: I, add: F) -> Counter<T, N, S>
+where
+    T: Hash + Eq + Send,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One + Send,
+    S: BuildHasher + Default + Send,
+    I: IntoParallelIterator,
+    F: Fn(&mut Counter<T, N, S>, I::Item) + Sync + Send,
+{
+    iterable
+        .into_par_iter()
+        .fold(Counter::default, |mut counter, item| {
+            add(&mut counter, item);
+            counter
+        })
+        .reduce(Counter::default, |mut a, mut b| {
+            // merge the smaller counter into the larger one
+            if a.map.len() < b.map.len() {
+                ::std::mem::swap(&mut a, &mut b);
+            }
+            a += b;
+            a
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_update_matches_update() {
+        let words = "the quick brown fox jumps over the lazy dog the end"
+            .split_whitespace()
+            .cycle()
+            .take(10_000)
+            .collect::<Vec<_>>();
+        let serial = Counter::<_>::init(words.iter().cloned());
+        let mut parallel = Counter::new();
+        parallel.par_update(words.par_iter().cloned());
+        assert_eq!(parallel, serial);
+
+        parallel.par_update(words.par_iter().cloned());
+        assert_eq!(parallel[&"the"], 2 * serial[&"the"]);
+    }
+
+    #[test]
+    fn test_par_iter() {
+        let mut counter = Counter::<_>::init("abbccc".chars());
+        counter.par_iter_mut().for_each(|(_, count)| *count *= 2);
+        assert_eq!(
+            counter.par_iter().map(|(_, &count)| count).sum::<usize>(),
+            12
+        );
+        let mut pairs = counter.into_par_iter().collect::<Vec<_>>();
+        pairs.sort();
+        assert_eq!(pairs, vec![('a', 2), ('b', 4), ('c', 6)]);
+    }
+
+    #[test]
+    fn test_par_most_common() {
+        let counter = (0..1000u32)
+            .flat_map(|n| ::std::iter::repeat_n(n, n as usize % 97))
+            .collect::<Counter<_>>();
+        let counts =
+            |items: Vec<(u32, usize)>| items.into_iter().map(|(_, c)| c).collect::<Vec<_>>();
+        assert_eq!(
+            counts(counter.par_most_common()),
+            counts(counter.most_common())
+        );
+        for &n in &[0, 1, 5, 50, 2000] {
+            let top = counter.par_most_common_n(n);
+            assert_eq!(top.len(), n.min(counter.len()));
+            assert_eq!(counts(top), counts(counter.most_common_n(n)));
+        }
+    }
+
+    #[test]
+    fn test_from_par_iter() {
+        let counter = (0..10_000u32)
+            .into_par_iter()
+            .map(|n| n % 7)
+            .collect::<Counter<_>>();
+        assert_eq!(
+            counter,
+            (0..10_000u32).map(|n| n % 7).collect::<Counter<_>>()
+        );
+
+        let pairs = vec![('a', 1), ('b', 2), ('a', 3)];
+        let counter = pairs.into_par_iter().collect::<Counter<_>>();
+        assert!(counter.into_map() == hashmap!{'a' => 4, 'b' => 2});
+
+        // counts which cancel out across chunks leave no entry, as when counting serially
+        let pairs = (0..10_000i32)
+            .map(|n| (n % 7, if n % 2 == 0 { 1 } else { -1 }))
+            .collect::<Vec<_>>();
+        let parallel: Counter<i32, i32> = pairs.clone().into_par_iter().collect();
+        let serial: Counter<i32, i32> = pairs.into_iter().collect();
+        assert_eq!(parallel.len(), serial.len());
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_par_extend() {
+        let mut counter = Counter::<_>::init("abb".chars());
+        counter.par_extend("bcc".par_chars());
+        counter.par_extend(vec![('a', 2), ('d', 1)]);
+        let expected = hashmap!{
+            'a' => 3,
+            'b' => 3,
+            'c' => 2,
+            'd' => 1,
+        };
+        assert!(counter.into_map() == expected);
+    }
+}
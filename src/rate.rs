@@ -0,0 +1,249 @@
+//! Counting events in time buckets, to measure their rate.
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use super::Counter;
+
+/// A counter which records when each count happened, in buckets of a fixed `resolution`, and
+/// reports per-key rates over recent windows of time.
+///
+/// Buckets older than the `retention` are dropped as newer counts arrive, so memory is
+/// bounded by the number of keys times `retention / resolution`. Rates are measured over
+/// whole buckets, so windows are rounded up to a multiple of the resolution.
+///
+/// ```rust
+/// # use counter::RateCounter;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut requests = RateCounter::new(Duration::from_secs(1), Duration::from_secs(60));
+/// for second in 0..10 {
+///     let time = start + Duration::from_secs(second);
+///     requests.add_count_at("/", 5, time);
+///     requests.incr_at("/about", time);
+/// }
+/// let now = start + Duration::from_secs(9);
+/// assert_eq!(requests.rate_per_sec_at(&"/", Duration::from_secs(5), now), 5.0);
+/// assert_eq!(requests.count_in_at(&"/about", Duration::from_secs(60), now), 10);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateCounter<T: Hash + Eq> {
+    // the counts of each key, as `(bucket, count)` pairs, oldest bucket first
+    buckets: HashMap<T, VecDeque<(u64, u64)>>,
+    origin: Instant,
+    resolution: Duration,
+    retention: u64,
+}
+
+impl<T> RateCounter<T>
+where
+    T: Hash + Eq,
+{
+    /// Create a new, empty `RateCounter` of buckets `resolution` long, keeping the buckets of
+    /// the last `retention`.
+    ///
+    /// Panics if `resolution` is zero.
+    pub fn new(resolution: Duration, retention: Duration) -> RateCounter<T> {
+        assert!(
+            !resolution.is_zero(),
+            "the buckets of a RateCounter must have a positive length"
+        );
+        RateCounter {
+            buckets: HashMap::new(),
+            origin: Instant::now(),
+            resolution,
+            retention: buckets_in(retention, resolution),
+        }
+    }
+
+    /// The length of each bucket.
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    /// The number of keys with a count in the retained buckets.
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Whether no key has a count in the retained buckets.
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Count one event of `key`, now.
+    pub fn incr(&mut self, key: T) {
+        self.add_count_at(key, 1, Instant::now());
+    }
+
+    /// Count one event of `key`, at `time`.
+    pub fn incr_at(&mut self, key: T, time: Instant) {
+        self.add_count_at(key, 1, time);
+    }
+
+    /// Count `count` events of `key`, at `time`.
+    ///
+    /// Times before the counter was created count in its first bucket. Counts more than
+    /// `retention` older than the newest count of `key` are dropped.
+    pub fn add_count_at(&mut self, key: T, count: u64, time: Instant) {
+        if count == 0 {
+            return;
+        }
+        let bucket = self.bucket_of(time);
+        let retention = self.retention;
+        let buckets = self.buckets.entry(key).or_default();
+        // counts usually arrive in order, so search from the newest bucket
+        match buckets.iter().rposition(|&(b, _)| b <= bucket) {
+            Some(index) if buckets[index].0 == bucket => buckets[index].1 += count,
+            Some(index) => buckets.insert(index + 1, (bucket, count)),
+            None => buckets.push_front((bucket, count)),
+        }
+        let newest = buckets.back().map_or(bucket, |&(b, _)| b);
+        while buckets
+            .front()
+            .is_some_and(|&(b, _)| b.saturating_add(retention) <= newest)
+        {
+            buckets.pop_front();
+        }
+    }
+
+    /// The number of events of `key` in the `window` up to `now`.
+    pub fn count_in_at(&self, key: &T, window: Duration, now: Instant) -> u64 {
+        let (first, last) = self.buckets_of(window, now);
+        self.buckets.get(key).map_or(0, |buckets| {
+            buckets
+                .iter()
+                .filter(|&&(b, _)| first <= b && b <= last)
+                .map(|&(_, count)| count)
+                .sum()
+        })
+    }
+
+    /// The number of events per second of `key` over the `window` up to `now`.
+    ///
+    /// The window is rounded up to whole buckets.
+    pub fn rate_per_sec_at(&self, key: &T, window: Duration, now: Instant) -> f64 {
+        let seconds = buckets_in(window, self.resolution) as f64 * self.resolution.as_secs_f64();
+        self.count_in_at(key, window, now) as f64 / seconds
+    }
+
+    /// The number of events per second of `key` over the last `window`.
+    pub fn rate_per_sec(&self, key: &T, window: Duration) -> f64 {
+        self.rate_per_sec_at(key, window, Instant::now())
+    }
+
+    /// Drop the buckets more than `retention` before `now`, and the keys left without any.
+    pub fn expire(&mut self, now: Instant) {
+        let newest = self.bucket_of(now);
+        let retention = self.retention;
+        self.buckets.retain(|_, buckets| {
+            while buckets
+                .front()
+                .is_some_and(|&(b, _)| b.saturating_add(retention) <= newest)
+            {
+                buckets.pop_front();
+            }
+            !buckets.is_empty()
+        });
+    }
+
+    /// The index of the bucket holding `time`.
+    fn bucket_of(&self, time: Instant) -> u64 {
+        let elapsed = time.saturating_duration_since(self.origin);
+        u64::try_from(elapsed.as_nanos() / self.resolution.as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    /// The first and last buckets of the `window` up to `now`.
+    fn buckets_of(&self, window: Duration, now: Instant) -> (u64, u64) {
+        let last = self.bucket_of(now);
+        let len = buckets_in(window, self.resolution);
+        ((last + 1).saturating_sub(len), last)
+    }
+}
+
+impl<T> RateCounter<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Create a `Counter` of the events of every key in the `window` up to `now`.
+    pub fn counter_in_at(&self, window: Duration, now: Instant) -> Counter<T, u64> {
+        let mut counter = Counter::new();
+        for key in self.buckets.keys() {
+            let count = self.count_in_at(key, window, now);
+            if count > 0 {
+                counter.map.insert(key.clone(), count);
+            }
+        }
+        counter
+    }
+}
+
+/// The number of buckets of length `resolution` needed to cover `span`, at least one.
+fn buckets_in(span: Duration, resolution: Duration) -> u64 {
+    u64::try_from(span.as_nanos().div_ceil(resolution.as_nanos()))
+        .unwrap_or(u64::MAX)
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_are_whole_buckets() {
+        let mut counter = RateCounter::new(Duration::from_millis(100), Duration::from_secs(10));
+        let start = counter.origin;
+        for tenth in 0..20 {
+            counter.incr_at('a', start + Duration::from_millis(tenth * 100 + 50));
+        }
+        let now = start + Duration::from_millis(1950);
+        assert_eq!(
+            counter.count_in_at(&'a', Duration::from_millis(250), now),
+            3
+        );
+        assert_eq!(
+            counter.rate_per_sec_at(&'a', Duration::from_secs(1), now),
+            10.0
+        );
+        assert_eq!(counter.count_in_at(&'b', Duration::from_secs(1), now), 0);
+    }
+
+    #[test]
+    fn test_old_buckets_are_dropped() {
+        let mut counter = RateCounter::new(Duration::from_secs(1), Duration::from_secs(3));
+        let start = counter.origin;
+        counter.add_count_at("x", 4, start);
+        counter.incr_at("y", start + Duration::from_secs(1));
+        // out of order, into an earlier bucket
+        counter.incr_at("x", start + Duration::from_secs(2));
+        counter.incr_at("x", start + Duration::from_millis(500));
+        let now = start + Duration::from_secs(2);
+        assert!(
+            counter
+                .counter_in_at(Duration::from_secs(3), now)
+                .into_map()
+                == hashmap!{"x" => 6, "y" => 1}
+        );
+
+        counter.incr_at("x", start + Duration::from_secs(3));
+        assert_eq!(counter.count_in_at(&"x", Duration::from_secs(60), now), 1);
+        counter.expire(start + Duration::from_secs(4));
+        assert_eq!(counter.len(), 1);
+        counter.expire(start + Duration::from_secs(60));
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_long_retention_saturates() {
+        let mut counter = RateCounter::new(Duration::from_nanos(1), Duration::MAX);
+        let start = counter.origin;
+        counter.incr_at('a', start + Duration::from_secs(1));
+        counter.incr_at('a', start);
+        counter.expire(start + Duration::from_secs(60));
+        let now = start + Duration::from_secs(60);
+        assert_eq!(counter.count_in_at(&'a', Duration::MAX, now), 2);
+    }
+}
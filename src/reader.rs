@@ -0,0 +1,115 @@
+//! Counting the lines and words of a reader, without reading it all into memory.
+
+use num_traits::{One, Zero};
+
+use std::hash::BuildHasher;
+use std::io::{self, BufRead};
+use std::ops::{AddAssign, SubAssign};
+
+use super::Counter;
+
+impl<N, S> Counter<String, N, S>
+where
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+    S: BuildHasher + Default,
+{
+    /// Create a new `Counter` of the lines read from `reader`.
+    ///
+    /// Lines are split as by [`BufRead::lines`], without their `\n` or `\r\n` ending. The
+    /// reader is streamed through a single buffer, and only lines not seen before are copied
+    /// into new keys. Fails on the first read error, or if the input isn't UTF-8.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let log = "GET /\nGET /about\r\nGET /\n";
+    /// let counter = Counter::<_>::from_lines(log.as_bytes()).unwrap();
+    /// assert_eq!(counter["GET /"], 2);
+    /// assert_eq!(counter["GET /about"], 1);
+    /// ```
+    pub fn from_lines<R: BufRead>(reader: R) -> io::Result<Counter<String, N, S>> {
+        let mut counter = Counter::default();
+        for_each_line(reader, |line| {
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            counter.add_count_borrowed(line, N::one());
+        })?;
+        Ok(counter)
+    }
+
+    /// Create a new `Counter` of the words read from `reader`.
+    ///
+    /// Words are separated by whitespace, as by [`str::split_whitespace`]; use
+    /// [`update_normalized`](#method.update_normalized) on the lines instead to also strip
+    /// punctuation or fold case. Like [`from_lines`](#method.from_lines), the reader is
+    /// streamed, and fails on read errors or input which isn't UTF-8.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let text = "the cat and\nthe hat\n";
+    /// let counter = Counter::<_>::from_words(text.as_bytes()).unwrap();
+    /// assert_eq!(counter["the"], 2);
+    /// assert_eq!(counter.len(), 4);
+    /// ```
+    pub fn from_words<R: BufRead>(reader: R) -> io::Result<Counter<String, N, S>> {
+        let mut counter = Counter::default();
+        for_each_line(reader, |line| {
+            for word in line.split_whitespace() {
+                counter.add_count_borrowed(word, N::one());
+            }
+        })?;
+        Ok(counter)
+    }
+}
+
+/// Call `f` on each line of `reader`, `\n` included, reusing one buffer.
+fn for_each_line<R, F>(mut reader: R, mut f: F) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(&str),
+{
+    let mut line = String::new();
+    while reader.read_line(&mut line)? != 0 {
+        f(&line);
+        line.clear();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read;
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+
+    #[test]
+    fn test_lines_without_trailing_newline() {
+        let counter = Counter::<String>::from_lines("a\n\nb\r\na".as_bytes()).unwrap();
+        assert!(
+            counter.into_map()
+                == hashmap!{
+                    "a".to_string() => 2,
+                    "".to_string() => 1,
+                    "b".to_string() => 1,
+                }
+        );
+    }
+
+    #[test]
+    fn test_errors_are_returned() {
+        let invalid: &[u8] = b"ok\n\xff\xfe\n";
+        let error = Counter::<String>::from_words(invalid).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let failing = io::BufReader::new("a b\n".as_bytes().chain(Failing));
+        let error = Counter::<String>::from_lines(failing).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+    }
+}
@@ -0,0 +1,355 @@
+//! Drawing random keys from a counter, weighted by their counts, behind the `rand` feature.
+
+use num_traits::ToPrimitive;
+use rand::Rng;
+
+use std::convert::TryFrom;
+use std::hash::{BuildHasher, Hash};
+
+use super::{to_f64, Counter};
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: ToPrimitive,
+    S: BuildHasher,
+{
+    /// Choose a key at random, with probability proportional to its count.
+    ///
+    /// This treats the counter as the empirical distribution of its keys. Keys with a count
+    /// which isn't positive are never chosen. Returns `None` if no key can be chosen. Each
+    /// draw scans the counter; see [`sample_n`](#method.sample_n) for many draws.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rand;
+    /// # use counter::Counter;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let counter = "aab".chars().collect::<Counter<_>>();
+    /// let key = counter.sample(&mut rng).unwrap();
+    /// assert!(*key == 'a' || *key == 'b');
+    /// assert_eq!(Counter::<char>::new().sample(&mut rng), None);
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        let total = self.map.values().map(weight).sum::<f64>();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = rng.random::<f64>() * total;
+        let mut last = None;
+        for (item, count) in &self.map {
+            let weight = weight(count);
+            if weight > 0.0 {
+                if target < weight {
+                    return Some(item);
+                }
+                target -= weight;
+                last = Some(item);
+            }
+        }
+        // rounding can leave `target` just past the last positive weight
+        last
+    }
+
+    /// Choose `n` keys at random, independently and with replacement, each with probability
+    /// proportional to its count.
+    ///
+    /// The cumulative counts are computed once, so each draw takes logarithmic time. Returns
+    /// an empty vector if no key can be chosen.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rand;
+    /// # use counter::Counter;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let counter = Counter::<_>::init(vec!["heads"; 9].into_iter().chain(Some("tails")));
+    /// let flips = counter.sample_n(&mut rng, 1000);
+    /// let heads = flips.iter().filter(|&&&flip| flip == "heads").count();
+    /// assert!(850 < heads && heads < 950);
+    /// ```
+    pub fn sample_n<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<&T> {
+        let cumulative = self.cumulative_weights();
+        if cumulative.is_empty() {
+            return Vec::new();
+        }
+        (0..n)
+            .map(|_| cumulative[draw(&cumulative, rng)].0)
+            .collect()
+    }
+
+    /// Draw `n` of the counted elements at random, without replacement.
+    ///
+    /// The counter is treated as an urn holding each key as many times as its count, which
+    /// is rounded down to a whole number; each draw takes one element out of the urn. A key
+    /// can be drawn as many times as its count, and once the urn is empty, no more are drawn,
+    /// so fewer than `n` keys are returned if the counts add up to less. The urn is set up
+    /// once, in linear time, and each draw then takes logarithmic time in the number of keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the positive counts add up to more than `u128::MAX`.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rand;
+    /// # use counter::Counter;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let counter = "aab".chars().collect::<Counter<_>>();
+    /// let mut all = counter.sample_n_without_replacement(&mut rng, 5);
+    /// all.sort();
+    /// assert_eq!(all, vec![&'a', &'a', &'b']);
+    /// ```
+    pub fn sample_n_without_replacement<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<&T> {
+        const TOO_MANY: &str = "the counts add up to more than u128::MAX";
+        let (items, counts): (Vec<&T>, Vec<u128>) = self
+            .map
+            .iter()
+            .filter(|&(_, count)| weight(count) > 0.0)
+            .map(|(item, count)| (item, count.to_u128().expect(TOO_MANY)))
+            .filter(|&(_, count)| count > 0)
+            .unzip();
+        let mut remaining = counts
+            .iter()
+            .try_fold(0u128, |sum, &count| sum.checked_add(count))
+            .expect(TOO_MANY);
+        let mut urn = Urn::new(&counts);
+        let mut drawn = Vec::with_capacity(usize::try_from(remaining).map_or(n, |all| n.min(all)));
+        while drawn.len() < n && remaining > 0 {
+            drawn.push(items[urn.take(rng.random_range(0..remaining))]);
+            remaining -= 1;
+        }
+        drawn
+    }
+
+    /// Draw `n` elements at random from the distribution of this counter, with replacement,
+    /// and count them.
+    ///
+    /// This is a multinomial sample: the counts add up to `n`, and each key's expected count
+    /// is `n` times its share of the total. Resampling a counter with its own total, many
+    /// times over, gives bootstrap estimates of how much its frequencies could vary. Returns
+    /// an empty counter if no key can be chosen.
+    ///
+    /// ```rust
+    /// # extern crate counter;
+    /// # extern crate rand;
+    /// # use counter::Counter;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    ///
+    /// let mut rng = StdRng::seed_from_u64(7);
+    /// let observed = "aaaaaaabbc".chars().collect::<Counter<_>>();
+    /// let mut shares = (0..200)
+    ///     .map(|_| observed.resample(&mut rng, 10)[&'a'] as f64 / 10.0)
+    ///     .collect::<Vec<_>>();
+    /// shares.sort_by(f64::total_cmp);
+    /// // a 90% confidence interval for the share of 'a'
+    /// let (low, high) = (shares[10], shares[189]);
+    /// assert!(low < 0.7 && 0.7 < high);
+    /// ```
+    pub fn resample<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Counter<T>
+    where
+        T: Clone,
+    {
+        let cumulative = self.cumulative_weights();
+        let mut counter = Counter::new();
+        if cumulative.is_empty() {
+            return counter;
+        }
+        let mut draws = vec![0; cumulative.len()];
+        for _ in 0..n {
+            draws[draw(&cumulative, rng)] += 1;
+        }
+        counter.map.extend(
+            cumulative
+                .iter()
+                .zip(draws)
+                .filter(|&(_, count)| count > 0)
+                .map(|(&(item, _), count)| (item.clone(), count)),
+        );
+        counter
+    }
+
+    /// Each key with a positive count, paired with the running total of the weights up to and
+    /// including its own.
+    fn cumulative_weights(&self) -> Vec<(&T, f64)> {
+        let mut total = 0.0;
+        self.map
+            .iter()
+            .filter(|&(_, count)| weight(count) > 0.0)
+            .map(|(item, count)| {
+                total += weight(count);
+                (item, total)
+            })
+            .collect()
+    }
+}
+
+/// The weight of a count in a draw: the count itself, or zero if it isn't positive.
+fn weight<N: ToPrimitive>(count: &N) -> f64 {
+    let count = to_f64(count);
+    if count > 0.0 {
+        count
+    } else {
+        0.0
+    }
+}
+
+/// The index of a key drawn from non-empty `cumulative` weights.
+fn draw<T, R: Rng + ?Sized>(cumulative: &[(T, f64)], rng: &mut R) -> usize {
+    let total = cumulative[cumulative.len() - 1].1;
+    let target = rng.random::<f64>() * total;
+    // rounding can put `target` at the very end
+    cumulative
+        .partition_point(|&(_, sum)| sum <= target)
+        .min(cumulative.len() - 1)
+}
+
+/// The elements left in an urn, as a Fenwick tree over the counts of its keys, so that the
+/// element at any position can be found and taken out in logarithmic time.
+struct Urn {
+    // `tree[i]` holds the sum of the counts of the keys in `(i - lowbit(i), i]`, one-based
+    tree: Vec<u128>,
+}
+
+impl Urn {
+    // The partial sums can't overflow, as none exceeds the total of `counts`, which the caller
+    // has checked fits.
+    fn new(counts: &[u128]) -> Urn {
+        let mut tree = vec![0; counts.len() + 1];
+        tree[1..].copy_from_slice(counts);
+        for index in 1..tree.len() {
+            let parent = index + (index & index.wrapping_neg());
+            if parent < tree.len() {
+                tree[parent] += tree[index];
+            }
+        }
+        Urn { tree }
+    }
+
+    // Take out the element at `position`, counting from zero, returning the index of its key.
+    fn take(&mut self, mut position: u128) -> usize {
+        let len = self.tree.len() - 1;
+        // find the most keys whose counts add up to no more than `position`
+        let mut before = 0;
+        let mut step = len.next_power_of_two();
+        while step > 0 {
+            let next = before + step;
+            if next <= len && self.tree[next] <= position {
+                position -= self.tree[next];
+                before = next;
+            }
+            step >>= 1;
+        }
+        let mut index = before + 1;
+        while index <= len {
+            self.tree[index] -= 1;
+            index += index & index.wrapping_neg();
+        }
+        before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_non_positive_counts_are_never_sampled() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut counter = Counter::<_, i32>::new();
+        counter.map.insert('a', -5);
+        counter.map.insert('b', 0);
+        assert_eq!(counter.sample(&mut rng), None);
+        assert!(counter.sample_n(&mut rng, 3).is_empty());
+        counter.map.insert('c', 2);
+        assert_eq!(counter.sample(&mut rng), Some(&'c'));
+        assert_eq!(counter.sample_n(&mut rng, 3), vec![&'c'; 3]);
+        assert_eq!(
+            counter.sample_n_without_replacement(&mut rng, 3),
+            vec![&'c'; 2]
+        );
+    }
+
+    #[test]
+    fn test_sample_frequencies() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let counter = Counter::<_>::init("abbbbbbbbb".chars());
+        let mut draws = Counter::<_>::new();
+        for _ in 0..2000 {
+            draws.update(counter.sample(&mut rng).cloned());
+        }
+        assert!(140 < draws[&'a'] && draws[&'a'] < 260);
+        assert_eq!(draws.total(), 2000);
+    }
+
+    #[test]
+    fn test_without_replacement_empties_the_urn() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let counter = Counter::<_>::init("aaabbc".chars());
+        let drawn = counter.sample_n_without_replacement(&mut rng, 4);
+        assert_eq!(drawn.len(), 4);
+        let all = Counter::<_>::init(counter.sample_n_without_replacement(&mut rng, 100));
+        assert!(all.into_map() == hashmap!{&'a' => 3, &'b' => 2, &'c' => 1});
+    }
+
+    #[test]
+    fn test_without_replacement_many_keys() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let counter: Counter<u32, u64> =
+            (0..100).map(|key| (key, u64::from(key % 3 + 1))).collect();
+        let drawn = counter.sample_n_without_replacement(&mut rng, 1000);
+        assert_eq!(drawn.len() as u64, counter.total());
+        let drawn: Counter<u32, u64> = drawn.into_iter().cloned().collect();
+        assert_eq!(drawn, counter);
+    }
+
+    #[test]
+    fn test_resample_total() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let counter = Counter::<_, u8>::init("aab".chars());
+        let resampled = counter.resample(&mut rng, 300);
+        assert_eq!(resampled.total(), 300);
+        assert!(resampled.keys().all(|key| counter.contains_key(key)));
+        assert!(150 < resampled[&'a'] && resampled[&'a'] < 250);
+        assert!(Counter::<char>::new().resample(&mut rng, 5).is_empty());
+    }
+
+    #[test]
+    fn test_without_replacement_counts_beyond_u64() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut counter = Counter::<_, u128>::new();
+        counter.map.insert('a', 1);
+        counter.map.insert('b', 4 * u128::from(u64::MAX));
+        let drawn = counter.sample_n_without_replacement(&mut rng, 100);
+        assert_eq!(drawn.len(), 100);
+        assert!(drawn.iter().filter(|&&&key| key == 'b').count() >= 99);
+
+        // the total overflows a `u64`, but not the urn
+        let mut counter = Counter::<_, u64>::new();
+        counter.map.insert('a', u64::MAX);
+        counter.map.insert('b', 2);
+        assert_eq!(counter.sample_n_without_replacement(&mut rng, 10).len(), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "the counts add up to more than u128::MAX")]
+    fn test_without_replacement_total_overflow() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counter = Counter::<_, u128>::new();
+        counter.map.insert('a', u128::MAX);
+        counter.map.insert('b', 1);
+        counter.sample_n_without_replacement(&mut rng, 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! `Serialize` and `Deserialize` for `Counter`, behind the `serde` feature.
+
+use num_traits::Zero;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+use std::ops::AddAssign;
+
+use super::Counter;
+
+impl<T, N, S> Serialize for Counter<T, N, S>
+where
+    T: Serialize + Hash + Eq,
+    N: Serialize,
+    S: BuildHasher,
+{
+    /// A counter serializes as a map from keys to counts, exactly like its `HashMap`.
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+impl<'de, T, N, S> Deserialize<'de> for Counter<T, N, S>
+where
+    T: Deserialize<'de> + Hash + Eq,
+    N: Deserialize<'de> + Zero + AddAssign,
+    S: BuildHasher + Default,
+{
+    /// Deserialize a counter from a map of keys to counts, combining repeated keys as by
+    /// [`update_with_counts`](#method.update_with_counts).
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(CounterVisitor(PhantomData))
+    }
+}
+
+struct CounterVisitor<T: Hash + Eq, N, S>(PhantomData<Counter<T, N, S>>);
+
+impl<'de, T, N, S> Visitor<'de> for CounterVisitor<T, N, S>
+where
+    T: Deserialize<'de> + Hash + Eq,
+    N: Deserialize<'de> + Zero + AddAssign,
+    S: BuildHasher + Default,
+{
+    type Value = Counter<T, N, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of keys to counts")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut counter = Counter::default();
+        while let Some((key, count)) = access.next_entry()? {
+            counter.add_count_pruned(key, count);
+        }
+        Ok(counter)
+    }
+}
+
+/// Serialize a `Counter` as a sequence of `(key, count)` pairs rather than as a map.
+///
+/// Formats like JSON only allow string keys in maps, so a counter of tuples or structs can't be
+/// serialized as a map. Use this module with `#[serde(with = "counter::as_pairs")]` to encode
+/// such a counter as a list of pairs instead.
+///
+/// ```rust
+/// # extern crate serde_json;
+/// # #[macro_use] extern crate serde_derive;
+/// # extern crate counter;
+/// use counter::Counter;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Edges {
+///     #[serde(with = "counter::as_pairs")]
+///     counts: Counter<(u32, u32)>,
+/// }
+///
+/// # fn main() {
+/// let edges = Edges { counts: vec![(1, 2), (1, 2)].into_iter().collect() };
+/// let json = serde_json::to_string(&edges).unwrap();
+/// assert_eq!(json, r#"{"counts":[[[1,2],2]]}"#);
+///
+/// let back: Edges = serde_json::from_str(&json).unwrap();
+/// assert_eq!(back.counts, edges.counts);
+/// # }
+/// ```
+pub mod as_pairs {
+    use num_traits::Zero;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use std::hash::{BuildHasher, Hash};
+    use std::ops::AddAssign;
+
+    use Counter;
+
+    /// Serialize `counter` as a sequence of `(key, count)` pairs, in arbitrary order.
+    pub fn serialize<T, N, S, Ser>(
+        counter: &Counter<T, N, S>,
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error>
+    where
+        T: Serialize + Hash + Eq,
+        N: Serialize,
+        S: BuildHasher,
+        Ser: Serializer,
+    {
+        serializer.collect_seq(counter.map.iter())
+    }
+
+    /// Deserialize a counter from a sequence of `(key, count)` pairs.
+    ///
+    /// Repeated keys are combined as by
+    /// [`update_with_counts`](../struct.Counter.html#method.update_with_counts).
+    pub fn deserialize<'de, T, N, S, D>(deserializer: D) -> Result<Counter<T, N, S>, D::Error>
+    where
+        T: Deserialize<'de> + Hash + Eq,
+        N: Deserialize<'de> + Zero + AddAssign,
+        S: BuildHasher + Default,
+        D: Deserializer<'de>,
+    {
+        let pairs = Vec::<(T, N)>::deserialize(deserializer)?;
+        let mut counter = Counter::default();
+        for (key, count) in pairs {
+            counter.add_count_pruned(key, count);
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json;
+    use Counter;
+
+    #[test]
+    fn test_string_keys() {
+        let counter = "the cat and the hat"
+            .split_whitespace()
+            .collect::<Counter<_>>();
+        let json = serde_json::to_string(&counter).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({"the": 2, "cat": 1, "and": 1, "hat": 1})
+        );
+        let back: Counter<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back[&"the".to_string()], 2);
+        assert_eq!(back.len(), 4);
+    }
+
+    #[test]
+    fn test_round_trip_non_string_keys() {
+        let ints = vec![3u32, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5]
+            .into_iter()
+            .collect::<Counter<_>>();
+        let json = serde_json::to_string(&ints).unwrap();
+        assert_eq!(serde_json::from_str::<Counter<u32>>(&json).unwrap(), ints);
+
+        let chars = "abbccc".chars().collect::<Counter<_, i64>>();
+        let json = serde_json::to_string(&chars).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Counter<char, i64>>(&json).unwrap(),
+            chars
+        );
+
+        let bools = vec![true, false, true].into_iter().collect::<Counter<_>>();
+        let json = serde_json::to_string(&bools).unwrap();
+        assert_eq!(serde_json::from_str::<Counter<bool>>(&json).unwrap(), bools);
+    }
+
+    #[test]
+    fn test_as_pairs() {
+        let counter = vec![(1u32, 'a'), (1, 'a'), (2, 'b')]
+            .into_iter()
+            .collect::<Counter<_>>();
+
+        let mut json = Vec::new();
+        super::as_pairs::serialize(&counter, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        let mut pairs: Vec<((u32, char), usize)> = serde_json::from_slice(&json).unwrap();
+        pairs.sort();
+        assert_eq!(pairs, vec![((1, 'a'), 2), ((2, 'b'), 1)]);
+
+        let back: Counter<(u32, char)> =
+            super::as_pairs::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+        assert_eq!(back, counter);
+    }
+
+    #[test]
+    fn test_as_pairs_sums_repeated_keys() {
+        let json = r#"[["a", 1], ["b", 2], ["a", 3]]"#;
+        let counter: Counter<String> =
+            super::as_pairs::deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap();
+        assert_eq!(counter[&"a".to_string()], 4);
+        assert_eq!(counter[&"b".to_string()], 2);
+    }
+
+    #[test]
+    fn test_map_sums_repeated_keys() {
+        let counter: Counter<String> = serde_json::from_str(r#"{"a": 1, "b": 2, "a": 3}"#).unwrap();
+        assert_eq!(counter[&"a".to_string()], 4);
+        assert_eq!(counter[&"b".to_string()], 2);
+
+        let counter: Counter<String, i32> = serde_json::from_str(r#"{"a": 2, "a": -2}"#).unwrap();
+        assert!(counter.is_empty());
+    }
+
+    #[test]
+    fn test_zero_counts_are_dropped() {
+        let counter: Counter<String> = serde_json::from_str(r#"{"a": 0, "b": 1}"#).unwrap();
+        assert_eq!(counter.keys().collect::<Vec<_>>(), vec!["b"]);
+
+        let json = r#"[["a", 1], ["b", 0], ["a", -1]]"#;
+        let counter: Counter<String, i32> =
+            super::as_pairs::deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap();
+        assert!(counter.is_empty());
+    }
+}
@@ -0,0 +1,261 @@
+//! The Count-Min sketch.
+
+use std::collections::hash_map::Entry;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+use super::SketchHasher;
+use Counter;
+
+/// A Count-Min sketch: an approximate counter of `u64` counts in `width × depth` cells.
+///
+/// Each key is counted in one cell of every row, picked by its hash, and its count is
+/// estimated by the smallest of those cells. Other keys landing in the same cells can only
+/// add to them, so an estimate is never below the true count. With a width of `⌈e / ε⌉` and a
+/// depth of `⌈ln(1 / δ)⌉`, an estimate exceeds the true count by more than `ε` times the
+/// [`total`](#method.total) with probability at most `δ`; [`with_error`](#method.with_error)
+/// picks the dimensions that way.
+///
+/// The sketch doesn't store keys, so it can't list them. To recover the most common ones,
+/// query it for candidates with [`heavy_hitters`](#method.heavy_hitters).
+///
+/// ```rust
+/// # use counter::sketch::CountMin;
+/// let mut sketch = CountMin::with_error(0.001, 0.01);
+/// sketch.update("the quick brown fox jumps over the lazy dog".split(' '));
+/// assert!(sketch.query(&"the") >= 2);
+/// assert_eq!(sketch.total(), 9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CountMin<T: ?Sized, S = SketchHasher> {
+    // `depth` rows of `width` cells, row after row
+    cells: Vec<u64>,
+    width: usize,
+    depth: usize,
+    total: u64,
+    hash_builder: S,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized + Hash> CountMin<T> {
+    /// Create a new, empty sketch of `depth` rows of `width` cells.
+    ///
+    /// Panics if either dimension is zero.
+    pub fn new(width: usize, depth: usize) -> CountMin<T> {
+        CountMin::with_hasher(width, depth, SketchHasher::default())
+    }
+
+    /// Create a new, empty sketch whose estimates exceed the true counts by more than
+    /// `epsilon` times the total with probability at most `delta`.
+    ///
+    /// The sketch takes `⌈e / epsilon⌉ × ⌈ln(1 / delta)⌉` cells of 8 bytes. Panics unless
+    /// both parameters are strictly between zero and one.
+    pub fn with_error(epsilon: f64, delta: f64) -> CountMin<T> {
+        assert!(
+            epsilon > 0.0 && epsilon < 1.0 && delta > 0.0 && delta < 1.0,
+            "the error and its probability must be strictly between zero and one"
+        );
+        let width = (::std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        CountMin::new(width, depth)
+    }
+}
+
+impl<T, S> CountMin<T, S>
+where
+    T: ?Sized + Hash,
+    S: BuildHasher,
+{
+    /// Create a new, empty sketch of `depth` rows of `width` cells, which will use the given
+    /// hash builder to hash keys.
+    ///
+    /// Panics if either dimension is zero.
+    pub fn with_hasher(width: usize, depth: usize, hash_builder: S) -> CountMin<T, S> {
+        assert!(
+            width > 0 && depth > 0,
+            "a CountMin sketch needs at least one cell"
+        );
+        CountMin {
+            cells: vec![0; width * depth],
+            width,
+            depth,
+            total: 0,
+            hash_builder,
+            marker: PhantomData,
+        }
+    }
+
+    /// The number of cells in each row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows, each counting every key once.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sum of all the counts added to this sketch.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Add `count` to the count of `item`.
+    pub fn add_count(&mut self, item: &T, count: u64) {
+        for index in self.cell_indices(item) {
+            self.cells[index] = self.cells[index].saturating_add(count);
+        }
+        self.total = self.total.saturating_add(count);
+    }
+
+    /// The estimated count of `item`: at least its true count, and usually equal to it.
+    pub fn query(&self, item: &T) -> u64 {
+        self.cell_indices(item)
+            .map(|index| self.cells[index])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Add the counts of `other` to this sketch, as if its items had been counted here too.
+    ///
+    /// Panics if the sketches have different dimensions. They must also hash keys the same
+    /// way, which sketches using the default hasher do when built with the same toolchain.
+    pub fn merge(&mut self, other: &CountMin<T, S>) {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "only sketches of the same dimensions can be merged"
+        );
+        for (cell, &other_cell) in self.cells.iter_mut().zip(other.cells.iter()) {
+            *cell = cell.saturating_add(other_cell);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    /// Reset every count to zero.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = 0;
+        }
+        self.total = 0;
+    }
+
+    /// The index into `cells` of `item`'s cell in each row.
+    fn cell_indices(&self, item: &T) -> impl Iterator<Item = usize> {
+        // derive every row's hash from one, as `h1 + row * h2`, rather than hashing `depth`
+        // times; this keeps the error bounds of independent hashes
+        let hash = self.hash_builder.hash_one(item);
+        let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        let width = self.width as u64;
+        (0..self.depth as u64)
+            .map(move |row| (row * width + h1.wrapping_add(row.wrapping_mul(h2)) % width) as usize)
+    }
+}
+
+impl<T, S> CountMin<T, S>
+where
+    T: Hash,
+    S: BuildHasher,
+{
+    /// Add the counts of the elements from the given iterable to this sketch
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.add_count(&item, 1);
+        }
+    }
+
+    /// Create an exact `Counter` of the `candidates` whose estimated count is at least
+    /// `threshold`, holding those estimates.
+    ///
+    /// The sketch can't know which keys it has seen, so the candidates must come from
+    /// elsewhere: a sample of the stream, or a second pass over it. Since estimates never
+    /// undercount, no candidate at or above the threshold is missed, but a few below it may
+    /// be let through.
+    ///
+    /// ```rust
+    /// # use counter::sketch::CountMin;
+    /// let words = "a b a c a b d".split(' ');
+    /// let mut sketch = CountMin::with_error(0.01, 0.01);
+    /// sketch.update(words.clone());
+    /// let frequent = sketch.heavy_hitters(words, 2);
+    /// assert_eq!(frequent.most_common_ordered(), vec![("a", 3), ("b", 2)]);
+    /// ```
+    pub fn heavy_hitters<I>(&self, candidates: I, threshold: u64) -> Counter<T, u64>
+    where
+        I: IntoIterator<Item = T>,
+        T: Eq,
+    {
+        let mut counter = Counter::new();
+        for item in candidates {
+            if let Entry::Vacant(entry) = counter.map.entry(item) {
+                let estimate = self.query(entry.key());
+                if estimate >= threshold && estimate > 0 {
+                    entry.insert(estimate);
+                }
+            }
+        }
+        counter
+    }
+}
+
+impl<T, S> Extend<T> for CountMin<T, S>
+where
+    T: Hash,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimates_never_undercount() {
+        let mut sketch = CountMin::new(16, 3);
+        for i in 0..1000u32 {
+            sketch.add_count(&i, u64::from(i % 7));
+        }
+        for i in 0..1000u32 {
+            assert!(sketch.query(&i) >= u64::from(i % 7));
+        }
+        assert_eq!(sketch.total(), (0..1000).map(|i| i % 7).sum::<u64>());
+    }
+
+    #[test]
+    fn test_wide_sketch_is_exact_for_few_keys() {
+        let mut sketch = CountMin::<str>::with_error(0.0001, 0.001);
+        assert_eq!((sketch.width(), sketch.depth()), (27183, 7));
+        sketch.add_count("x", 5);
+        sketch.add_count("y", 2);
+        assert_eq!(sketch.query("x"), 5);
+        assert_eq!(sketch.query("z"), 0);
+        sketch.clear();
+        assert_eq!(sketch.query("x"), 0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = CountMin::new(64, 4);
+        let mut b = CountMin::new(64, 4);
+        a.update("aab".chars());
+        b.update("abc".chars());
+        a.merge(&b);
+        assert!(a.query(&'a') >= 3);
+        assert_eq!(a.total(), 6);
+        assert!(
+            a.heavy_hitters("abcz".chars(), 2).into_map()
+                == hashmap!{'a' => a.query(&'a'), 'b' => a.query(&'b')}
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "same dimensions")]
+    fn test_merge_needs_same_dimensions() {
+        CountMin::<u8>::new(8, 2).merge(&CountMin::new(8, 3));
+    }
+}
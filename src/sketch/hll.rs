@@ -0,0 +1,237 @@
+//! The HyperLogLog cardinality estimator.
+
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+use super::SketchHasher;
+use Counter;
+
+/// A HyperLogLog estimator of the number of distinct items in a stream.
+///
+/// It keeps `2^precision` one-byte registers, whatever the number of items, and estimates
+/// their number with a relative standard error of about `1.04 / √(2^precision)`: 1.6% for
+/// the 4KB of a precision of 12. Estimators with the same precision can be
+/// [`merge`](#method.merge)d, giving the number of distinct items in either stream, which
+/// exact counts can only tell by merging their maps.
+///
+/// Small cardinalities are corrected by linear counting, so they are nearly exact.
+///
+/// ```rust
+/// # use counter::sketch::Hll;
+/// let mut hll = Hll::new(12);
+/// hll.update(0..100_000);
+/// hll.update(0..50_000);
+/// let estimate = hll.estimate() as f64;
+/// assert!((estimate - 100_000.0).abs() < 100_000.0 * 3.0 * hll.relative_error());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Hll<T: ?Sized, S = SketchHasher> {
+    registers: Vec<u8>,
+    precision: u8,
+    hash_builder: S,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: ?Sized + Hash> Hll<T> {
+    /// Create a new, empty estimator with `2^precision` registers.
+    ///
+    /// Panics unless `precision` is between 4 and 18.
+    pub fn new(precision: u8) -> Hll<T> {
+        Hll::with_hasher(precision, SketchHasher::default())
+    }
+}
+
+impl<T, S> Hll<T, S>
+where
+    T: ?Sized + Hash,
+    S: BuildHasher,
+{
+    /// Create a new, empty estimator with `2^precision` registers, which will use the given
+    /// hash builder to hash items.
+    ///
+    /// Panics unless `precision` is between 4 and 18.
+    pub fn with_hasher(precision: u8, hash_builder: S) -> Hll<T, S> {
+        assert!(
+            (4..=18).contains(&precision),
+            "the precision of a HyperLogLog must be between 4 and 18"
+        );
+        Hll {
+            registers: vec![0; 1 << precision],
+            precision,
+            hash_builder,
+            marker: PhantomData,
+        }
+    }
+
+    /// The base-2 logarithm of the number of registers.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// The relative standard error of the estimates.
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    /// Count `item` as seen.
+    pub fn insert(&mut self, item: &T) {
+        let hash = self.hash_builder.hash_one(item);
+        let index = (hash >> (64 - self.precision)) as usize;
+        // the bits left after the index, with a one at the end so the rank stays bounded
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// The estimated number of distinct items seen.
+    pub fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-i32::from(register)))
+            .sum::<f64>();
+        let raw = alpha * m * m / sum;
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+
+    /// Add the items seen by `other` to this estimator.
+    ///
+    /// Panics if the estimators have different precisions. They must also hash items the
+    /// same way, which estimators using the default hasher do when built with the same
+    /// toolchain.
+    pub fn merge(&mut self, other: &Hll<T, S>) {
+        assert!(
+            self.precision == other.precision,
+            "only HyperLogLogs of the same precision can be merged"
+        );
+        for (register, &other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *register = (*register).max(other_register);
+        }
+    }
+
+    /// Forget every item seen.
+    pub fn clear(&mut self) {
+        for register in &mut self.registers {
+            *register = 0;
+        }
+    }
+}
+
+impl<T, S> Hll<T, S>
+where
+    T: Hash,
+    S: BuildHasher,
+{
+    /// Count the elements from the given iterable as seen
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.insert(&item);
+        }
+    }
+}
+
+impl<T, S> Extend<T> for Hll<T, S>
+where
+    T: Hash,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Create a HyperLogLog estimator of `2^precision` registers holding this counter's keys.
+    ///
+    /// A counter knows its own number of distinct keys exactly, as [`len`](#method.len), but
+    /// two counters only know how many they have between them once merged. Their estimators,
+    /// at a few kilobytes each, can be merged instead, and kept alongside the counts or in
+    /// their place.
+    ///
+    /// Panics unless `precision` is between 4 and 18.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let monday = "the cat sat".split(' ').collect::<Counter<_>>();
+    /// let tuesday = "the dog sat".split(' ').collect::<Counter<_>>();
+    /// let mut unique = monday.approx_unique(10);
+    /// unique.merge(&tuesday.approx_unique(10));
+    /// assert_eq!(unique.estimate(), 4);
+    /// ```
+    pub fn approx_unique(&self, precision: u8) -> Hll<T> {
+        let mut hll = Hll::new(precision);
+        for item in self.map.keys() {
+            hll.insert(item);
+        }
+        hll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_cardinalities_are_nearly_exact() {
+        let mut hll = Hll::<str>::new(14);
+        assert_eq!(hll.estimate(), 0);
+        for word in "a b c a b".split(' ') {
+            hll.insert(word);
+        }
+        assert_eq!(hll.estimate(), 3);
+        hll.clear();
+        assert_eq!(hll.estimate(), 0);
+    }
+
+    #[test]
+    fn test_large_cardinality_within_error() {
+        for &precision in &[4, 10, 16] {
+            let mut hll = Hll::new(precision);
+            hll.update(0..200_000u32);
+            let error = (hll.estimate() as f64 - 200_000.0).abs() / 200_000.0;
+            assert!(
+                error < 4.0 * hll.relative_error(),
+                "precision {}",
+                precision
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_is_union() {
+        let mut a = Hll::new(12);
+        a.update(0..10_000u32);
+        let mut b = Hll::new(12);
+        b.update(5_000..15_000u32);
+        let mut union = Hll::new(12);
+        union.update(0..15_000u32);
+        a.merge(&b);
+        assert_eq!(a.estimate(), union.estimate());
+    }
+}
@@ -0,0 +1,29 @@
+//! Approximate counters, for streams with more distinct keys than fit in memory.
+//!
+//! These trade exactness for a fixed memory footprint which doesn't grow with the number of
+//! distinct keys, except for `PrefilteredCounter`, which only saves the memory of the keys
+//! seen once. Their hashes are deterministic, so sketches built separately, by other threads
+//! or by other runs of a program, can be merged as long as they have the same dimensions.
+//! The default hasher is only stable for one Rust toolchain, though, so sketches from
+//! programs built with different compilers may disagree. To merge those, build `CountMin` and
+//! `Hll` sketches `with_hasher` and a hasher whose output is documented as fixed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+mod count_min;
+mod hll;
+mod prefilter;
+mod top_k;
+
+pub use self::count_min::CountMin;
+pub use self::hll::Hll;
+pub use self::prefilter::PrefilteredCounter;
+pub use self::top_k::{Estimate, TopK};
+
+/// The hasher of a sketch which doesn't name one: SipHash with fixed keys, so that every
+/// sketch hashes keys the same way.
+///
+/// The standard library doesn't promise that `DefaultHasher` stays the same across Rust
+/// releases, so this only holds for programs built with the same toolchain.
+pub type SketchHasher = BuildHasherDefault<DefaultHasher>;
@@ -0,0 +1,170 @@
+//! A counter which keeps keys seen only once out of its map, with a Bloom filter.
+
+use num_traits::{One, Zero};
+
+use std::hash::{BuildHasher, Hash};
+use std::ops::{AddAssign, SubAssign};
+
+use super::SketchHasher;
+use Counter;
+
+/// A counter of the keys seen at least twice, which remembers the keys seen once only in a
+/// Bloom filter.
+///
+/// In heavy-tailed data, such as the k-mers of sequencing reads, most keys occur once and
+/// take up most of a counter's memory. Here the first sighting of a key only sets a few bits
+/// of the filter; a key goes into the map, with a count of two, the next time it turns up.
+///
+/// The filter has false positives, at the rate it was created with, so some singletons get
+/// through with a count of two. Passing the same data to [`recount`](#method.recount) makes
+/// the counts exact, dropping the singletons again; the map never holds more than the keys
+/// which passed the filter.
+///
+/// ```rust
+/// # use counter::sketch::PrefilteredCounter;
+/// let reads = "ACGT ACGA ACGT TTTT ACGT";
+/// let mut counter = PrefilteredCounter::<_>::new(1000, 0.01);
+/// counter.update(reads.split(' '));
+/// let exact = counter.recount(reads.split(' '));
+/// assert_eq!(exact[&"ACGT"], 3);
+/// assert!(!exact.contains_key(&"TTTT"));
+/// ```
+#[derive(Clone)]
+pub struct PrefilteredCounter<T: Hash + Eq, N = usize> {
+    // the Bloom filter of keys seen at least once
+    bits: Vec<u64>,
+    bit_len: u64,
+    hashes: u32,
+    hash_builder: SketchHasher,
+    counter: Counter<T, N>,
+}
+
+impl<T, N> PrefilteredCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty counter whose filter is sized for `expected_keys` distinct keys
+    /// with the given rate of false positives.
+    ///
+    /// The filter takes about `-1.44 × log2(false_positive_rate)` bits per expected key: under
+    /// 10 bits for a rate of 1%. Panics unless the rate is strictly between zero and one.
+    pub fn new(expected_keys: usize, false_positive_rate: f64) -> PrefilteredCounter<T, N> {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "the false positive rate must be strictly between zero and one"
+        );
+        let ln2 = ::std::f64::consts::LN_2;
+        let keys = expected_keys.max(1) as f64;
+        let bit_len = (-keys * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let hashes = ((bit_len as f64 / keys) * ln2).round().max(1.0) as u32;
+        PrefilteredCounter {
+            bits: vec![0; bit_len.div_ceil(64) as usize],
+            bit_len,
+            hashes,
+            hash_builder: SketchHasher::default(),
+            counter: Counter::new(),
+        }
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.insert(item);
+        }
+    }
+
+    /// Count one sighting of `item`.
+    ///
+    /// The first sighting is only recorded in the filter. At the second, `item` enters the
+    /// counter with a count of two.
+    pub fn insert(&mut self, item: T) {
+        if let Some(count) = self.counter.map.get_mut(&item) {
+            *count += N::one();
+        } else if self.check_and_set(&item) {
+            self.counter.map.insert(item, N::one() + N::one());
+        }
+    }
+
+    /// The counter of the keys which passed the filter.
+    pub fn counter(&self) -> &Counter<T, N> {
+        &self.counter
+    }
+
+    /// Move out the counter of the keys which passed the filter.
+    ///
+    /// Each count is exact, except for the false positives of the filter, whose count is one
+    /// too high.
+    pub fn into_counter(self) -> Counter<T, N> {
+        self.counter
+    }
+
+    /// Count the given iterable again, for the keys which passed the filter only, and return
+    /// their exact counts, without the keys seen once.
+    ///
+    /// The iterable should hold the same items as were counted before, such as a second
+    /// read of the same file.
+    pub fn recount<I>(self, iterable: I) -> Counter<T, N>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = self.counter;
+        for count in counter.map.values_mut() {
+            *count = N::zero();
+        }
+        for item in iterable.into_iter() {
+            if let Some(count) = counter.map.get_mut(&item) {
+                *count += N::one();
+            }
+        }
+        counter.map.retain(|_, count| *count > N::one());
+        counter
+    }
+
+    /// Set the filter's bits for `item`, returning whether they were all set already.
+    fn check_and_set(&mut self, item: &T) -> bool {
+        let hash = self.hash_builder.hash_one(item);
+        let (h1, h2) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        let mut seen = true;
+        for i in 0..u64::from(self.hashes) {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % self.bit_len;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            seen &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_singletons_stay_out_of_the_map() {
+        let mut counter = PrefilteredCounter::<u32>::new(10_000, 0.001);
+        counter.update(0..10_000);
+        counter.update(vec![7, 7, 42]);
+        assert!(counter.counter().len() < 20);
+        assert_eq!(counter.counter()[&7], 3);
+        assert_eq!(counter.counter()[&42], 2);
+    }
+
+    #[test]
+    fn test_recount_is_exact() {
+        let data = (0..5_000u32).chain(0..100).chain(0..10).collect::<Vec<_>>();
+        // a tiny filter, so most singletons get through
+        let mut counter = PrefilteredCounter::<_, u8>::new(10, 0.5);
+        counter.update(data.iter().cloned());
+        assert!(counter.counter().len() > 100);
+        let exact = counter.recount(data);
+        assert_eq!(exact.len(), 100);
+        assert_eq!(exact[&5], 3);
+        assert_eq!(exact[&50], 2);
+    }
+}
@@ -0,0 +1,319 @@
+//! The Space-Saving heavy-hitters summary.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::SketchHasher;
+use Counter;
+
+/// The estimated count of an item in a [`TopK`](struct.TopK.html) summary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Estimate {
+    /// An upper bound on the item's true count.
+    pub count: u64,
+    /// How much of `count` may be overestimated: the true count is at least
+    /// `count - error`.
+    pub error: u64,
+}
+
+impl Estimate {
+    /// A lower bound on the item's true count.
+    pub fn lower_bound(&self) -> u64 {
+        self.count - self.error
+    }
+}
+
+/// A summary of the most frequent items of a stream, in the memory of `capacity` items.
+///
+/// This is the Space-Saving algorithm of Metwally, Agrawal and El Abbadi. It counts the first
+/// `capacity` distinct items exactly. After that, a new item takes the place of the least
+/// frequent one, inheriting its count as the [`error`](struct.Estimate.html#structfield.error)
+/// of its own [`Estimate`](struct.Estimate.html). Any item whose true count exceeds
+/// `total / capacity` is guaranteed to be in the summary, and every estimate exceeds the true
+/// count by at most that much.
+///
+/// Replacing an item scans the summary for the least frequent one, so keep the capacity in
+/// the hundreds or low thousands.
+///
+/// ```rust
+/// # use counter::sketch::TopK;
+/// let mut top = TopK::new(2);
+/// top.update("abacabadabacaba".chars());
+/// let most_common = top.most_common();
+/// assert_eq!(most_common[0].0, 'a');
+/// assert_eq!(most_common[0].1.lower_bound(), 8);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TopK<T: Hash + Eq> {
+    items: HashMap<T, Estimate, SketchHasher>,
+    capacity: usize,
+    total: u64,
+}
+
+impl<T> TopK<T>
+where
+    T: Hash + Eq,
+{
+    /// Create a new, empty summary which tracks at most `capacity` items.
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> TopK<T> {
+        assert!(
+            capacity > 0,
+            "a TopK summary needs room for at least one item"
+        );
+        TopK {
+            items: HashMap::with_capacity_and_hasher(capacity, SketchHasher::default()),
+            capacity,
+            total: 0,
+        }
+    }
+
+    /// The most items the summary tracks.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of items the summary is tracking.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether no item has been counted.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Sum of all the counts added to this summary.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Add the counts of the elements from the given iterable to this summary
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.add_count(item, 1);
+        }
+    }
+
+    /// Add `count` to the count of `item`, replacing the least frequent item if `item` isn't
+    /// tracked and the summary is full.
+    pub fn add_count(&mut self, item: T, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.total = self.total.saturating_add(count);
+        if let Some(estimate) = self.items.get_mut(&item) {
+            estimate.count = estimate.count.saturating_add(count);
+            return;
+        }
+        let error = if self.items.len() < self.capacity {
+            0
+        } else {
+            self.evict_min()
+        };
+        self.items.insert(
+            item,
+            Estimate {
+                count: error.saturating_add(count),
+                error,
+            },
+        );
+    }
+
+    /// The estimated count of `item`, if it is tracked.
+    ///
+    /// An untracked item's true count is at most [`min_count`](#method.min_count).
+    pub fn estimate(&self, item: &T) -> Option<Estimate> {
+        self.items.get(item).cloned()
+    }
+
+    /// The smallest count in a full summary, or zero if there is room left.
+    ///
+    /// This bounds the count of any item which isn't tracked.
+    pub fn min_count(&self) -> u64 {
+        if self.items.len() < self.capacity {
+            0
+        } else {
+            self.items
+                .values()
+                .map(|estimate| estimate.count)
+                .min()
+                .unwrap_or(0)
+        }
+    }
+
+    /// Add the items of `other` to this summary, as if its stream had been counted here too.
+    ///
+    /// Items missing from one summary are assumed to have had its
+    /// [`min_count`](#method.min_count) there, which keeps the error bounds of both. The
+    /// merged summary keeps the `capacity` items with the highest counts.
+    pub fn merge(&mut self, other: TopK<T>) {
+        let (self_min, other_min) = (self.min_count(), other.min_count());
+        let mut other_items = other.items;
+        let mut items = Vec::with_capacity(self.items.len() + other_items.len());
+        for (item, estimate) in self.items.drain() {
+            let (count, error) = match other_items.remove(&item) {
+                Some(other_estimate) => (other_estimate.count, other_estimate.error),
+                None => (other_min, other_min),
+            };
+            let estimate = Estimate {
+                count: estimate.count.saturating_add(count),
+                error: estimate.error.saturating_add(error),
+            };
+            items.push((item, estimate));
+        }
+        for (item, estimate) in other_items {
+            let estimate = Estimate {
+                count: estimate.count.saturating_add(self_min),
+                error: estimate.error.saturating_add(self_min),
+            };
+            items.push((item, estimate));
+        }
+
+        items.sort_by_key(|&(_, estimate)| Reverse(estimate.count));
+        items.truncate(self.capacity);
+        self.items.extend(items);
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    /// Move the upper bounds of the tracked items' counts into a `Counter`.
+    pub fn into_counter(self) -> Counter<T, u64> {
+        let mut counter = Counter::new();
+        counter.map.extend(
+            self.items
+                .into_iter()
+                .map(|(item, estimate)| (item, estimate.count)),
+        );
+        counter
+    }
+
+    /// Move the lower bounds of the tracked items' counts into a `Counter`, leaving out those
+    /// which may not have been seen at all.
+    ///
+    /// These are the counts each item is certain to have reached.
+    pub fn into_guaranteed_counter(self) -> Counter<T, u64> {
+        let mut counter = Counter::new();
+        counter.map.extend(
+            self.items
+                .into_iter()
+                .map(|(item, estimate)| (item, estimate.lower_bound()))
+                .filter(|&(_, count)| count > 0),
+        );
+        counter
+    }
+
+    /// Remove an item with the smallest count from a full summary, returning that count.
+    fn evict_min(&mut self) -> u64 {
+        let min = self.min_count();
+        let mut evicted = false;
+        self.items.retain(|_, estimate| {
+            let keep = evicted || estimate.count != min;
+            evicted |= !keep;
+            keep
+        });
+        min
+    }
+}
+
+impl<T> TopK<T>
+where
+    T: Hash + Eq + Clone,
+{
+    /// Create a vector of the tracked items and their estimates, most to least common.
+    ///
+    /// Note that the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, Estimate)> {
+        let mut items = self
+            .items
+            .iter()
+            .map(|(item, &estimate)| (item.clone(), estimate))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|&(_, estimate)| Reverse(estimate.count));
+        items
+    }
+}
+
+impl<T> Extend<T> for TopK<T>
+where
+    T: Hash + Eq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_below_capacity() {
+        let mut top = TopK::new(3);
+        top.update("abbccc".chars());
+        assert_eq!(top.min_count(), 1);
+        assert_eq!(top.estimate(&'c'), Some(Estimate { count: 3, error: 0 }));
+        assert!(
+            top.into_guaranteed_counter().into_map() == hashmap!{'a' => 1, 'b' => 2, 'c' => 3}
+        );
+    }
+
+    #[test]
+    fn test_replacement_inherits_error() {
+        let mut top = TopK::new(2);
+        top.update("aaab".chars());
+        top.add_count('c', 2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top.estimate(&'b'), None);
+        assert_eq!(top.estimate(&'c'), Some(Estimate { count: 3, error: 1 }));
+        assert_eq!(top.total(), 6);
+        assert!(top.clone().into_counter().into_map() == hashmap!{'a' => 3, 'c' => 3});
+        assert!(top.into_guaranteed_counter().into_map() == hashmap!{'a' => 3, 'c' => 2});
+    }
+
+    #[test]
+    fn test_merge_keeps_bounds() {
+        let stream = "aaaaabbbcccdde".chars().collect::<Vec<_>>();
+        let (left, right) = stream.split_at(7);
+        let mut top = TopK::new(3);
+        top.update(left.iter().cloned());
+        let mut other = TopK::new(3);
+        other.update(right.iter().cloned());
+        top.merge(other);
+
+        assert_eq!(top.len(), 3);
+        assert_eq!(top.total(), 14);
+        let exact = Counter::<_, u64>::init(stream);
+        for (item, estimate) in top.most_common() {
+            assert!(estimate.lower_bound() <= exact[&item]);
+            assert!(estimate.count >= exact[&item]);
+        }
+        assert_eq!(top.most_common()[0].0, 'a');
+    }
+
+    #[test]
+    fn test_counts_saturate() {
+        let mut top = TopK::new(1);
+        top.add_count('a', u64::MAX);
+        top.add_count('a', 1);
+        top.add_count('b', 1);
+        assert_eq!(top.total(), u64::MAX);
+        assert_eq!(
+            top.estimate(&'b'),
+            Some(Estimate {
+                count: u64::MAX,
+                error: u64::MAX,
+            })
+        );
+
+        let mut other = TopK::new(1);
+        other.add_count('b', 2);
+        top.merge(other);
+        assert_eq!(top.total(), u64::MAX);
+        assert_eq!(top.estimate(&'b').unwrap().count, u64::MAX);
+    }
+}
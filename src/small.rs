@@ -0,0 +1,659 @@
+//! A counter for a handful of distinct keys.
+
+use num_traits::{One, Zero};
+
+use compat::collections::{hash_map, HashMap};
+use compat::hash::Hash;
+use compat::iter;
+use compat::ops::{Add, AddAssign, BitAnd, BitOr, Index, Sub, SubAssign};
+use compat::slice;
+use compat::vec::Vec;
+
+use super::Counter;
+
+/// The number of distinct keys a `SmallCounter` holds inline unless told otherwise.
+const DEFAULT_PROMOTE_AT: usize = 32;
+
+#[derive(Clone, Debug)]
+enum Repr<T, N> {
+    Inline(Vec<(T, N)>),
+    Map(HashMap<T, N>),
+}
+
+/// A counter which stores its entries in a `Vec` and finds them by linear scan.
+///
+/// When only a few distinct keys are counted, scanning a short vector is faster than hashing.
+/// Once the counter holds more than its promotion threshold of distinct keys, it moves its
+/// entries into a `HashMap` and carries on from there. The default threshold is 32 keys;
+/// [`with_promote_at`](#method.with_promote_at) changes it, and `usize::MAX` disables
+/// promotion altogether.
+///
+/// ```rust
+/// # use counter::SmallCounter;
+/// let mut counter = "abbccc".chars().collect::<SmallCounter<_>>();
+/// counter += "cd".chars();
+/// assert_eq!(counter[&'c'], 4);
+/// assert_eq!(counter.most_common(), vec![('c', 4), ('b', 2), ('a', 1), ('d', 1)]);
+/// assert!(!counter.is_promoted());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SmallCounter<T: Hash + Eq, N = usize> {
+    repr: Repr<T, N>,
+    promote_at: usize,
+    // necessary for `Index::index` since we cannot declare generic `static` variables.
+    zero: N,
+}
+
+impl<T, N> SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero,
+{
+    /// Create a new, empty `SmallCounter` with the default promotion threshold
+    pub fn new() -> SmallCounter<T, N> {
+        SmallCounter::with_promote_at(DEFAULT_PROMOTE_AT)
+    }
+
+    /// Create a new, empty `SmallCounter` which switches to a `HashMap` once it holds more
+    /// than `promote_at` distinct keys.
+    ///
+    /// ```rust
+    /// # use counter::SmallCounter;
+    /// let mut counter: SmallCounter<char> = SmallCounter::with_promote_at(2);
+    /// counter.update("aab".chars());
+    /// assert!(!counter.is_promoted());
+    /// counter.update("c".chars());
+    /// assert!(counter.is_promoted());
+    /// assert_eq!(counter[&'a'], 2);
+    /// ```
+    pub fn with_promote_at(promote_at: usize) -> SmallCounter<T, N> {
+        SmallCounter {
+            repr: Repr::Inline(Vec::new()),
+            promote_at,
+            zero: N::zero(),
+        }
+    }
+
+    /// Whether the counter has outgrown its inline storage and moved to a `HashMap`.
+    pub fn is_promoted(&self) -> bool {
+        match &self.repr {
+            Repr::Inline(_) => false,
+            Repr::Map(_) => true,
+        }
+    }
+
+    /// The number of distinct keys in the counter.
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Inline(entries) => entries.len(),
+            Repr::Map(map) => map.len(),
+        }
+    }
+
+    /// Whether the counter contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the count of `key`, if it is present.
+    pub fn get(&self, key: &T) -> Option<&N> {
+        match &self.repr {
+            Repr::Inline(entries) => entries
+                .iter()
+                .find(|(item, _)| item == key)
+                .map(|(_, count)| count),
+            Repr::Map(map) => map.get(key),
+        }
+    }
+
+    /// Get the count of `key`, or zero if it isn't present.
+    pub fn get_or_zero(&self, key: &T) -> &N {
+        self.get(key).unwrap_or(&self.zero)
+    }
+
+    /// Whether `key` has a count in this counter.
+    pub fn contains_key(&self, key: &T) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Remove `key` from the counter, returning its count if it was present.
+    ///
+    /// While the counter is inline, the other keys keep the order in which they were first seen.
+    pub fn remove(&mut self, key: &T) -> Option<N> {
+        match &mut self.repr {
+            Repr::Inline(entries) => entries
+                .iter()
+                .position(|(item, _)| item == key)
+                .map(|index| entries.remove(index).1),
+            Repr::Map(map) => map.remove(key),
+        }
+    }
+
+    /// Iterate over `(&item, &count)` pairs, in arbitrary order.
+    pub fn iter(&self) -> SmallIter<'_, T, N> {
+        match &self.repr {
+            Repr::Inline(entries) => SmallIter::Inline(entries.iter()),
+            Repr::Map(map) => SmallIter::Map(map.iter()),
+        }
+    }
+
+    /// Consumes this counter and returns a HashMap mapping the items to the counts.
+    pub fn into_map(self) -> HashMap<T, N> {
+        match self.repr {
+            Repr::Inline(entries) => entries.into_iter().collect(),
+            Repr::Map(map) => map,
+        }
+    }
+
+    /// Get a mutable reference to the count of `key`, inserting a zero count if it is absent.
+    fn entry(&mut self, key: T) -> &mut N {
+        if let Repr::Inline(entries) = &self.repr {
+            if entries.len() >= self.promote_at && !entries.iter().any(|(item, _)| *item == key) {
+                self.promote();
+            }
+        }
+        match &mut self.repr {
+            Repr::Inline(entries) => {
+                let index = match entries.iter().position(|(item, _)| *item == key) {
+                    Some(index) => index,
+                    None => {
+                        entries.push((key, N::zero()));
+                        entries.len() - 1
+                    }
+                };
+                &mut entries[index].1
+            }
+            Repr::Map(map) => map.entry(key).or_insert_with(N::zero),
+        }
+    }
+
+    fn get_mut(&mut self, key: &T) -> Option<&mut N> {
+        match &mut self.repr {
+            Repr::Inline(entries) => entries
+                .iter_mut()
+                .find(|(item, _)| item == key)
+                .map(|(_, count)| count),
+            Repr::Map(map) => map.get_mut(key),
+        }
+    }
+
+    fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T, &mut N) -> bool,
+    {
+        match &mut self.repr {
+            Repr::Inline(entries) => entries.retain_mut(|(item, count)| keep(item, count)),
+            Repr::Map(map) => map.retain(|item, count| keep(item, count)),
+        }
+    }
+
+    fn promote(&mut self) {
+        let map = match &mut self.repr {
+            Repr::Inline(entries) => entries.drain(..).collect(),
+            Repr::Map(_) => return,
+        };
+        self.repr = Repr::Map(map);
+    }
+}
+
+impl<T, N> SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new `SmallCounter` initialized with the given iterable
+    pub fn init<I>(iterable: I) -> SmallCounter<T, N>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counter = SmallCounter::new();
+        counter.update(iterable);
+        counter
+    }
+
+    /// Add the counts of the elements from the given iterable to this counter
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            *self.entry(item) += N::one();
+        }
+    }
+
+    /// Remove the counts of the elements from the given iterable to this counter
+    ///
+    /// Non-positive counts are automatically removed
+    pub fn subtract<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            let mut remove = false;
+            if let Some(entry) = self.get_mut(&item) {
+                if *entry > N::zero() {
+                    *entry -= N::one();
+                }
+                remove = *entry == N::zero();
+            }
+            if remove {
+                self.remove(&item);
+            }
+        }
+    }
+}
+
+impl<T, N> SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Clone + Zero + AddAssign,
+{
+    /// Sum of all the counts in this counter.
+    pub fn total(&self) -> N {
+        let mut total = N::zero();
+        for (_, count) in self.iter() {
+            total += count.clone();
+        }
+        total
+    }
+}
+
+impl<T, N> SmallCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: Clone + Zero + Ord,
+{
+    /// Create a vector of `(elem, frequency)` pairs, sorted most to least common.
+    ///
+    /// While the counter is inline, keys with equal counts appear in the order they were first
+    /// seen; once promoted, the ordering of duplicates is unstable.
+    pub fn most_common(&self) -> Vec<(T, N)> {
+        let mut items = self
+            .iter()
+            .map(|(key, count)| (key.clone(), count.clone()))
+            .collect::<Vec<_>>();
+        items.sort_by(|(_, a_count), (_, b_count)| b_count.cmp(a_count));
+        items
+    }
+}
+
+/// An iterator over the `(&item, &count)` pairs of a [`SmallCounter`](struct.SmallCounter.html).
+pub enum SmallIter<'a, T: 'a, N: 'a> {
+    #[doc(hidden)]
+    Inline(slice::Iter<'a, (T, N)>),
+    #[doc(hidden)]
+    Map(hash_map::Iter<'a, T, N>),
+}
+
+impl<'a, T, N> Iterator for SmallIter<'a, T, N> {
+    type Item = (&'a T, &'a N);
+
+    fn next(&mut self) -> Option<(&'a T, &'a N)> {
+        match self {
+            SmallIter::Inline(entries) => entries.next().map(|(item, count)| (item, count)),
+            SmallIter::Map(entries) => entries.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            SmallIter::Inline(entries) => entries.size_hint(),
+            SmallIter::Map(entries) => entries.size_hint(),
+        }
+    }
+}
+
+impl<'a, T, N> IntoIterator for &'a SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero,
+{
+    type Item = (&'a T, &'a N);
+    type IntoIter = SmallIter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, N> PartialEq for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero + PartialEq,
+{
+    /// Two counters are equal when they hold the same counts, however they store them.
+    fn eq(&self, other: &SmallCounter<T, N>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, count)| other.get(key) == Some(count))
+    }
+}
+
+impl<T, N> Eq for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero + Eq,
+{
+}
+
+impl<T, N> Default for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero,
+{
+    fn default() -> Self {
+        SmallCounter::new()
+    }
+}
+
+impl<'a, T, N> Index<&'a T> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero,
+{
+    type Output = N;
+
+    /// Index in immutable contexts
+    ///
+    /// Returns a reference to a zero count if the key isn't present.
+    fn index(&self, key: &'a T) -> &N {
+        self.get_or_zero(key)
+    }
+}
+
+impl<T, N> AddAssign for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero + AddAssign,
+{
+    /// Add another counter to this counter
+    ///
+    /// `c += d;` -> `c[x] += d[x]` for all `x`
+    fn add_assign(&mut self, rhs: Self) {
+        for (key, value) in rhs.into_map() {
+            *self.entry(key) += value;
+        }
+    }
+}
+
+impl<T, N> Add for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Zero + AddAssign,
+{
+    type Output = SmallCounter<T, N>;
+
+    /// Add two counters together.
+    ///
+    /// `out = c + d;` -> `out[x] == c[x] + d[x]` for all `x`
+    fn add(mut self, rhs: SmallCounter<T, N>) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl<T, N> SubAssign for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + SubAssign + Zero,
+{
+    /// Subtract (keeping only positive values).
+    ///
+    /// `c -= d;` -> `c[x] -= d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    fn sub_assign(&mut self, rhs: Self) {
+        self.retain(|key, count| match rhs.get(key) {
+            Some(value) => {
+                if *count >= *value {
+                    *count -= value.clone();
+                    *count != N::zero()
+                } else {
+                    false
+                }
+            }
+            None => true,
+        });
+    }
+}
+
+impl<T, N> Sub for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + SubAssign + Zero,
+{
+    type Output = SmallCounter<T, N>;
+
+    /// Subtract (keeping only positive values).
+    ///
+    /// `out = c - d;` -> `out[x] == c[x] - d[x]` for all `x`,
+    /// keeping only items with a value greater than N::zero().
+    fn sub(mut self, rhs: SmallCounter<T, N>) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+impl<T, N> BitAnd for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: Clone + PartialOrd + Zero,
+{
+    type Output = SmallCounter<T, N>;
+
+    /// Intersection
+    ///
+    /// `out = c & d;` -> `out[x] == min(c[x], d[x])`
+    fn bitand(mut self, rhs: SmallCounter<T, N>) -> Self::Output {
+        self.retain(|key, count| match rhs.get(key) {
+            Some(other) => {
+                if *other < *count {
+                    *count = other.clone();
+                }
+                true
+            }
+            None => false,
+        });
+        self
+    }
+}
+
+impl<T, N> BitOr for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + Zero,
+{
+    type Output = SmallCounter<T, N>;
+
+    /// Union
+    ///
+    /// `out = c | d;` -> `out[x] == max(c[x], d[x])`
+    fn bitor(mut self, rhs: SmallCounter<T, N>) -> Self::Output {
+        for (key, value) in rhs.into_map() {
+            let count = self.entry(key);
+            if value > *count {
+                *count = value;
+            }
+        }
+        self
+    }
+}
+
+impl<I, T, N> AddAssign<I> for SmallCounter<T, N>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Directly add the counts of the elements of `I` to `self`
+    fn add_assign(&mut self, rhs: I) {
+        self.update(rhs);
+    }
+}
+
+impl<I, T, N> SubAssign<I> for SmallCounter<T, N>
+where
+    I: IntoIterator<Item = T>,
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Directly subtract the counts of the elements of `I` from `self`,
+    /// keeping only items with a value greater than N::zero().
+    fn sub_assign(&mut self, rhs: I) {
+        self.subtract(rhs);
+    }
+}
+
+impl<T, N> iter::FromIterator<T> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Produce a `SmallCounter` from an iterator of items. This is called automatically
+    /// by `iter.collect()`.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SmallCounter::init(iter)
+    }
+}
+
+impl<T, N> iter::FromIterator<(T, N)> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: AddAssign + Zero,
+{
+    /// `from_iter` creates a counter from `(item, count)` tuples.
+    ///
+    /// The counts of duplicate items are summed.
+    fn from_iter<I: IntoIterator<Item = (T, N)>>(iter: I) -> Self {
+        let mut cnt = SmallCounter::new();
+        cnt.extend(iter);
+        cnt
+    }
+}
+
+impl<T, N> Extend<T> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Extend a counter with an iterator of items, exactly like [`update`](#method.update).
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.update(iter);
+    }
+}
+
+impl<T, N> Extend<(T, N)> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: AddAssign + Zero,
+{
+    /// Extend a counter with `(item, count)` tuples.
+    ///
+    /// The counts of duplicate items are summed.
+    fn extend<I: IntoIterator<Item = (T, N)>>(&mut self, iter: I) {
+        for (item, item_count) in iter {
+            *self.entry(item) += item_count;
+        }
+    }
+}
+
+impl<T, N> From<SmallCounter<T, N>> for Counter<T, N>
+where
+    T: Hash + Eq,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    fn from(counter: SmallCounter<T, N>) -> Counter<T, N> {
+        counter.into_map().into_iter().collect()
+    }
+}
+
+impl<T, N> From<Counter<T, N>> for SmallCounter<T, N>
+where
+    T: Hash + Eq,
+    N: AddAssign + Zero,
+{
+    /// Convert a `Counter`, keeping its entries inline only if there are few enough of them.
+    fn from(counter: Counter<T, N>) -> SmallCounter<T, N> {
+        counter.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inline_counts() {
+        let counter = "abbccc".chars().collect::<SmallCounter<_>>();
+        assert_eq!(counter.len(), 3);
+        assert_eq!(counter[&'b'], 2);
+        assert_eq!(counter[&'z'], 0);
+        assert_eq!(counter.total(), 6);
+        assert!(!counter.is_promoted());
+    }
+
+    #[test]
+    fn test_promotion_keeps_counts() {
+        let mut counter: SmallCounter<u32> = SmallCounter::with_promote_at(4);
+        counter.update(0..4);
+        assert!(!counter.is_promoted());
+        counter.update(2..8);
+        assert!(counter.is_promoted());
+        assert_eq!(counter.len(), 8);
+        assert_eq!(
+            counter.into_map(),
+            hashmap!{
+                0 => 1, 1 => 1, 2 => 2, 3 => 2, 4 => 1, 5 => 1, 6 => 1, 7 => 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_never_promote() {
+        let counter: SmallCounter<u32> = {
+            let mut c = SmallCounter::with_promote_at(usize::MAX);
+            c.update(0..1000);
+            c
+        };
+        assert!(!counter.is_promoted());
+        assert_eq!(counter.len(), 1000);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let c = "abbccc".chars().collect::<SmallCounter<_>>();
+        let d = "bccddd".chars().collect::<SmallCounter<_>>();
+
+        let sum = c.clone() + d.clone();
+        assert_eq!(
+            sum.most_common(),
+            vec![('c', 5), ('b', 3), ('d', 3), ('a', 1)]
+        );
+        assert_eq!(
+            (c.clone() - d.clone()).into_map(),
+            hashmap!{'a' => 1, 'b' => 1, 'c' => 1}
+        );
+        assert_eq!((c.clone() & d.clone()).total(), 3);
+        assert_eq!((c | d).total(), 9);
+    }
+
+    #[test]
+    fn test_subtract_keeps_first_seen_order() {
+        let mut counter = "abc".chars().collect::<SmallCounter<_>>();
+        counter.subtract("a".chars());
+        assert_eq!(counter.most_common(), vec![('b', 1), ('c', 1)]);
+    }
+
+    #[test]
+    fn test_equality_across_representations() {
+        let inline = "abcabc".chars().collect::<SmallCounter<_>>();
+        let mut promoted = SmallCounter::with_promote_at(1);
+        promoted.update("cbacba".chars());
+        assert!(promoted.is_promoted());
+        assert_eq!(inline, promoted);
+        assert_eq!(
+            Counter::from(inline),
+            "aabbcc".chars().collect::<Counter<_>>()
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! A compact binary encoding for checkpointing counters.
+//!
+//! [`Counter::write_to`](../struct.Counter.html#method.write_to) writes the number of entries,
+//! then each key followed by its count. Integers are written as
+//! [LEB128](https://en.wikipedia.org/wiki/LEB128) varints, with signed integers zigzag-encoded
+//! first, so small counts take a single byte. Strings and vectors are prefixed with their
+//! length. Everything needed to read the snapshot back is in the stream itself; no external
+//! schema or dependency is required.
+//!
+//! Keys and counts of your own types can be stored by implementing
+//! [`Snapshot`](trait.Snapshot.html), usually in terms of [`write_varint`](fn.write_varint.html)
+//! and [`read_varint`](fn.read_varint.html) or the implementations for the standard types.
+//!
+//! Reading performs many small reads, so wrap files in a `BufReader` (and a `BufWriter` for
+//! writing).
+
+use num_traits::Zero;
+
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Read, Write};
+use std::ops::AddAssign;
+
+use super::Counter;
+
+/// A type which can be written to and read from a counter snapshot.
+pub trait Snapshot: Sized {
+    /// Write `self` to `writer`.
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// Read a value written by [`write_snapshot`](#tymethod.write_snapshot) from `reader`.
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+/// Write `value` as an unsigned LEB128 varint.
+pub fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+    let mut buf = [0u8; 10];
+    let mut len = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buf[..len])
+}
+
+/// Read an unsigned LEB128 varint written by [`write_varint`](fn.write_varint.html).
+pub fn read_varint<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        reader.read_exact(&mut byte)?;
+        let low = u64::from(byte[0] & 0x7f);
+        if shift == 63 && low > 1 || shift > 63 {
+            return Err(invalid_data("varint overflows 64 bits"));
+        }
+        value |= low << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Read a length prefix, refusing lengths which don't fit in memory.
+fn read_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let len = read_varint(reader)?;
+    if len > usize::MAX as u64 {
+        return Err(invalid_data("length overflows usize"));
+    }
+    Ok(len as usize)
+}
+
+// Don't trust a length prefix further than this when preallocating.
+const MAX_PREALLOCATE: usize = 4096;
+
+macro_rules! snapshot_unsigned {
+    ($($t:ty),*) => {$(
+        impl Snapshot for $t {
+            fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                write_varint(writer, *self as u64)
+            }
+
+            fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<$t> {
+                let value = read_varint(reader)?;
+                if value > <$t>::MAX as u64 {
+                    return Err(invalid_data(concat!("value overflows ", stringify!($t))));
+                }
+                Ok(value as $t)
+            }
+        }
+    )*};
+}
+
+macro_rules! snapshot_signed {
+    ($($t:ty),*) => {$(
+        impl Snapshot for $t {
+            fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                let value = *self as i64;
+                write_varint(writer, ((value << 1) ^ (value >> 63)) as u64)
+            }
+
+            fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<$t> {
+                let zigzag = read_varint(reader)?;
+                let value = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+                if value < <$t>::MIN as i64 || value > <$t>::MAX as i64 {
+                    return Err(invalid_data(concat!("value overflows ", stringify!($t))));
+                }
+                Ok(value as $t)
+            }
+        }
+    )*};
+}
+
+snapshot_unsigned!(u8, u16, u32, u64, usize);
+snapshot_signed!(i8, i16, i32, i64, isize);
+
+impl Snapshot for f32 {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<f32> {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(f32::from_le_bytes(bytes))
+    }
+}
+
+impl Snapshot for f64 {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<f64> {
+        let mut bytes = [0u8; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(f64::from_le_bytes(bytes))
+    }
+}
+
+impl Snapshot for bool {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[*self as u8])
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<bool> {
+        match u8::read_snapshot(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid bool")),
+        }
+    }
+}
+
+impl Snapshot for char {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_varint(writer, u64::from(*self as u32))
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<char> {
+        let value = u32::read_snapshot(reader)?;
+        ::std::char::from_u32(value).ok_or_else(|| invalid_data("invalid char"))
+    }
+}
+
+impl Snapshot for String {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_varint(writer, self.len() as u64)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<String> {
+        let len = read_len(reader)?;
+        let mut bytes = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+        reader.take(len as u64).read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(bytes).map_err(|_| invalid_data("invalid UTF-8"))
+    }
+}
+
+impl<T: Snapshot> Snapshot for Vec<T> {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_varint(writer, self.len() as u64)?;
+        for item in self {
+            item.write_snapshot(writer)?;
+        }
+        Ok(())
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<Vec<T>> {
+        let len = read_len(reader)?;
+        let mut items = Vec::with_capacity(len.min(MAX_PREALLOCATE));
+        for _ in 0..len {
+            items.push(T::read_snapshot(reader)?);
+        }
+        Ok(items)
+    }
+}
+
+impl<A: Snapshot, B: Snapshot> Snapshot for (A, B) {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.write_snapshot(writer)?;
+        self.1.write_snapshot(writer)
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<(A, B)> {
+        let a = A::read_snapshot(reader)?;
+        let b = B::read_snapshot(reader)?;
+        Ok((a, b))
+    }
+}
+
+impl<A: Snapshot, B: Snapshot, C: Snapshot> Snapshot for (A, B, C) {
+    fn write_snapshot<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.write_snapshot(writer)?;
+        self.1.write_snapshot(writer)?;
+        self.2.write_snapshot(writer)
+    }
+
+    fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<(A, B, C)> {
+        let a = A::read_snapshot(reader)?;
+        let b = B::read_snapshot(reader)?;
+        let c = C::read_snapshot(reader)?;
+        Ok((a, b, c))
+    }
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Snapshot + Hash + Eq,
+    N: Snapshot,
+    S: BuildHasher,
+{
+    /// Write a binary snapshot of this counter, which [`read_from`](#method.read_from) can load.
+    ///
+    /// See the [`snapshot`](snapshot/index.html) module for the encoding.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let mut snapshot = Vec::new();
+    /// counter.write_to(&mut snapshot).unwrap();
+    /// // one byte for the length, then one for each char and count
+    /// assert_eq!(snapshot.len(), 7);
+    ///
+    /// let loaded: Counter<char> = Counter::read_from(&snapshot[..]).unwrap();
+    /// assert_eq!(loaded, counter);
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_varint(&mut writer, self.map.len() as u64)?;
+        for (key, count) in self.map.iter() {
+            key.write_snapshot(&mut writer)?;
+            count.write_snapshot(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Load a counter from a snapshot written by [`write_to`](#method.write_to).
+    ///
+    /// Repeated keys are combined as by [`update_with_counts`](#method.update_with_counts).
+    /// Malformed snapshots produce an error of kind `InvalidData`, and truncated ones an error
+    /// of kind `UnexpectedEof`.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Counter<T, N, S>>
+    where
+        N: Zero + AddAssign,
+        S: Default,
+    {
+        let len = read_len(&mut reader)?;
+        let mut counter = Counter::default();
+        counter.map.reserve(len.min(MAX_PREALLOCATE));
+        for _ in 0..len {
+            let key = T::read_snapshot(&mut reader)?;
+            let count = N::read_snapshot(&mut reader)?;
+            counter.add_count_pruned(key, count);
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Snapshot>(value: &T) -> T {
+        let mut bytes = Vec::new();
+        value.write_snapshot(&mut bytes).unwrap();
+        let mut reader = &bytes[..];
+        let back = T::read_snapshot(&mut reader).unwrap();
+        assert!(reader.is_empty());
+        back
+    }
+
+    #[test]
+    fn test_varint() {
+        for &value in &[0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            assert_eq!(round_trip(&value), value);
+        }
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, 300).unwrap();
+        assert_eq!(bytes, vec![0xac, 0x02]);
+
+        let too_long = [0xffu8; 11];
+        let err = read_varint(&mut &too_long[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_signed_and_other_types() {
+        for &value in &[0i64, -1, 1, -64, 64, i64::MIN, i64::MAX] {
+            assert_eq!(round_trip(&value), value);
+        }
+        assert_eq!(round_trip(&-3i8), -3);
+        assert_eq!(round_trip(&'é'), 'é');
+        assert_eq!(round_trip(&"héllo".to_string()), "héllo");
+        assert_eq!(
+            round_trip(&(7u32, vec![true, false])),
+            (7, vec![true, false])
+        );
+        assert_eq!(round_trip(&0.25f64), 0.25);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let mut bytes = Vec::new();
+        300u32.write_snapshot(&mut bytes).unwrap();
+        let err = u8::read_snapshot(&mut &bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_counter_round_trip() {
+        let counter = "the cat and the hat"
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Counter<_, i64>>();
+        let mut bytes = Vec::new();
+        counter.write_to(&mut bytes).unwrap();
+        assert_eq!(Counter::read_from(&bytes[..]).unwrap(), counter);
+
+        let err = Counter::<String, i64>::read_from(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_drops_zero_counts() {
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, 3).unwrap();
+        for &(key, count) in &[('a', 1i64), ('b', 0), ('a', -1)] {
+            key.write_snapshot(&mut bytes).unwrap();
+            count.write_snapshot(&mut bytes).unwrap();
+        }
+        let counter = Counter::<char, i64>::read_from(&bytes[..]).unwrap();
+        assert!(counter.is_empty());
+    }
+}
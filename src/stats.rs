@@ -0,0 +1,496 @@
+//! Statistics over the distribution of counts.
+//!
+//! Counters are treated as empirical distributions: each item's probability is its count
+//! divided by the total. Counts are converted to `f64` with `ToPrimitive`.
+
+use num_traits::ToPrimitive;
+
+use std::hash::{BuildHasher, Hash};
+
+use super::{to_f64, Counter};
+
+/// The unit in which an information quantity, such as entropy, is measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// Logarithms to base 2.
+    Bits,
+    /// Natural logarithms.
+    Nats,
+}
+
+impl Unit {
+    // Convert a quantity measured in nats to this unit.
+    fn convert_nats(self, nats: f64) -> f64 {
+        match self {
+            Unit::Bits => nats / ::std::f64::consts::LN_2,
+            Unit::Nats => nats,
+        }
+    }
+}
+
+/// How to treat items which are missing from one of two compared counters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Smoothing {
+    /// Use the raw counts. An item missing from the reference distribution has probability
+    /// zero there, which makes divergences infinite.
+    None,
+    /// Add the given pseudo-count to every item seen in either counter (Laplace smoothing
+    /// for a pseudo-count of one).
+    Additive(f64),
+}
+
+/// The result of a goodness-of-fit test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GoodnessOfFit {
+    /// The test statistic.
+    pub statistic: f64,
+    /// The degrees of freedom: one less than the number of items in either counter.
+    pub degrees_of_freedom: usize,
+}
+
+impl<T, N, S> Counter<T, N, S>
+where
+    T: Hash + Eq,
+    N: ToPrimitive,
+    S: BuildHasher,
+{
+    /// The Shannon entropy of the distribution of counts.
+    ///
+    /// An empty counter has an entropy of zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// use counter::stats::Unit;
+    ///
+    /// let counter = "aabbccdd".chars().collect::<Counter<_>>();
+    /// assert_eq!(counter.entropy(Unit::Bits), 2.0);
+    /// assert_eq!("aaaa".chars().collect::<Counter<_>>().entropy(Unit::Nats), 0.0);
+    /// ```
+    pub fn entropy(&self, unit: Unit) -> f64 {
+        let total = self.map.values().map(to_f64).sum::<f64>();
+        if total == 0.0 {
+            return 0.0;
+        }
+        let nats = self
+            .map
+            .values()
+            .map(|count| to_f64(count) / total)
+            .filter(|&p| p > 0.0)
+            .map(|p| -p * p.ln())
+            .sum::<f64>();
+        unit.convert_nats(nats)
+    }
+
+    /// The cross-entropy of `other` relative to this distribution.
+    ///
+    /// This is the expected information needed to encode items drawn from `self` with a
+    /// code optimized for `other`. It is infinite if `other` lacks an item of `self` and no
+    /// smoothing is applied.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// use counter::stats::{Smoothing, Unit};
+    ///
+    /// let p = "ab".chars().collect::<Counter<_>>();
+    /// let q = "abbb".chars().collect::<Counter<_>>();
+    /// let bits = p.cross_entropy(&q, Smoothing::None, Unit::Bits);
+    /// assert_eq!(bits, 0.5 * 2.0 + 0.5 * (4.0f64 / 3.0).log2());
+    ///
+    /// let r = "a".chars().collect::<Counter<_>>();
+    /// assert!(p.cross_entropy(&r, Smoothing::None, Unit::Bits).is_infinite());
+    /// ```
+    pub fn cross_entropy(&self, other: &Counter<T, N, S>, smoothing: Smoothing, unit: Unit) -> f64 {
+        let nats = self
+            .probabilities(other, smoothing)
+            .into_iter()
+            .filter(|&(p, _)| p > 0.0)
+            .map(|(p, q)| -p * q.ln())
+            .sum::<f64>();
+        unit.convert_nats(nats)
+    }
+
+    /// The Kullback-Leibler divergence of `other` from this distribution.
+    ///
+    /// This is zero when the two distributions are equal, and infinite if `other` lacks an
+    /// item of `self` and no smoothing is applied. With
+    /// [`Smoothing::Additive`](enum.Smoothing.html#variant.Additive), it is always finite.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// use counter::stats::{Smoothing, Unit};
+    ///
+    /// let p = "aabb".chars().collect::<Counter<_>>();
+    /// let q = "abbb".chars().collect::<Counter<_>>();
+    /// assert_eq!(p.kl_divergence(&p, Smoothing::None, Unit::Bits), 0.0);
+    /// assert!(p.kl_divergence(&q, Smoothing::None, Unit::Bits) > 0.0);
+    ///
+    /// let r = "ac".chars().collect::<Counter<_>>();
+    /// assert!(p.kl_divergence(&r, Smoothing::None, Unit::Nats).is_infinite());
+    /// assert!(p.kl_divergence(&r, Smoothing::Additive(1.0), Unit::Nats).is_finite());
+    /// ```
+    pub fn kl_divergence(&self, other: &Counter<T, N, S>, smoothing: Smoothing, unit: Unit) -> f64 {
+        let nats = self
+            .probabilities(other, smoothing)
+            .into_iter()
+            .filter(|&(p, _)| p > 0.0)
+            .map(|(p, q)| p * (p.ln() - q.ln()))
+            .sum::<f64>();
+        unit.convert_nats(nats)
+    }
+
+    /// The Jaccard similarity of the sets of items of two counters, ignoring their counts.
+    ///
+    /// This is the number of items in both counters divided by the number of items in
+    /// either; it is zero if both counters are empty.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let a = "aab".chars().collect::<Counter<_>>();
+    /// let b = "bc".chars().collect::<Counter<_>>();
+    /// assert_eq!(a.jaccard(&b), 1.0 / 3.0);
+    /// ```
+    pub fn jaccard(&self, other: &Counter<T, N, S>) -> f64 {
+        let both = self
+            .map
+            .keys()
+            .filter(|key| other.map.contains_key(key))
+            .count();
+        let either = self.map.len() + other.map.len() - both;
+        if either == 0 {
+            return 0.0;
+        }
+        both as f64 / either as f64
+    }
+
+    /// The weighted Jaccard similarity of two counters, treated as multisets.
+    ///
+    /// This is the size of the intersection `self & other` divided by the size of the union
+    /// `self | other`, summing the minimum and maximum count of each item; it is zero if
+    /// both counters are empty.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let a = "aab".chars().collect::<Counter<_>>();
+    /// let b = "abc".chars().collect::<Counter<_>>();
+    /// assert_eq!(a.weighted_jaccard(&b), 2.0 / 4.0);
+    /// ```
+    pub fn weighted_jaccard(&self, other: &Counter<T, N, S>) -> f64 {
+        let mut intersection = 0.0;
+        let mut union = 0.0;
+        for (key, count) in self.map.iter() {
+            let count = to_f64(count);
+            let other_count = other.map.get(key).map_or(0.0, to_f64);
+            intersection += count.min(other_count);
+            union += count.max(other_count);
+        }
+        for (key, count) in other.map.iter() {
+            if !self.map.contains_key(key) {
+                union += to_f64(count);
+            }
+        }
+        if union == 0.0 {
+            return 0.0;
+        }
+        intersection / union
+    }
+
+    /// The cosine similarity of two counters, treated as sparse vectors of counts.
+    ///
+    /// If either counter is a zero vector, such as an empty counter, the similarity is zero.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let a = "ab".chars().collect::<Counter<_>>();
+    /// let b = "aabb".chars().collect::<Counter<_>>();
+    /// let c = "cd".chars().collect::<Counter<_>>();
+    /// assert!((a.cosine_similarity(&b) - 1.0).abs() < 1e-12);
+    /// assert_eq!(a.cosine_similarity(&c), 0.0);
+    /// ```
+    pub fn cosine_similarity(&self, other: &Counter<T, N, S>) -> f64 {
+        let (small, large) = if self.map.len() <= other.map.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let dot = small
+            .map
+            .iter()
+            .filter_map(|(key, count)| {
+                large
+                    .map
+                    .get(key)
+                    .map(|other| to_f64(count) * to_f64(other))
+            })
+            .sum::<f64>();
+        let norm = |counter: &Counter<T, N, S>| {
+            counter
+                .map
+                .values()
+                .map(|count| to_f64(count).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let norms = norm(self) * norm(other);
+        if norms == 0.0 {
+            return 0.0;
+        }
+        dot / norms
+    }
+
+    /// Pearson's chi-squared test of these observed counts against `expected` frequencies.
+    ///
+    /// The expected frequencies are scaled to the observed total, so they may be counts or
+    /// probabilities. An observed item with an expected frequency of zero makes the statistic
+    /// infinite, while an item both observed and expected zero times is left out altogether.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let observed = "aaaabbbbbb".chars().collect::<Counter<_>>();
+    /// let expected = [('a', 0.5), ('b', 0.5)].iter().cloned().collect::<Counter<_, f64>>();
+    /// let fit = observed.chi_squared(&expected);
+    /// assert_eq!(fit.statistic, 0.4);
+    /// assert_eq!(fit.degrees_of_freedom, 1);
+    /// ```
+    pub fn chi_squared<M, R>(&self, expected: &Counter<T, M, R>) -> GoodnessOfFit
+    where
+        M: ToPrimitive,
+        R: BuildHasher,
+    {
+        self.goodness_of_fit(expected, |observed, expected| {
+            (observed - expected).powi(2) / expected
+        })
+    }
+
+    /// The G-test (log-likelihood ratio test) of these observed counts against `expected`
+    /// frequencies.
+    ///
+    /// The expected frequencies are scaled as in [`chi_squared`](#method.chi_squared); the
+    /// statistic is in nats, so that it approximately follows a chi-squared distribution.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let observed = "aabb".chars().collect::<Counter<_>>();
+    /// let expected = "ab".chars().collect::<Counter<_>>();
+    /// let fit = observed.g_test(&expected);
+    /// assert_eq!(fit.statistic, 0.0);
+    /// assert_eq!(fit.degrees_of_freedom, 1);
+    /// ```
+    pub fn g_test<M, R>(&self, expected: &Counter<T, M, R>) -> GoodnessOfFit
+    where
+        M: ToPrimitive,
+        R: BuildHasher,
+    {
+        let mut fit = self.goodness_of_fit(expected, |observed, expected| {
+            if observed > 0.0 {
+                observed * (observed / expected).ln()
+            } else {
+                0.0
+            }
+        });
+        fit.statistic *= 2.0;
+        fit
+    }
+
+    // Sum `term(observed, expected)` over the items of either counter, with the expected
+    // frequencies scaled to the observed total. Items which are zero in both are skipped, as
+    // they would make the terms `0 / 0` and carry no degree of freedom.
+    fn goodness_of_fit<M, R, F>(&self, expected: &Counter<T, M, R>, term: F) -> GoodnessOfFit
+    where
+        M: ToPrimitive,
+        R: BuildHasher,
+        F: Fn(f64, f64) -> f64,
+    {
+        let observed_total = self.map.values().map(to_f64).sum::<f64>();
+        let expected_total = expected.map.values().map(to_f64).sum::<f64>();
+        let scale = if expected_total == 0.0 {
+            0.0
+        } else {
+            observed_total / expected_total
+        };
+        let mut statistic = 0.0;
+        let mut categories = 0usize;
+        let mut add = |observed: f64, expected: f64| {
+            if observed != 0.0 || expected != 0.0 {
+                statistic += term(observed, expected);
+                categories += 1;
+            }
+        };
+        for (key, count) in self.map.iter() {
+            add(
+                to_f64(count),
+                expected.map.get(key).map_or(0.0, to_f64) * scale,
+            );
+        }
+        for (key, count) in expected.map.iter() {
+            if !self.map.contains_key(key) {
+                add(0.0, to_f64(count) * scale);
+            }
+        }
+        GoodnessOfFit {
+            statistic,
+            degrees_of_freedom: categories.saturating_sub(1),
+        }
+    }
+
+    // The probabilities `(p, q)` of each item of either counter, in `self` and in `other`.
+    //
+    // This is empty if `self` is empty; if only `other` is empty, every `q` is zero.
+    fn probabilities(&self, other: &Counter<T, N, S>, smoothing: Smoothing) -> Vec<(f64, f64)> {
+        let alpha = match smoothing {
+            Smoothing::None => 0.0,
+            Smoothing::Additive(alpha) => alpha,
+        };
+        let keys = self
+            .map
+            .keys()
+            .chain(other.map.keys().filter(|key| !self.map.contains_key(key)))
+            .collect::<Vec<_>>();
+        let smoothed =
+            |counter: &Counter<T, N, S>, key: &T| counter.map.get(key).map_or(0.0, to_f64) + alpha;
+        let total_p = keys.iter().map(|key| smoothed(self, key)).sum::<f64>();
+        let total_q = keys.iter().map(|key| smoothed(other, key)).sum::<f64>();
+        if total_p == 0.0 {
+            return Vec::new();
+        }
+        keys.into_iter()
+            .map(|key| {
+                let q = if total_q == 0.0 {
+                    0.0
+                } else {
+                    smoothed(other, key) / total_q
+                };
+                (smoothed(self, key) / total_p, q)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_entropy() {
+        let counter = Counter::<_>::init("aab".chars());
+        let expected = -(2.0 / 3.0 * (2.0f64 / 3.0).ln() + 1.0 / 3.0 * (1.0f64 / 3.0).ln());
+        assert!(close(counter.entropy(Unit::Nats), expected));
+        assert!(close(
+            counter.entropy(Unit::Bits),
+            expected / ::std::f64::consts::LN_2
+        ));
+        assert_eq!(Counter::<char>::new().entropy(Unit::Bits), 0.0);
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let a = Counter::<_>::init("aaabbc".chars());
+        let b = Counter::<_>::init("abbbbd".chars());
+        assert_eq!(a.jaccard(&b), 0.5);
+        assert_eq!(a.jaccard(&a), 1.0);
+        // intersection {a: 1, b: 2}, union {a: 3, b: 4, c: 1, d: 1}
+        assert_eq!(a.weighted_jaccard(&b), 3.0 / 9.0);
+        assert_eq!(a.weighted_jaccard(&a), 1.0);
+
+        let empty = Counter::<char>::new();
+        assert_eq!(empty.jaccard(&empty), 0.0);
+        assert_eq!(empty.weighted_jaccard(&empty), 0.0);
+        assert_eq!(a.weighted_jaccard(&empty), 0.0);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        let a = Counter::<_>::init("aab".chars());
+        let b = Counter::<_>::init("abbc".chars());
+        // a = (2, 1, 0), b = (1, 2, 1)
+        let expected = 4.0 / (5.0f64.sqrt() * 6.0f64.sqrt());
+        assert!(close(a.cosine_similarity(&b), expected));
+        assert!(close(b.cosine_similarity(&a), expected));
+        assert!(close(a.cosine_similarity(&a), 1.0));
+
+        let empty = Counter::<char>::new();
+        assert_eq!(a.cosine_similarity(&empty), 0.0);
+        assert_eq!(empty.cosine_similarity(&empty), 0.0);
+    }
+
+    #[test]
+    fn test_goodness_of_fit() {
+        let observed = Counter::<_>::init("aaaaabbbcc".chars());
+        // scaled to the observed total: {a: 4, b: 4, c: 2}
+        let expected = Counter::<_>::init("aabbc".chars());
+        let chi = observed.chi_squared(&expected);
+        assert!(close(chi.statistic, 0.25 + 0.25 + 0.0));
+        assert_eq!(chi.degrees_of_freedom, 2);
+
+        let g = observed.g_test(&expected);
+        let expected_g = 2.0 * (5.0 * 1.25f64.ln() + 3.0 * 0.75f64.ln());
+        assert!(close(g.statistic, expected_g));
+        assert_eq!(g.degrees_of_freedom, 2);
+
+        let unexpected = Counter::<_>::init("aab".chars());
+        assert!(unexpected
+            .chi_squared(&Counter::<_>::init("a".chars()))
+            .statistic
+            .is_infinite());
+        assert!(unexpected
+            .g_test(&Counter::<_>::init("a".chars()))
+            .statistic
+            .is_infinite());
+
+        // an item which is expected but never observed adds its expected count
+        let missing =
+            Counter::<_>::init("aa".chars()).chi_squared(&Counter::<_>::init("ab".chars()));
+        assert!(close(missing.statistic, 1.0 + 1.0));
+    }
+
+    #[test]
+    fn test_goodness_of_fit_skips_zero_against_zero() {
+        let mut observed = Counter::<_>::init("aabb".chars());
+        observed[&'z'] = 0;
+        let mut expected = Counter::<_>::init("ab".chars());
+        expected[&'y'] = 0;
+        for fit in &[observed.chi_squared(&expected), observed.g_test(&expected)] {
+            assert_eq!(fit.statistic, 0.0);
+            assert_eq!(fit.degrees_of_freedom, 1);
+        }
+
+        // with nothing observed, every expected frequency scales to zero
+        let empty = Counter::<char>::new();
+        for fit in &[empty.chi_squared(&expected), empty.g_test(&expected)] {
+            assert_eq!(fit.statistic, 0.0);
+            assert_eq!(fit.degrees_of_freedom, 0);
+        }
+    }
+
+    #[test]
+    fn test_kl_divergence_and_cross_entropy() {
+        let p = Counter::<_>::init("aab".chars());
+        let q = Counter::<_>::init("abb".chars());
+        let kl = 2.0 / 3.0 * 2.0f64.ln() + 1.0 / 3.0 * 0.5f64.ln();
+        assert!(close(p.kl_divergence(&q, Smoothing::None, Unit::Nats), kl));
+        // cross-entropy decomposes into entropy plus divergence
+        assert!(close(
+            p.cross_entropy(&q, Smoothing::None, Unit::Bits),
+            p.entropy(Unit::Bits) + p.kl_divergence(&q, Smoothing::None, Unit::Bits)
+        ));
+
+        // with one pseudo-count, p is {a: 3, b: 2, c: 1} and r is {a: 2, b: 1, c: 2}
+        let r = Counter::<_>::init("ac".chars());
+        let smoothed = 0.5 * (0.5f64 / 0.4).ln()
+            + 1.0 / 3.0 * (1.0 / 3.0 / 0.2f64).ln()
+            + 1.0 / 6.0 * (1.0 / 6.0 / 0.4f64).ln();
+        assert!(close(
+            p.kl_divergence(&r, Smoothing::Additive(1.0), Unit::Nats),
+            smoothed
+        ));
+
+        let empty = Counter::<char>::new();
+        assert_eq!(empty.kl_divergence(&p, Smoothing::None, Unit::Nats), 0.0);
+        assert!(p
+            .cross_entropy(&empty, Smoothing::None, Unit::Nats)
+            .is_infinite());
+    }
+}
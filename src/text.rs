@@ -0,0 +1,304 @@
+//! The textual report format of `Counter`.
+
+use num_traits::Zero;
+
+use compat::error::Error;
+use compat::fmt;
+use compat::hash::{BuildHasher, Hash};
+use compat::ops::AddAssign;
+use compat::str::FromStr;
+use compat::string::ToString;
+use compat::vec::Vec;
+
+use super::{cmp_counts_descending, Counter};
+
+impl<T, N, S> fmt::Display for Counter<T, N, S>
+where
+    T: fmt::Display + Hash + Eq,
+    N: fmt::Display + PartialOrd,
+    S: BuildHasher,
+{
+    /// Format the counter as a table of keys and counts, most common first.
+    ///
+    /// Each line holds a key, padded to the width of the longest key, then its count, aligned
+    /// to the right. Keys with equal counts are ordered by their formatted text, so the output
+    /// is deterministic, and counts which can't be ordered, such as NaN, come last. The
+    /// precision, if given, limits the table to that many rows.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "the cat and the hat and the bat".split_whitespace().collect::<Counter<_>>();
+    /// assert_eq!(counter.to_string(), "\
+    /// the  3
+    /// and  2
+    /// bat  1
+    /// cat  1
+    /// hat  1");
+    /// assert_eq!(format!("{:.2}", counter), "the  3\nand  2");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rows = self
+            .map
+            .iter()
+            .map(|(key, count)| (key.to_string(), count.to_string(), count))
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| cmp_counts_descending(a.2, b.2).then_with(|| a.0.cmp(&b.0)));
+        if let Some(limit) = f.precision() {
+            rows.truncate(limit);
+        }
+
+        let key_width = rows
+            .iter()
+            .map(|row| row.0.chars().count())
+            .max()
+            .unwrap_or(0);
+        let count_width = rows
+            .iter()
+            .map(|row| row.1.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (index, (key, count, _)) in rows.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(
+                f,
+                "{:<key_width$}  {:>count_width$}",
+                key,
+                count,
+                key_width = key_width,
+                count_width = count_width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, N, S> fmt::Debug for Counter<T, N, S>
+where
+    T: fmt::Debug + Hash + Eq,
+    N: fmt::Debug + PartialOrd,
+    S: BuildHasher,
+{
+    /// Format the counter as a map, most common first.
+    ///
+    /// Keys with equal counts are ordered by their `Debug` text, so the output is the same
+    /// from run to run, and failing assertions produce readable diffs. Counts which can't be
+    /// ordered, such as NaN, come last.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// assert_eq!(format!("{:?}", counter), "{'c': 3, 'b': 2, 'a': 1}");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries = self
+            .map
+            .iter()
+            .map(|(key, count)| (format!("{:?}", key), key, count))
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| cmp_counts_descending(a.2, b.2).then_with(|| a.0.cmp(&b.0)));
+        f.debug_map()
+            .entries(entries.into_iter().map(|(_, key, count)| (key, count)))
+            .finish()
+    }
+}
+
+/// The error returned when parsing a counter from its textual report format fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseCounterError {
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    MissingCount,
+    InvalidKey,
+    InvalidCount,
+}
+
+impl ParseCounterError {
+    /// The line, counting from 1, on which parsing failed.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseCounterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problem = match self.kind {
+            ParseErrorKind::MissingCount => "missing count",
+            ParseErrorKind::InvalidKey => "invalid key",
+            ParseErrorKind::InvalidCount => "invalid count",
+        };
+        write!(f, "line {}: {}", self.line, problem)
+    }
+}
+
+impl Error for ParseCounterError {}
+
+impl<T, N, S> FromStr for Counter<T, N, S>
+where
+    T: FromStr + Hash + Eq,
+    N: FromStr + Zero + AddAssign,
+    S: BuildHasher + Default,
+{
+    type Err = ParseCounterError;
+
+    /// Parse a counter from the table written by its `Display` implementation.
+    ///
+    /// Each non-blank line holds a key followed by whitespace and a count; the key is everything
+    /// before the last run of whitespace. Repeated keys are combined as by
+    /// [`update_with_counts`](#method.update_with_counts). Keys whose text ends in whitespace
+    /// or spans several lines can't be read back.
+    ///
+    /// ```rust
+    /// # use counter::Counter;
+    /// let counter = "abbccc".chars().collect::<Counter<_>>();
+    /// let parsed: Counter<char> = counter.to_string().parse().unwrap();
+    /// assert_eq!(parsed, counter);
+    ///
+    /// let words: Counter<String> = "big apple  3\npear       1".parse().unwrap();
+    /// assert_eq!(words[&"big apple".to_string()], 3);
+    ///
+    /// let err = "a  1\nb  many".parse::<Counter<char>>().unwrap_err();
+    /// assert_eq!(err.to_string(), "line 2: invalid count");
+    /// ```
+    fn from_str(s: &str) -> Result<Counter<T, N, S>, ParseCounterError> {
+        let mut counter = Counter::default();
+        for (index, line) in s.lines().enumerate() {
+            let error = |kind| ParseCounterError {
+                line: index + 1,
+                kind,
+            };
+
+            let line = line.trim_end();
+            if line.trim_start().is_empty() {
+                continue;
+            }
+            let split = line
+                .rfind(char::is_whitespace)
+                .ok_or_else(|| error(ParseErrorKind::MissingCount))?;
+            let (key, count) = line.split_at(split);
+
+            let key = key
+                .trim_end()
+                .parse::<T>()
+                .map_err(|_| error(ParseErrorKind::InvalidKey))?;
+            let count = count
+                .trim_start()
+                .parse::<N>()
+                .map_err(|_| error(ParseErrorKind::InvalidCount))?;
+            counter.add_count_pruned(key, count);
+        }
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use compat::string::{String, ToString};
+    use compat::vec::Vec;
+    use Counter;
+
+    #[test]
+    fn test_display_alignment() {
+        let mut counter = "a bb a".split_whitespace().collect::<Counter<_>>();
+        counter[&"ccc"] = 12;
+        assert_eq!(counter.to_string(), "ccc  12\na     2\nbb    1");
+    }
+
+    #[test]
+    fn test_display_nan_counts_last() {
+        let mut counter = Counter::<u32, f64>::new();
+        for key in 0..60 {
+            let count = if key % 3 == 0 { f64::NAN } else { 1.5 };
+            counter.map.insert(key, count);
+        }
+        let display = counter.to_string();
+        let lines = display.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 60);
+        assert!(lines[..40].iter().all(|line| line.ends_with("1.5")));
+        assert!(lines[40..].iter().all(|line| line.ends_with("NaN")));
+    }
+
+    #[test]
+    fn test_display_limit_and_empty() {
+        let counter = "abbccc".chars().collect::<Counter<_>>();
+        assert_eq!(format!("{:.1}", counter), "c  3");
+        assert_eq!(format!("{:.0}", counter), "");
+        assert_eq!(Counter::<char>::new().to_string(), "");
+    }
+
+    #[test]
+    fn test_debug_order() {
+        let counter = "a bb a ccc bb dd"
+            .split_whitespace()
+            .collect::<Counter<_>>();
+        assert_eq!(
+            format!("{:?}", counter),
+            r#"{"a": 2, "bb": 2, "ccc": 1, "dd": 1}"#
+        );
+        assert_eq!(format!("{:?}", Counter::<char>::new()), "{}");
+    }
+
+    #[test]
+    fn test_debug_nan_counts_last() {
+        let mut counter = Counter::<u32, f64>::new();
+        for key in 0..60 {
+            let count = if key % 3 == 0 { f64::NAN } else { f64::from(key) };
+            counter.map.insert(key, count);
+        }
+        let debug = format!("{:?}", counter);
+        let first_nan = debug.find("NaN").unwrap();
+        assert!(debug.starts_with("{59: 59.0, 58: 58.0, "));
+        assert!(!debug[first_nan..].contains(".0"));
+
+        let mut counter = Counter::<char, f64>::new();
+        counter.map.insert('a', f64::NAN);
+        counter.map.insert('b', 1.0);
+        assert_eq!(format!("{:?}", counter), "{'b': 1.0, 'a': NaN}");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let counter = "the cat and the hat and the bat"
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Counter<_, i32>>();
+        assert_eq!(
+            counter.to_string().parse::<Counter<String, i32>>(),
+            Ok(counter)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use super::ParseCounterError;
+
+        let missing = "a 1\n\nlonely\n".parse::<Counter<char>>().unwrap_err();
+        assert_eq!(missing.line(), 3);
+        assert_eq!(missing.to_string(), "line 3: missing count");
+
+        let bad_key: Result<Counter<u8>, ParseCounterError> = "300  1".parse();
+        assert_eq!(bad_key.unwrap_err().to_string(), "line 1: invalid key");
+    }
+
+    #[test]
+    fn test_parse_sums_and_skips_blank_lines() {
+        let counter: Counter<char> = "a 1\n  \n\na 2\nb\t4\n".parse().unwrap();
+        assert_eq!(counter.into_map(), hashmap!{'a' => 3, 'b' => 4});
+    }
+
+    #[test]
+    fn test_parse_drops_zero_counts() {
+        let zero: Counter<char> = "a 0
+b 1".parse().unwrap();
+        assert_eq!(zero.into_map(), hashmap!{'b' => 1});
+        let cancelled: Counter<char, i32> = "a 1
+a -1".parse().unwrap();
+        assert!(cancelled.is_empty());
+        assert!(cancelled.most_common().is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Counters of only the most recent items of a stream.
+
+use num_traits::{One, Zero};
+
+use compat::collections::VecDeque;
+use compat::hash::Hash;
+use compat::iter;
+use compat::ops::{AddAssign, Deref, SubAssign};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+use super::Counter;
+
+/// A counter of the last `len` items pushed into it.
+///
+/// Every item is kept in a queue as well as counted; once the queue is full, pushing an item
+/// drops the oldest one and takes it off the count. The counts can be read through `Deref`
+/// to a `Counter`, but only changed by pushing, so that they always match the window.
+///
+/// ```rust
+/// # use counter::WindowedCounter;
+/// let mut recent = WindowedCounter::<_>::new(3);
+/// recent.update("aabcc".chars());
+/// assert_eq!(recent[&'a'], 0);
+/// assert_eq!(recent[&'c'], 2);
+/// assert_eq!(recent.most_common_ordered(), vec![('c', 2), ('b', 1)]);
+/// ```
+#[derive(Clone)]
+pub struct WindowedCounter<T: Hash + Eq, N = usize> {
+    window: VecDeque<T>,
+    len: usize,
+    counter: Counter<T, N>,
+}
+
+impl<T, N> WindowedCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty `WindowedCounter` of the last `len` items.
+    ///
+    /// Panics if `len` is zero.
+    pub fn new(len: usize) -> WindowedCounter<T, N> {
+        assert!(
+            len > 0,
+            "a WindowedCounter needs room for at least one item"
+        );
+        WindowedCounter {
+            window: VecDeque::with_capacity(len),
+            len,
+            counter: Counter::default(),
+        }
+    }
+
+    /// The number of items the window holds when full.
+    pub fn window_len(&self) -> usize {
+        self.len
+    }
+
+    /// Count `item`, returning the item which fell out of the window to make room for it, if
+    /// any.
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.window.len() == self.len {
+            self.window.pop_front()
+        } else {
+            None
+        };
+        if let Some(ref old) = evicted {
+            self.counter.subtract(iter::once(old.clone()));
+        }
+        self.counter.update(iter::once(item.clone()));
+        self.window.push_back(item);
+        evicted
+    }
+
+    /// Push each of the elements from the given iterable in turn
+    pub fn update<I>(&mut self, iterable: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iterable.into_iter() {
+            self.push(item);
+        }
+    }
+
+    /// Iterate over the items in the window, oldest first.
+    pub fn window(&self) -> impl Iterator<Item = &T> {
+        self.window.iter()
+    }
+
+    /// Empty the window and its counts.
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.counter.map.clear();
+    }
+
+    /// Move the counts of the window into a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, N> {
+        self.counter
+    }
+}
+
+impl<T, N> Deref for WindowedCounter<T, N>
+where
+    T: Hash + Eq,
+{
+    type Target = Counter<T, N>;
+
+    fn deref(&self) -> &Counter<T, N> {
+        &self.counter
+    }
+}
+
+/// A counter of the items pushed into it during the last `span` of time.
+///
+/// Each item is stamped with the time it was pushed at; items older than `span` are
+/// taken off the count as newer ones arrive, or on an explicit [`expire`](#method.expire).
+/// Like [`WindowedCounter`](struct.WindowedCounter.html), it derefs to the `Counter` of the
+/// window. Requires the `std` feature.
+///
+/// ```rust
+/// # use counter::TimeWindowedCounter;
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut last_minute = TimeWindowedCounter::<_>::new(Duration::from_secs(60));
+/// last_minute.push_at("/", start);
+/// last_minute.push_at("/about", start + Duration::from_secs(30));
+/// last_minute.push_at("/", start + Duration::from_secs(70));
+/// assert_eq!(last_minute[&"/"], 1);
+/// assert_eq!(last_minute[&"/about"], 1);
+/// last_minute.expire(start + Duration::from_secs(95));
+/// assert!(!last_minute.contains_key(&"/about"));
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub struct TimeWindowedCounter<T: Hash + Eq, N = usize> {
+    window: VecDeque<(Instant, T)>,
+    span: Duration,
+    counter: Counter<T, N>,
+}
+
+#[cfg(feature = "std")]
+impl<T, N> TimeWindowedCounter<T, N>
+where
+    T: Hash + Eq + Clone,
+    N: PartialOrd + AddAssign + SubAssign + Zero + One,
+{
+    /// Create a new, empty `TimeWindowedCounter` of the items pushed in the last `span`.
+    pub fn new(span: Duration) -> TimeWindowedCounter<T, N> {
+        TimeWindowedCounter {
+            window: VecDeque::new(),
+            span,
+            counter: Counter::default(),
+        }
+    }
+
+    /// The span of time the window covers.
+    pub fn span(&self) -> Duration {
+        self.span
+    }
+
+    /// Count `item` as pushed now.
+    pub fn push(&mut self, item: T) {
+        self.push_at(item, Instant::now());
+    }
+
+    /// Count `item` as pushed at `time`, then take off the items older than `span` before it.
+    ///
+    /// Times should not go backwards: an item is expired in the order it was pushed, so one
+    /// pushed with an earlier time than its predecessor stays until they have expired.
+    pub fn push_at(&mut self, item: T, time: Instant) {
+        self.counter.update(iter::once(item.clone()));
+        self.window.push_back((time, item));
+        self.expire(time);
+    }
+
+    /// Take the items pushed more than `span` before `now` off the count.
+    pub fn expire(&mut self, now: Instant) {
+        while let Some(&(time, _)) = self.window.front() {
+            if now.saturating_duration_since(time) <= self.span {
+                break;
+            }
+            if let Some((_, item)) = self.window.pop_front() {
+                self.counter.subtract(iter::once(item));
+            }
+        }
+    }
+
+    /// Empty the window and its counts.
+    pub fn clear(&mut self) {
+        self.window.clear();
+        self.counter.map.clear();
+    }
+
+    /// Move the counts of the window into a regular `Counter`.
+    pub fn into_counter(self) -> Counter<T, N> {
+        self.counter
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, N> Deref for TimeWindowedCounter<T, N>
+where
+    T: Hash + Eq,
+{
+    type Target = Counter<T, N>;
+
+    fn deref(&self) -> &Counter<T, N> {
+        &self.counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use compat::string::String;
+
+    #[test]
+    fn test_items_fall_out_of_the_window() {
+        let mut counter = WindowedCounter::<_>::new(2);
+        assert_eq!(counter.push('a'), None);
+        assert_eq!(counter.push('b'), None);
+        assert_eq!(counter.push('a'), Some('a'));
+        assert_eq!(counter.push('a'), Some('b'));
+        assert_eq!(counter.window().collect::<String>(), "aa");
+        assert!(counter.clone().into_counter().into_map() == hashmap!{'a' => 2});
+        counter.clear();
+        assert!(counter.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_time_window() {
+        let start = Instant::now();
+        let mut counter = TimeWindowedCounter::<_, u8>::new(Duration::from_secs(10));
+        for second in 0..30 {
+            counter.push_at(second % 3, start + Duration::from_secs(second));
+        }
+        // seconds 19 to 29 are within ten seconds of the last push
+        assert_eq!(counter.total(), 11);
+        assert_eq!(counter[&0], 3);
+        counter.expire(start + Duration::from_secs(100));
+        assert!(counter.is_empty());
+    }
+}